@@ -1,5 +1,14 @@
+use crate::file::encoding::parse_encoding;
+use crate::file::transform::TransformSpec;
+use crate::options::{
+    ClipboardBackend, OnError, PathStyle, SortOrder, DEFAULT_MAX_FILESIZE, DEFAULT_MAX_PART_SIZE,
+    DEFAULT_MAX_TOTAL_SIZE,
+};
+use crate::walk::modified::{parse_age, TimeReference};
 use clap::Parser;
+use encoding_rs::Encoding;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Combine multiple text files into a single prompt for a Large Language Model (LLM).
 #[derive(Parser, Debug, PartialEq)]
@@ -19,6 +28,12 @@ use std::path::PathBuf;
     >\x1b[1m quagga --template prompt.md --include '*.txt' \x1b[0m \n\n  \
     Include only files that contain the words 'todo' or 'fixthis', look in '~/code/myapp' directory:\n  \
     >\x1b[1m quagga --contain todo fixthis -- ~/code/myapp \x1b[0m \n\n  \
+    Include only files that use 'unsafe' but do not contain '// SAFETY:' comments:\n  \
+    >\x1b[1m quagga --contain-word unsafe --not-contain '// SAFETY:' \x1b[0m \n\n  \
+    Include files modified today, the most recently modified first:\n  \
+    >\x1b[1m quagga --modified-within 1d --sort mtime \x1b[0m \n\n  \
+    Put the README and the entry point first, followed by the largest files:\n  \
+    >\x1b[1m quagga --sort size --first 'README*' src/main.rs \x1b[0m \n\n  \
     Pipe file paths from another program:\n  \
     >\x1b[1m find . -name '*.txt' | quagga \x1b[0m \n\n  \
     Use a list of files from a text file:\n  \
@@ -37,20 +52,68 @@ pub struct Cli {
     #[arg(short = 'C', long, value_name = "TEXT", num_args(1..))]
     pub contain: Vec<String>,
 
+    /// Include only files that contain the specified whole words
+    #[arg(long, value_name = "WORD", num_args(1..))]
+    pub contain_word: Vec<String>,
+
+    /// Include only files that match the specified regular expressions
+    #[arg(long, value_name = "REGEX", num_args(1..))]
+    pub contain_regex: Vec<String>,
+
+    /// Include only files that contain all of the --contain, --contain-word and --contain-regex patterns instead of any
+    #[arg(long)]
+    pub contain_all: bool,
+
+    /// Exclude files that contain the specified text
+    #[arg(long, value_name = "TEXT", num_args(1..))]
+    pub not_contain: Vec<String>,
+
+    /// Ignore case in --contain, --contain-word, --contain-regex and --not-contain
+    #[arg(long)]
+    pub ignore_case: bool,
+
+    /// Include only the lines matching --contain, --contain-word and --contain-regex, with LINES of context around them
+    #[arg(long, value_name = "LINES")]
+    pub excerpt: Option<usize>,
+
+    /// Include only files modified within AGE, e.g. 30m, 12h, 2d or 1w
+    #[arg(long, value_name = "AGE", value_parser = parse_age)]
+    pub modified_within: Option<Duration>,
+
+    /// Include only files modified after TIME: a date (2025-05-12, 2025-05-12 14:30), an age (2d) or a path to a file
+    #[arg(long, value_name = "TIME")]
+    pub newer_than: Option<TimeReference>,
+
+    /// Include only files modified before TIME: a date, an age or a path to a file
+    #[arg(long, value_name = "TIME")]
+    pub older_than: Option<TimeReference>,
+
+    /// The order of the files in the prompt [default: path, or the order of piped paths]
+    #[arg(long, value_name = "ORDER", value_enum)]
+    pub sort: Option<SortOrder>,
+
+    /// Reverse the order of the files
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Put the files matching the glob patterns first, in the order of the patterns (e.g., 'README*' src/main.rs)
+    #[arg(long, value_name = "PATTERN", num_args(1..))]
+    pub first: Vec<String>,
+
     /// Descend only DEPTH directories deep
     #[arg(short = 'd', long, value_name = "DEPTH")]
     pub max_depth: Option<usize>,
 
     /// Output is split into parts of this size if it exceeds this limit
-    #[arg(short = 'p', long, value_name = "CHARS", default_value_t = 100_000)]
+    #[arg(short = 'p', long, value_name = "CHARS", default_value_t = DEFAULT_MAX_PART_SIZE)]
     pub max_part_size: u64,
 
     /// Ignore files above the specified size
-    #[arg(short = 'f', long, value_name = "BYTES", default_value_t = 300 * 1024)]
+    #[arg(short = 'f', long, value_name = "BYTES", default_value_t = DEFAULT_MAX_FILESIZE)]
     pub max_filesize: u64,
 
     /// Show error if total size of files is over the specified size
-    #[arg(short = 's', long, value_name = "BYTES", default_value_t = 500*1024)]
+    #[arg(short = 's', long, value_name = "BYTES", default_value_t = DEFAULT_MAX_TOTAL_SIZE)]
     pub max_total_size: u64,
 
     /// Don't use .gitignore files (used by default)
@@ -65,6 +128,26 @@ pub struct Cli {
     #[arg(short = 'B', long)]
     pub binary: bool,
 
+    /// Read files in this encoding (e.g., utf-16le, windows-1252, shift_jis) instead of detecting it
+    #[arg(long, value_name = "ENCODING", value_parser = parse_encoding)]
+    pub encoding: Option<&'static Encoding>,
+
+    /// Include the text outputs of Jupyter notebook cells, truncated to CHARS characters (left out by default)
+    #[arg(long, value_name = "CHARS")]
+    pub notebook_outputs: Option<usize>,
+
+    /// Skip generated files and lock files (default)
+    #[arg(long, overrides_with = "include_generated")]
+    pub skip_generated: bool,
+
+    /// Skip minified files, source maps and encoded data such as base64 (default)
+    #[arg(long)]
+    pub skip_minified: bool,
+
+    /// Include generated, minified and lock files (skipped by default)
+    #[arg(long, overrides_with = "skip_generated")]
+    pub include_generated: bool,
+
     /// Include hidden files (ignored by default)
     #[arg(short = 'H', long)]
     pub hidden: bool,
@@ -85,14 +168,65 @@ pub struct Cli {
     #[arg(short = 'n', long)]
     pub no_quagga_template: bool,
 
+    /// How to show the paths to files: relative to the root, absolute, starting with
+    /// the root directory name, or starting with "project" instead of it
+    #[arg(long, value_name = "STYLE", value_enum, default_value_t = PathStyle::Relative)]
+    pub path_style: PathStyle,
+
     /// Output to a file instead of stdout
-    #[arg(short = 'o', long, value_name = "PATH")]
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "PATH",
+        conflicts_with_all = ["clipboard", "send", "exec"]
+    )]
     pub output: Option<PathBuf>,
 
+    /// Also write PATH.manifest.json with --output, listing the files, line ranges,
+    /// sizes and SHA-256 hashes of the parts, the template and the command line
+    #[arg(
+        long,
+        requires = "output",
+        conflicts_with_all = ["exec", "clipboard", "send", "paths", "tree", "file_sizes", "size"]
+    )]
+    pub manifest: bool,
+
     /// Copy the output to the clipboard instead of stdout
-    #[arg(short = 'c', long)]
+    #[arg(short = 'c', long, conflicts_with_all = ["send", "exec"])]
     pub clipboard: bool,
 
+    /// The clipboard to copy to with --clipboard: the system clipboard, or OSC 52 escape
+    /// sequences of the terminal that work over SSH (auto uses OSC 52 when there is no display server)
+    #[arg(long, value_name = "BACKEND", value_enum, default_value_t = ClipboardBackend::Auto)]
+    pub clipboard_backend: ClipboardBackend,
+
+    /// Copy part N of the output last copied with --clipboard --cache again, without combining the files.
+    /// The parts are saved only with --cache
+    #[arg(long, value_name = "N")]
+    pub clipboard_part: Option<usize>,
+
+    /// Send the prompt to an OpenAI-compatible chat completions endpoint
+    /// (e.g., http://localhost:8080) and print the reply instead of the prompt
+    #[arg(long, value_name = "URL", conflicts_with = "exec")]
+    pub send: Option<String>,
+
+    /// The model the prompt is sent to with --send
+    #[arg(long, value_name = "NAME", requires = "send")]
+    pub model: Option<String>,
+
+    /// The environment variable with the API key for --send (e.g., OPENAI_API_KEY)
+    #[arg(long, value_name = "VAR", requires = "send")]
+    pub api_key_env: Option<String>,
+
+    /// The system message sent before the prompt with --send
+    #[arg(long, value_name = "TEXT", requires = "send")]
+    pub system: Option<String>,
+
+    /// Run the shell command for each part, with the part on stdin and its number in
+    /// QUAGGA_PART and QUAGGA_TOTAL_PARTS environment variables (e.g., 'wl-copy')
+    #[arg(long, value_name = "COMMAND")]
+    pub exec: Option<String>,
+
     /// Show paths to files without combining them
     #[arg(short = 'a', long)]
     pub paths: bool,
@@ -113,6 +247,69 @@ pub struct Cli {
     #[arg(short = 'r', long)]
     pub remove_comments: bool,
 
+    /// Replace function bodies with { ... } in Rust, JavaScript, TypeScript, Python, Go and Java files
+    #[arg(long)]
+    pub outline: bool,
+
+    /// Outline only the files matching the glob patterns (e.g., 'vendor/**')
+    #[arg(long, value_name = "PATTERN", num_args(1..))]
+    pub outline_glob: Vec<String>,
+
+    /// Remove license headers (e.g., SPDX, Apache, MIT, GPL) and the comment blocks that many files start with
+    #[arg(long)]
+    pub strip_license_headers: bool,
+
+    /// Remove trailing whitespace and collapse runs of blank lines, keeping the whitespace
+    /// that is part of the syntax of Python, YAML, Markdown and makefiles, and of multiline
+    /// strings in Rust, JavaScript, TypeScript, Go, Java and Python (strings in other
+    /// languages are compacted too)
+    #[arg(long)]
+    pub compact: bool,
+
+    /// With --compact, replace each WIDTH spaces of indentation with a tab, except in Python, YAML and makefiles
+    #[arg(long, value_name = "WIDTH", requires = "compact")]
+    pub compact_tabs: Option<usize>,
+
+    /// Apply transforms to the files in order, optionally only to the files matching a glob
+    /// (e.g., collapse-blank-lines 'tabs-to-spaces=2:*.go'). Transforms: remove-comments,
+    /// collapse-blank-lines, trim-trailing-whitespace, strip-license-header,
+    /// tabs-to-spaces[=WIDTH], truncate-lines[=CHARS], redact, outline, compact[=TAB_WIDTH]
+    #[arg(long, value_name = "NAME[=VALUE][:GLOB]", num_args(1..))]
+    pub transform: Vec<TransformSpec>,
+
+    /// Print the number of characters saved by each transform to stderr
+    #[arg(long)]
+    pub transform_report: bool,
+
+    /// Replace secrets, such as private keys and tokens, with [REDACTED:kind] (default)
+    #[arg(long, overrides_with = "no_redact")]
+    pub redact: bool,
+
+    /// Don't replace secrets in the files
+    #[arg(long, overrides_with = "redact")]
+    pub no_redact: bool,
+
+    /// Print the file and line of each redacted secret to stderr
+    #[arg(long)]
+    pub redact_report: bool,
+
+    /// What to do when a file can not be read: skip it, skip it with a warning, or stop
+    #[arg(short = 'e', long, value_name = "ACTION", value_enum, default_value_t = OnError::Warn)]
+    pub on_error: OnError,
+
+    /// Cache binary detection, file classes and comment removal on disk to speed up repeated runs,
+    /// and save the parts copied with --clipboard for --clipboard-part
+    #[arg(long, overrides_with = "no_cache")]
+    pub cache: bool,
+
+    /// Don't use the on-disk cache (default)
+    #[arg(long, overrides_with = "cache")]
+    pub no_cache: bool,
+
+    /// Remove all entries from the on-disk cache
+    #[arg(long)]
+    pub clear_cache: bool,
+
     /// The root directory to search for files
     #[arg(value_name = "DIRECTORY", default_value = ".")]
     pub root: PathBuf,
@@ -121,28 +318,55 @@ pub struct Cli {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::file::transform::TransformKind;
     use clap::Parser;
 
     #[test]
     fn test_default_values() {
-        let args = Cli::parse_from(&["quagga"]);
+        let args = Cli::parse_from(["quagga"]);
         assert_eq!(
             args,
             Cli {
                 include: Vec::new(),
                 exclude: Vec::new(),
                 contain: Vec::new(),
+                contain_word: Vec::new(),
+                contain_regex: Vec::new(),
+                contain_all: false,
+                not_contain: Vec::new(),
+                ignore_case: false,
+                excerpt: None,
+                modified_within: None,
+                newer_than: None,
+                older_than: None,
+                sort: None,
+                reverse: false,
+                first: Vec::new(),
                 max_depth: None,
                 no_gitignore: false,
                 no_quagga_ignore: false,
                 binary: false,
+                encoding: None,
+                notebook_outputs: None,
+                skip_generated: false,
+                skip_minified: false,
+                include_generated: false,
                 hidden: false,
                 follow_links: false,
                 template: None,
                 copy_template: false,
                 no_quagga_template: false,
+                path_style: PathStyle::Relative,
                 output: None,
+                manifest: false,
                 clipboard: false,
+                clipboard_backend: ClipboardBackend::Auto,
+                clipboard_part: None,
+                send: None,
+                model: None,
+                api_key_env: None,
+                system: None,
+                exec: None,
                 paths: false,
                 file_sizes: false,
                 tree: false,
@@ -152,6 +376,20 @@ mod tests {
                 root: PathBuf::from("."),
                 size: false,
                 remove_comments: false,
+                outline: false,
+                outline_glob: Vec::new(),
+                strip_license_headers: false,
+                compact: false,
+                compact_tabs: None,
+                transform: Vec::new(),
+                transform_report: false,
+                redact: false,
+                no_redact: false,
+                redact_report: false,
+                on_error: OnError::Warn,
+                cache: false,
+                no_cache: false,
+                clear_cache: false,
             }
         );
     }
@@ -176,7 +414,7 @@ mod tests {
 
     #[test]
     fn test_contain() {
-        let args = Cli::parse_from(vec!["quagga", "--contain", "hello world", "hi"].iter());
+        let args = Cli::parse_from(["quagga", "--contain", "hello world", "hi"].iter());
 
         assert_eq!(args.contain, vec!("hello world", "hi"));
         assert_eq!(args.root, PathBuf::from("."));
@@ -188,17 +426,35 @@ mod tests {
           --include *.js \
           --exclude node_modules \
           --contain hello \
+          --contain-word world \
+          --contain-regex ^fn \
+          --contain-all \
+          --not-contain bye \
+          --ignore-case \
+          --excerpt 3 \
+          --modified-within 2d \
+          --newer-than 2025-05-12 \
+          --older-than Cargo.toml \
+          --sort mtime \
+          --reverse \
+          --first README* \
           --max-depth 2 \
           --no-gitignore \
           --no-quagga-ignore \
           --binary \
+          --encoding latin1 \
+          --notebook-outputs 500 \
+          --include-generated \
+          --skip-minified \
           --hidden \
           --follow-links \
           --template template.txt \
           --copy-template \
           --no-quagga-template \
+          --path-style anonymized \
           --output output.txt \
-          --clipboard \
+          --clipboard-backend system \
+          --clipboard-part 3 \
           --paths \
           --tree \
           --size \
@@ -207,6 +463,18 @@ mod tests {
           --max-filesize 10000 \
           --max-total-size 20000 \
           --remove-comments \
+          --outline \
+          --outline-glob vendor/** \
+          --strip-license-headers \
+          --compact \
+          --compact-tabs 4 \
+          --transform trim-trailing-whitespace truncate-lines=100:*.json \
+          --transform-report \
+          --no-redact \
+          --redact-report \
+          --on-error skip \
+          --cache \
+          --clear-cache \
           src";
 
         let args = Cli::parse_from(cmd.split_whitespace());
@@ -217,17 +485,43 @@ mod tests {
                 include: vec!["*.js".to_string()],
                 exclude: vec!["node_modules".to_string()],
                 contain: vec!("hello".to_string()),
+                contain_word: vec!("world".to_string()),
+                contain_regex: vec!("^fn".to_string()),
+                contain_all: true,
+                not_contain: vec!("bye".to_string()),
+                ignore_case: true,
+                excerpt: Some(3),
+                modified_within: Some(Duration::from_secs(2 * 24 * 60 * 60)),
+                newer_than: Some("2025-05-12".parse().unwrap()),
+                older_than: Some(TimeReference::File(PathBuf::from("Cargo.toml"))),
+                sort: Some(SortOrder::Mtime),
+                reverse: true,
+                first: vec!["README*".to_string()],
                 max_depth: Some(2),
                 no_gitignore: true,
                 no_quagga_ignore: true,
                 binary: true,
+                encoding: Some(encoding_rs::WINDOWS_1252),
+                notebook_outputs: Some(500),
+                skip_generated: false,
+                skip_minified: true,
+                include_generated: true,
                 hidden: true,
                 follow_links: true,
                 template: Some(PathBuf::from("template.txt")),
                 copy_template: true,
                 no_quagga_template: true,
+                path_style: PathStyle::Anonymized,
                 output: Some(PathBuf::from("output.txt")),
-                clipboard: true,
+                manifest: false,
+                clipboard: false,
+                clipboard_backend: ClipboardBackend::System,
+                clipboard_part: Some(3),
+                send: None,
+                model: None,
+                api_key_env: None,
+                system: None,
+                exec: None,
                 paths: true,
                 tree: true,
                 max_part_size: 300,
@@ -237,7 +531,139 @@ mod tests {
                 size: true,
                 file_sizes: true,
                 remove_comments: true,
+                outline: true,
+                outline_glob: vec!["vendor/**".to_string()],
+                strip_license_headers: true,
+                compact: true,
+                compact_tabs: Some(4),
+                transform: vec![
+                    TransformSpec {
+                        kind: TransformKind::TrimTrailingWhitespace,
+                        glob: None,
+                    },
+                    TransformSpec {
+                        kind: TransformKind::TruncateLines(100),
+                        glob: Some("*.json".to_string()),
+                    },
+                ],
+                transform_report: true,
+                redact: false,
+                no_redact: true,
+                redact_report: true,
+                on_error: OnError::Skip,
+                cache: true,
+                no_cache: false,
+                clear_cache: true,
             }
         );
     }
+
+    #[test]
+    fn test_sort_values() {
+        let args = Cli::parse_from(["quagga", "--sort", "git-recency"]);
+        assert_eq!(args.sort, Some(SortOrder::GitRecency));
+
+        let args = Cli::parse_from(["quagga", "--sort", "none"]);
+        assert_eq!(args.sort, Some(SortOrder::Unsorted));
+    }
+
+    #[test]
+    fn test_invalid_encoding() {
+        let result = Cli::try_parse_from(["quagga", "--encoding", "klingon"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_invalid_modified_within() {
+        let result = Cli::try_parse_from(["quagga", "--modified-within", "2 days"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_no_cache_overrides_cache() {
+        let args = Cli::parse_from(["quagga", "--cache", "--no-cache"]);
+        assert!(!args.cache);
+        assert!(args.no_cache);
+
+        let args = Cli::parse_from(["quagga", "--no-cache", "--cache"]);
+        assert!(args.cache);
+        assert!(!args.no_cache);
+    }
+
+    #[test]
+    fn test_no_redact_overrides_redact() {
+        let args = Cli::parse_from(["quagga", "--redact", "--no-redact"]);
+        assert!(!args.redact);
+        assert!(args.no_redact);
+
+        let args = Cli::parse_from(["quagga", "--no-redact", "--redact"]);
+        assert!(args.redact);
+        assert!(!args.no_redact);
+    }
+
+    #[test]
+    fn test_output_destinations() {
+        let args = Cli::parse_from([
+            "quagga",
+            "--send",
+            "http://localhost:8080",
+            "--model",
+            "llama3",
+            "--api-key-env",
+            "LLM_API_KEY",
+        ]);
+        assert_eq!(args.send, Some("http://localhost:8080".to_string()));
+        assert_eq!(args.model, Some("llama3".to_string()));
+        assert_eq!(args.api_key_env, Some("LLM_API_KEY".to_string()));
+
+        assert!(Cli::parse_from(["quagga", "--clipboard"]).clipboard);
+        assert_eq!(
+            Cli::parse_from(["quagga", "--exec", "wc"]).exec,
+            Some("wc".to_string())
+        );
+
+        // The prompt goes to one place only
+        let destinations = [
+            "--output=prompt.txt",
+            "--clipboard",
+            "--send=http://localhost:8080",
+            "--exec=wc",
+        ];
+
+        for (i, first) in destinations.iter().enumerate() {
+            for second in &destinations[i + 1..] {
+                let result = Cli::try_parse_from(["quagga", first, second]);
+
+                assert!(result.is_err(), "{} should conflict with {}", first, second);
+            }
+        }
+    }
+
+    #[test]
+    fn test_manifest() {
+        let args = Cli::parse_from(["quagga", "--output", "prompt.txt", "--manifest"]);
+        assert!(args.manifest);
+
+        // The manifest describes the files written with --output
+        assert!(Cli::try_parse_from(["quagga", "--manifest"]).is_err());
+
+        for option in [
+            "--exec=wc",
+            "--clipboard",
+            "--send=http://localhost:8080",
+            "--paths",
+            "--tree",
+            "--file-sizes",
+            "--size",
+        ] {
+            let result =
+                Cli::try_parse_from(["quagga", "--output", "prompt.txt", "--manifest", option]);
+
+            assert!(
+                result.is_err(),
+                "{} should conflict with --manifest",
+                option
+            );
+        }
+    }
 }
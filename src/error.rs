@@ -0,0 +1,309 @@
+use crate::file::size::human_readable_size;
+use crate::file::skipped_file::{format_skipped_files, SkippedFile};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Errors returned by `quagga`.
+#[derive(Debug)]
+pub enum QuaggaError {
+    /// There are no files to include in the prompt.
+    /// `skipped` lists the files that were left out because they could not be read.
+    NoFiles { skipped: Vec<SkippedFile> },
+
+    /// The total size of the files (in bytes) is above the `max_total_size` limit.
+    TotalSizeExceeded { total: u64, limit: u64 },
+
+    /// A file could not be opened or read.
+    FileRead { path: PathBuf, source: io::Error },
+
+    /// The metadata of a file could not be read.
+    FileMetadata { path: PathBuf, source: io::Error },
+
+    /// The path does not point to a regular file.
+    NotAFile { path: PathBuf },
+
+    /// An error occurred while walking the directory tree,
+    /// or an include/exclude pattern is invalid.
+    Walk(ignore::Error),
+
+    /// The template file could not be read.
+    TemplateRead { path: PathBuf, source: io::Error },
+
+    /// The template could not be parsed because of the `tag` on the given line.
+    TemplateParse {
+        tag: String,
+        line: usize,
+        kind: TemplateParseErrorKind,
+    },
+
+    /// The `.quagga_template` file being created already exists.
+    TemplateExists { path: PathBuf },
+
+    /// The output could not be written to a file.
+    Output { path: PathBuf, source: io::Error },
+
+    /// The output could not be copied to the clipboard.
+    Clipboard(String),
+
+    /// The cache directory could not be removed.
+    CacheClear { path: PathBuf, source: io::Error },
+
+    /// A `--contain-regex` or `.quagga_redact` pattern is not a valid regular expression.
+    InvalidRegex { pattern: String, message: String },
+
+    /// The prompt could not be sent to the chat completions endpoint at `url` with `--send`,
+    /// or the endpoint returned an error.
+    Send { url: String, message: String },
+
+    /// The `--exec` command could not be run for the part with the given number,
+    /// or it exited with a non-zero status.
+    Exec {
+        command: String,
+        part: usize,
+        message: String,
+    },
+}
+
+/// The reason a template tag could not be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateParseErrorKind {
+    /// The opening tag (e.g. `<prompt>`) is missing.
+    OpeningTagNotFound,
+
+    /// The closing tag (e.g. `</prompt>`) is missing.
+    ClosingTagNotFound,
+
+    /// The closing tag comes before the opening tag.
+    ClosingTagBeforeOpening,
+}
+
+impl QuaggaError {
+    /// Returns the process exit code for the error. Each kind of error has its own code,
+    /// code 1 is reserved for other errors and 2 for invalid command line arguments.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            QuaggaError::NoFiles { .. } => 3,
+            QuaggaError::TotalSizeExceeded { .. } => 4,
+            QuaggaError::FileRead { .. } => 5,
+            QuaggaError::FileMetadata { .. } => 6,
+            QuaggaError::NotAFile { .. } => 7,
+            QuaggaError::Walk(_) => 8,
+            QuaggaError::TemplateRead { .. } => 9,
+            QuaggaError::TemplateParse { .. } => 10,
+            QuaggaError::TemplateExists { .. } => 11,
+            QuaggaError::Output { .. } => 12,
+            QuaggaError::Clipboard(_) => 13,
+            QuaggaError::CacheClear { .. } => 14,
+            QuaggaError::InvalidRegex { .. } => 15,
+            QuaggaError::Send { .. } => 16,
+            QuaggaError::Exec { .. } => 17,
+        }
+    }
+}
+
+impl fmt::Display for QuaggaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuaggaError::NoFiles { skipped } => {
+                write!(f, "No files to process")?;
+
+                if !skipped.is_empty() {
+                    write!(f, "\nSkipped files:\n{}", format_skipped_files(skipped))?;
+                }
+
+                Ok(())
+            }
+            QuaggaError::TotalSizeExceeded { total, limit } => write!(
+                f,
+                r#"Total size of files ({}) exceeds the maximum allowed size ({}).
+Use --max-total-size=BYTES option to increase the limit.
+"#,
+                human_readable_size(*total),
+                human_readable_size(*limit)
+            ),
+            QuaggaError::FileRead { path, source } => {
+                write!(f, "Failed to read file {}: {}", path.display(), source)
+            }
+            QuaggaError::FileMetadata { path, source } => write!(
+                f,
+                "Failed to read metadata for file {}: {}",
+                path.display(),
+                source
+            ),
+            QuaggaError::NotAFile { path } => {
+                write!(f, "Path is not a regular file: {}", path.display())
+            }
+            QuaggaError::Walk(source) => write!(f, "{}", source),
+            QuaggaError::TemplateRead { path, source } => write!(
+                f,
+                "Failed to read template from '{}': {}",
+                path.display(),
+                source
+            ),
+            QuaggaError::TemplateParse { tag, line, kind } => {
+                let message = match kind {
+                    TemplateParseErrorKind::OpeningTagNotFound => {
+                        format!("Opening tag <{}> not found in the provided text.", tag)
+                    }
+                    TemplateParseErrorKind::ClosingTagNotFound => {
+                        format!("Closing tag </{}> not found in the provided text.", tag)
+                    }
+                    TemplateParseErrorKind::ClosingTagBeforeOpening => {
+                        format!("Closing tag </{}> found before opening tag <{}>.", tag, tag)
+                    }
+                };
+
+                write!(f, "Template error on line {}: {}", line, message)
+            }
+            QuaggaError::TemplateExists { path } => {
+                write!(f, "Template file '{}' already exists.", path.display())
+            }
+            QuaggaError::Output { path, source } => {
+                write!(
+                    f,
+                    "Failed to write output to {}: {}",
+                    path.display(),
+                    source
+                )
+            }
+            QuaggaError::Clipboard(message) => {
+                write!(f, "Failed to copy to clipboard: {}", message)
+            }
+            QuaggaError::CacheClear { path, source } => {
+                write!(f, "Failed to clear cache at {}: {}", path.display(), source)
+            }
+            QuaggaError::InvalidRegex { pattern, message } => {
+                write!(f, "Invalid regular expression '{}': {}", pattern, message)
+            }
+            QuaggaError::Send { url, message } => {
+                write!(f, "Failed to send the prompt to {}: {}", url, message)
+            }
+            QuaggaError::Exec {
+                command,
+                part,
+                message,
+            } => write!(
+                f,
+                "Command '{}' failed on part {}: {}",
+                command, part, message
+            ),
+        }
+    }
+}
+
+impl Error for QuaggaError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            QuaggaError::FileRead { source, .. }
+            | QuaggaError::FileMetadata { source, .. }
+            | QuaggaError::TemplateRead { source, .. }
+            | QuaggaError::Output { source, .. }
+            | QuaggaError::CacheClear { source, .. } => Some(source),
+            QuaggaError::Walk(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<ignore::Error> for QuaggaError {
+    fn from(error: ignore::Error) -> Self {
+        QuaggaError::Walk(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            QuaggaError::NoFiles { skipped: vec![] },
+            QuaggaError::TotalSizeExceeded { total: 2, limit: 1 },
+            QuaggaError::FileRead {
+                path: PathBuf::from("a"),
+                source: io::Error::other("e"),
+            },
+            QuaggaError::FileMetadata {
+                path: PathBuf::from("a"),
+                source: io::Error::other("e"),
+            },
+            QuaggaError::NotAFile {
+                path: PathBuf::from("a"),
+            },
+            QuaggaError::Walk(ignore::Error::Glob {
+                glob: None,
+                err: "e".to_string(),
+            }),
+            QuaggaError::TemplateRead {
+                path: PathBuf::from("a"),
+                source: io::Error::other("e"),
+            },
+            QuaggaError::TemplateParse {
+                tag: "prompt".to_string(),
+                line: 1,
+                kind: TemplateParseErrorKind::OpeningTagNotFound,
+            },
+            QuaggaError::TemplateExists {
+                path: PathBuf::from("a"),
+            },
+            QuaggaError::Output {
+                path: PathBuf::from("a"),
+                source: io::Error::other("e"),
+            },
+            QuaggaError::Clipboard("e".to_string()),
+            QuaggaError::CacheClear {
+                path: PathBuf::from("a"),
+                source: io::Error::other("e"),
+            },
+            QuaggaError::InvalidRegex {
+                pattern: "(".to_string(),
+                message: "e".to_string(),
+            },
+            QuaggaError::Send {
+                url: "http://localhost".to_string(),
+                message: "e".to_string(),
+            },
+            QuaggaError::Exec {
+                command: "cat".to_string(),
+                part: 1,
+                message: "e".to_string(),
+            },
+        ];
+
+        let mut codes: Vec<i32> = errors.iter().map(|e| e.exit_code()).collect();
+        codes.sort();
+        codes.dedup();
+
+        assert_eq!(codes.len(), errors.len());
+        assert!(codes.iter().all(|code| *code > 2));
+    }
+
+    #[test]
+    fn test_display_total_size_exceeded() {
+        let error = QuaggaError::TotalSizeExceeded {
+            total: 15,
+            limit: 10,
+        };
+
+        assert!(error
+            .to_string()
+            .contains("Total size of files (15 B) exceeds the maximum allowed size (10 B)."));
+    }
+
+    #[test]
+    fn test_display_template_parse() {
+        let error = QuaggaError::TemplateParse {
+            tag: "file".to_string(),
+            line: 4,
+            kind: TemplateParseErrorKind::ClosingTagBeforeOpening,
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Template error on line 4: Closing tag </file> found before opening tag <file>."
+        );
+    }
+}
@@ -0,0 +1,663 @@
+use chrono::{Local, NaiveDate, TimeZone};
+use flate2::read::GzDecoder;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use zip::ZipArchive;
+
+/// Separates the path to the archive from the path of a file inside it,
+/// e.g. `project.zip!/src/main.rs`.
+pub const ENTRY_SEPARATOR: &str = "!/";
+
+/// The supported archive formats, detected by the file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveFormat {
+    /// `.zip`
+    Zip,
+
+    /// `.tar`
+    Tar,
+
+    /// `.tar.gz` or `.tgz`
+    TarGz,
+}
+
+impl ArchiveFormat {
+    /// Returns the format of the archive from the file extension, or `None` if the path
+    /// is not an archive.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_string_lossy().to_lowercase();
+
+        if name.ends_with(".zip") {
+            Some(ArchiveFormat::Zip)
+        } else if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Some(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar") {
+            Some(ArchiveFormat::Tar)
+        } else {
+            None
+        }
+    }
+}
+
+/// A regular file inside an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// The path of the file inside the archive, e.g. `src/main.rs`.
+    pub name: String,
+
+    /// The uncompressed size of the file in bytes.
+    pub size: u64,
+
+    /// The modification time of the file, if it is stored in the archive.
+    pub modified: Option<SystemTime>,
+}
+
+/// Checks if the path is an existing archive file in one of the supported formats.
+pub fn is_archive(path: &Path) -> bool {
+    ArchiveFormat::from_path(path).is_some() && path.is_file()
+}
+
+/// Returns the path used for a file inside the archive, e.g. `project.zip!/src/main.rs`.
+///
+/// # Arguments
+///
+/// * `archive` - The path to the archive.
+/// * `name` - The path of the file inside the archive.
+pub fn entry_path(archive: &Path, name: &str) -> PathBuf {
+    let mut path = OsString::from(archive.as_os_str());
+    path.push(ENTRY_SEPARATOR);
+    path.push(name);
+    PathBuf::from(path)
+}
+
+/// Splits the path of a file inside an archive into the path to the archive
+/// and the path of the file inside it.
+///
+/// # Returns
+///
+/// `Some((archive, name))` for paths like `project.zip!/src/main.rs`,
+/// or `None` for paths to regular files.
+pub fn split_entry_path(path: &Path) -> Option<(PathBuf, String)> {
+    let text = path.to_str()?;
+
+    text.match_indices(ENTRY_SEPARATOR).find_map(|(index, _)| {
+        let archive = Path::new(&text[..index]);
+        ArchiveFormat::from_path(archive)?;
+        let name = &text[index + ENTRY_SEPARATOR.len()..];
+        Some((archive.to_path_buf(), name.to_string()))
+    })
+}
+
+/// Keeps the listings and the contents of the archives read while generating a prompt,
+/// so that each archive is read once, although the sizes and contents of the files in it
+/// are looked up one file at a time. Each `Quagga` has its own cache, which is passed
+/// along with the options. The clones of the cache share the same archives.
+#[derive(Clone, Default)]
+pub struct ArchiveCache {
+    archives: Arc<Mutex<HashMap<PathBuf, CachedArchive>>>,
+}
+
+/// The listing of a version of an archive and the contents of the files read from it.
+struct CachedArchive {
+    key: ArchiveKey,
+    entries: Arc<Vec<ArchiveEntry>>,
+    contents: HashMap<String, Arc<[u8]>>,
+}
+
+impl ArchiveCache {
+    /// Lists the regular files in the archive. Directories, links and other special entries
+    /// are left out.
+    ///
+    /// # Returns
+    ///
+    /// The files in the order they are stored in the archive, or an error if the archive
+    /// can not be read.
+    pub fn list_entries(&self, archive: &Path) -> io::Result<Arc<Vec<ArchiveEntry>>> {
+        let mut archives = self.archives.lock().unwrap();
+        let cached = cached_archive(&mut archives, archive)?;
+        Ok(Arc::clone(&cached.entries))
+    }
+
+    /// Returns the information about a single file in the archive.
+    ///
+    /// # Returns
+    ///
+    /// The entry, or an error with `io::ErrorKind::NotFound` if there is no such file in the archive.
+    pub fn find_entry(&self, archive: &Path, name: &str) -> io::Result<ArchiveEntry> {
+        self.list_entries(archive)?
+            .iter()
+            .find(|entry| entry.name == name)
+            .cloned()
+            .ok_or_else(|| entry_not_found(name))
+    }
+
+    /// Reads the contents of a single file in the archive. The archive is read only if
+    /// the file was not read before, e.g. by `read_entries`.
+    ///
+    /// # Returns
+    ///
+    /// The bytes of the file, or an error if the archive can not be read, there is no such file
+    /// or the file is larger than the size stored for it in the archive.
+    pub fn read_entry(&self, archive: &Path, name: &str) -> io::Result<Arc<[u8]>> {
+        let entry = self.find_entry(archive, name)?;
+        let mut contents = self.read_entries(archive, &HashSet::from([name]), |_| entry.size)?;
+        contents.remove(name).ok_or_else(|| entry_too_large(name))
+    }
+
+    /// Reads the contents of the files with the given names, reading the archive once
+    /// and keeping the contents for later calls. Only the files that were not read
+    /// before are read from the archive, and at most `max_size` bytes are read from each file.
+    ///
+    /// # Returns
+    ///
+    /// The contents of the files by their names. The names of files that are not
+    /// in the archive or are larger than `max_size` are left out.
+    pub fn read_entries<F>(
+        &self,
+        archive: &Path,
+        names: &HashSet<&str>,
+        max_size: F,
+    ) -> io::Result<HashMap<String, Arc<[u8]>>>
+    where
+        F: Fn(&ArchiveEntry) -> u64,
+    {
+        let mut archives = self.archives.lock().unwrap();
+        let cached = cached_archive(&mut archives, archive)?;
+
+        let missing: HashMap<&str, u64> = cached
+            .entries
+            .iter()
+            .filter(|entry| {
+                names.contains(entry.name.as_str()) && !cached.contents.contains_key(&entry.name)
+            })
+            .map(|entry| (entry.name.as_str(), max_size(entry)))
+            .collect();
+
+        if !missing.is_empty() {
+            for (name, bytes) in read_entries(archive, &missing)? {
+                cached.contents.insert(name, bytes.into());
+            }
+        }
+
+        Ok(names
+            .iter()
+            .filter_map(|name| {
+                let bytes = cached.contents.get(*name)?;
+                Some((name.to_string(), Arc::clone(bytes)))
+            })
+            .collect())
+    }
+
+    /// Drops the kept contents of the files in the archive for which `keep` returns false,
+    /// e.g. the files that are left out of the prompt.
+    pub fn retain_contents<F>(&self, archive: &Path, keep: F)
+    where
+        F: Fn(&str) -> bool,
+    {
+        let Ok(path) = fs::canonicalize(archive) else {
+            return;
+        };
+
+        if let Some(cached) = self.archives.lock().unwrap().get_mut(&path) {
+            cached.contents.retain(|name, _| keep(name));
+        }
+    }
+}
+
+impl fmt::Debug for ArchiveCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ArchiveCache").finish_non_exhaustive()
+    }
+}
+
+/// Returns the cached archive, reading its listing if the archive has not been read yet
+/// or has changed since.
+fn cached_archive<'a>(
+    archives: &'a mut HashMap<PathBuf, CachedArchive>,
+    archive: &Path,
+) -> io::Result<&'a mut CachedArchive> {
+    let key = ArchiveKey::new(archive)?;
+
+    if archives
+        .get(&key.path)
+        .is_some_and(|cached| cached.key != key)
+    {
+        archives.remove(&key.path);
+    }
+
+    match archives.entry(key.path.clone()) {
+        Entry::Occupied(entry) => Ok(entry.into_mut()),
+        Entry::Vacant(entry) => Ok(entry.insert(CachedArchive {
+            entries: Arc::new(read_listing(archive)?),
+            contents: HashMap::new(),
+            key,
+        })),
+    }
+}
+
+/// Reads the contents of the files with the given names from the archive.
+/// A tar archive is read only until all the files are found.
+///
+/// # Returns
+///
+/// The contents of the files by their names.
+fn read_entries(
+    archive: &Path,
+    names: &HashMap<&str, u64>,
+) -> io::Result<HashMap<String, Vec<u8>>> {
+    let mut contents = HashMap::new();
+
+    match ArchiveFormat::from_path(archive) {
+        Some(ArchiveFormat::Zip) => {
+            let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+
+            for (&name, &max_size) in names {
+                if let Some(bytes) = read_limited(zip.by_name(name)?, max_size)? {
+                    contents.insert(name.to_string(), bytes);
+                }
+            }
+        }
+        Some(format) => {
+            let mut tar = tar::Archive::new(open_tar(archive, format)?);
+            let mut found = HashSet::new();
+
+            for entry in tar.entries()? {
+                if found.len() == names.len() {
+                    break;
+                }
+
+                let entry = entry?;
+
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+
+                let name = normalize_tar_name(&entry.path()?);
+
+                let Some(&max_size) = names.get(name.as_str()) else {
+                    continue;
+                };
+
+                if !found.insert(name.clone()) {
+                    continue;
+                }
+
+                if let Some(bytes) = read_limited(entry, max_size)? {
+                    contents.insert(name, bytes);
+                }
+            }
+        }
+        None => return Err(not_an_archive()),
+    }
+
+    Ok(contents)
+}
+
+/// Reads at most `max_size` bytes from a file in the archive, so a file that is larger
+/// than the size stored for it does not have to fit in memory.
+///
+/// # Returns
+///
+/// The bytes of the file, or `None` if the file is larger than `max_size`.
+fn read_limited<R: Read>(reader: R, max_size: u64) -> io::Result<Option<Vec<u8>>> {
+    let mut bytes = Vec::new();
+    reader
+        .take(max_size.saturating_add(1))
+        .read_to_end(&mut bytes)?;
+
+    Ok((bytes.len() as u64 <= max_size).then_some(bytes))
+}
+
+/// Identifies a version of an archive in the cache.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ArchiveKey {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    size: u64,
+}
+
+impl ArchiveKey {
+    fn new(archive: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(archive)?;
+
+        Ok(ArchiveKey {
+            path: fs::canonicalize(archive)?,
+            modified: metadata.modified().ok(),
+            size: metadata.len(),
+        })
+    }
+}
+
+/// Reads the list of regular files from the archive.
+fn read_listing(archive: &Path) -> io::Result<Vec<ArchiveEntry>> {
+    let mut entries = Vec::new();
+
+    match ArchiveFormat::from_path(archive) {
+        Some(ArchiveFormat::Zip) => {
+            let mut zip = ZipArchive::new(BufReader::new(File::open(archive)?))?;
+
+            for index in 0..zip.len() {
+                let file = zip.by_index_raw(index)?;
+
+                if !file.is_file() {
+                    continue;
+                }
+
+                entries.push(ArchiveEntry {
+                    name: file.name().to_string(),
+                    size: file.size(),
+                    modified: file.last_modified().and_then(zip_time),
+                });
+            }
+        }
+        Some(format) => {
+            let mut tar = tar::Archive::new(open_tar(archive, format)?);
+
+            for entry in tar.entries()? {
+                let entry = entry?;
+                let header = entry.header();
+
+                if !header.entry_type().is_file() {
+                    continue;
+                }
+
+                entries.push(ArchiveEntry {
+                    name: normalize_tar_name(&entry.path()?),
+                    size: entry.size(),
+                    modified: header
+                        .mtime()
+                        .ok()
+                        .map(|seconds| UNIX_EPOCH + Duration::from_secs(seconds)),
+                });
+            }
+        }
+        None => return Err(not_an_archive()),
+    }
+
+    Ok(entries)
+}
+
+/// Opens the tar archive, decompressing it if needed.
+fn open_tar(archive: &Path, format: ArchiveFormat) -> io::Result<Box<dyn Read>> {
+    let file = BufReader::new(File::open(archive)?);
+
+    match format {
+        ArchiveFormat::TarGz => Ok(Box::new(GzDecoder::new(file))),
+        _ => Ok(Box::new(file)),
+    }
+}
+
+/// Converts the path of a tar entry to a name with `/` separators,
+/// removing the leading `./` that some tools add.
+fn normalize_tar_name(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect::<Vec<String>>()
+        .join("/")
+}
+
+/// Converts the modification time of a zip entry, which is stored in local time.
+fn zip_time(time: zip::DateTime) -> Option<SystemTime> {
+    let date_time =
+        NaiveDate::from_ymd_opt(time.year().into(), time.month().into(), time.day().into())?
+            .and_hms_opt(
+                time.hour().into(),
+                time.minute().into(),
+                time.second().into(),
+            )?;
+
+    Local
+        .from_local_datetime(&date_time)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+fn entry_not_found(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("no file named '{}' in the archive", name),
+    )
+}
+
+fn entry_too_large(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("file '{}' is larger than its size in the archive", name),
+    )
+}
+
+fn not_an_archive() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, "not a zip or tar archive")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+
+    #[test]
+    fn test_archive_format_from_path() {
+        let format = |path: &str| ArchiveFormat::from_path(Path::new(path));
+
+        assert_eq!(format("project.zip"), Some(ArchiveFormat::Zip));
+        assert_eq!(format("dir/SRC.TAR.GZ"), Some(ArchiveFormat::TarGz));
+        assert_eq!(format("src.tgz"), Some(ArchiveFormat::TarGz));
+        assert_eq!(format("src.tar"), Some(ArchiveFormat::Tar));
+        assert_eq!(format("src.gz"), None);
+        assert_eq!(format("main.rs"), None);
+    }
+
+    #[test]
+    fn test_entry_path() {
+        let path = entry_path(Path::new("dir/project.zip"), "src/main.rs");
+
+        assert_eq!(path, PathBuf::from("dir/project.zip!/src/main.rs"));
+
+        assert_eq!(
+            split_entry_path(&path),
+            Some((PathBuf::from("dir/project.zip"), "src/main.rs".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_split_entry_path_not_in_archive() {
+        assert_eq!(split_entry_path(Path::new("src/main.rs")), None);
+        assert_eq!(split_entry_path(Path::new("wow!/main.rs")), None);
+    }
+
+    #[test]
+    fn test_list_and_read_zip_entries() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip(
+            "project.zip",
+            &[("src/main.rs", "fn main() {}"), ("README.md", "Hi")],
+        );
+        let archives = ArchiveCache::default();
+
+        let entries = archives.list_entries(&archive).unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+
+        assert_eq!(names, vec!["src/main.rs", "README.md"]);
+        assert_eq!(entries[0].size, 12);
+        assert!(entries[0].modified.is_some());
+
+        assert_eq!(
+            *archives.read_entry(&archive, "src/main.rs").unwrap(),
+            *b"fn main() {}"
+        );
+        assert_eq!(archives.find_entry(&archive, "README.md").unwrap().size, 2);
+    }
+
+    #[test]
+    fn test_list_and_read_tar_gz_entries() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mktar_gz(
+            "src.tar.gz",
+            &[("src/main.rs", "fn main() {}"), ("README.md", "Hi")],
+        );
+        let archives = ArchiveCache::default();
+
+        let entries = archives.list_entries(&archive).unwrap();
+
+        assert_eq!(
+            entries[0],
+            ArchiveEntry {
+                name: "src/main.rs".to_string(),
+                size: 12,
+                modified: Some(UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            }
+        );
+
+        assert_eq!(entries[1].name, "README.md");
+        assert_eq!(*archives.read_entry(&archive, "README.md").unwrap(), *b"Hi");
+    }
+
+    #[test]
+    fn test_read_entries() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip(
+            "project.zip",
+            &[("a.txt", "A"), ("b.txt", "B"), ("c.txt", "C")],
+        );
+
+        let names = HashSet::from(["a.txt", "c.txt", "missing.txt"]);
+        let contents = ArchiveCache::default()
+            .read_entries(&archive, &names, |_| u64::MAX)
+            .unwrap();
+
+        assert_eq!(contents.len(), 2);
+        assert_eq!(*contents["a.txt"], *b"A");
+        assert_eq!(*contents["c.txt"], *b"C");
+    }
+
+    #[test]
+    fn test_read_entries_max_size() {
+        let td = TempDir::new().unwrap();
+        let zip = td.mkzip("project.zip", &[("a.txt", "A"), ("b.txt", "Large")]);
+        let tar = td.mktar_gz("src.tar.gz", &[("a.txt", "A"), ("b.txt", "Large")]);
+
+        for archive in [zip, tar] {
+            let contents =
+                read_entries(&archive, &HashMap::from([("a.txt", 1), ("b.txt", 4)])).unwrap();
+
+            assert_eq!(contents.len(), 1);
+            assert_eq!(contents["a.txt"], b"A");
+        }
+    }
+
+    #[test]
+    fn test_read_entries_stops_when_all_found() {
+        let td = TempDir::new().unwrap();
+        let large: String = (0..20_000u64)
+            .map(|i| format!("{:08x}", i.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> 32))
+            .collect();
+        let archive = td.mktar_gz("src.tar.gz", &[("a.txt", "A"), ("b.txt", &large)]);
+
+        // Cut the archive in the middle of the second file
+        let bytes = fs::read(&archive).unwrap();
+        fs::write(&archive, &bytes[..bytes.len() / 2]).unwrap();
+
+        let contents = read_entries(&archive, &HashMap::from([("a.txt", 10)])).unwrap();
+
+        assert_eq!(contents["a.txt"], b"A");
+        assert!(read_entries(&archive, &HashMap::from([("b.txt", u64::MAX)])).is_err());
+    }
+
+    #[test]
+    fn test_archive_cache_reads_archive_once() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip("project.zip", &[("a.txt", "A")]);
+        let modified = fs::metadata(&archive).unwrap().modified().unwrap();
+        let archives = ArchiveCache::default();
+
+        assert_eq!(*archives.read_entry(&archive, "a.txt").unwrap(), *b"A");
+
+        // Replace the archive with one of the same size and modification time
+        td.mkzip("project.zip", &[("a.txt", "B")]);
+        File::options()
+            .write(true)
+            .open(&archive)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        assert_eq!(*archives.read_entry(&archive, "a.txt").unwrap(), *b"A");
+        assert_eq!(
+            *archives.clone().read_entry(&archive, "a.txt").unwrap(),
+            *b"A"
+        );
+        assert_eq!(
+            *ArchiveCache::default()
+                .read_entry(&archive, "a.txt")
+                .unwrap(),
+            *b"B"
+        );
+    }
+
+    #[test]
+    fn test_archive_cache_reads_changed_archive() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip("project.zip", &[("a.txt", "A")]);
+        let archives = ArchiveCache::default();
+
+        assert_eq!(archives.list_entries(&archive).unwrap().len(), 1);
+
+        td.mkzip("project.zip", &[("a.txt", "A"), ("b.txt", "Changed")]);
+
+        assert_eq!(archives.list_entries(&archive).unwrap().len(), 2);
+        assert_eq!(
+            *archives.read_entry(&archive, "b.txt").unwrap(),
+            *b"Changed"
+        );
+    }
+
+    #[test]
+    fn test_archive_cache_retain_contents() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip("project.zip", &[("a.txt", "A"), ("b.txt", "B")]);
+        let archives = ArchiveCache::default();
+
+        archives
+            .read_entries(&archive, &HashSet::from(["a.txt", "b.txt"]), |_| 10)
+            .unwrap();
+        archives.retain_contents(&archive, |name| name == "a.txt");
+
+        let cached = archives.archives.lock().unwrap();
+        let names: Vec<&String> = cached
+            .values()
+            .flat_map(|cached| cached.contents.keys())
+            .collect();
+
+        assert_eq!(names, vec!["a.txt"]);
+    }
+
+    #[test]
+    fn test_read_missing_entry() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip("project.zip", &[("a.txt", "A")]);
+
+        let error = ArchiveCache::default()
+            .read_entry(&archive, "missing.txt")
+            .unwrap_err();
+
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn test_list_entries_invalid_archive() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkfile_with_contents("broken.zip", "not a zip");
+
+        assert!(ArchiveCache::default().list_entries(&archive).is_err());
+    }
+}
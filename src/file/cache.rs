@@ -0,0 +1,447 @@
+use crate::file::comment_remover::remove_comments_from_file;
+use crate::file::file_content::FileContent;
+use crate::options::QuaggaOptions;
+use crate::walk::classifier::FileClass;
+use home::home_dir;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Version of the cache entry format. Entries with a different version are ignored.
+const CACHE_VERSION: u32 = 6;
+
+/// On-disk cache of the results of processing files, which makes repeated runs
+/// on the same project faster. Each file has its own cache entry, which is used only
+/// if the path, modification time, size and inode of the file have not changed.
+///
+/// The contents of the files are not cached, only the results of binary detection and
+/// classification and the contents with the comments removed. The cache files are readable
+/// only by the owner. Token counts are not cached either: they are estimated from the number
+/// of characters, which is faster than loading a cache entry.
+///
+/// Errors reading or writing the cache are ignored: the file is then processed as if
+/// there was no cache.
+#[derive(Debug, Clone)]
+pub struct FileCache {
+    dir: PathBuf,
+}
+
+/// Identifies the version of a file the cache entry was created for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct CacheKey {
+    path: PathBuf,
+    modified_nanos: u128,
+    size: u64,
+    inode: u64,
+}
+
+/// The cached results of detecting the kind of a file, `None` for the ones not known yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CachedFile {
+    /// Whether the file is a text file (see `is_valid_text_content`).
+    pub is_text: Option<bool>,
+
+    /// Whether the file is generated or minified (see `classify_file`).
+    pub file_class: Option<FileClass>,
+}
+
+/// The cached results of processing a single file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    version: u32,
+    key: CacheKey,
+
+    #[serde(flatten)]
+    file: CachedFile,
+
+    /// The content of the file with the comments removed.
+    text_without_comments: Option<String>,
+
+    /// The hash of the text the comments were removed from. It differs from the hash
+    /// of the file when the text was changed before removing comments, e.g. redacted.
+    comments_source_hash: Option<String>,
+}
+
+impl FileCache {
+    /// Creates a cache that stores its entries in the `dir` directory.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> FileCache {
+        FileCache { dir: dir.into() }
+    }
+
+    /// Creates the cache if it is enabled in the options.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the cache is disabled, the cache directory can not be determined,
+    /// or the `encoding` option is used, since the cached binary detection results
+    /// are made with the detected encodings.
+    pub fn from_options(options: &QuaggaOptions) -> Option<FileCache> {
+        if !options.cache || options.encoding.is_some() {
+            return None;
+        }
+
+        options
+            .cache_dir
+            .clone()
+            .or_else(default_cache_dir)
+            .map(FileCache::new)
+    }
+
+    /// Returns the directory where the cache entries are stored.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Removes all cache entries.
+    pub fn clear(&self) -> io::Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)?;
+        }
+
+        Ok(())
+    }
+
+    /// Loads the cached results for the file. The results are empty if the file
+    /// has not been cached or has changed since.
+    pub fn load(&self, path: &Path) -> CachedFile {
+        self.load_entry(path)
+            .map(|entry| entry.file)
+            .unwrap_or_default()
+    }
+
+    /// Stores the results for the file, keeping its cached content without comments.
+    pub fn store(&self, path: &Path, file: CachedFile) {
+        self.update(path, |entry| entry.file = file);
+    }
+
+    /// Removes comments from the files, reusing the cached results when possible.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_contents` - The files to remove comments from.
+    ///
+    /// # Returns
+    ///
+    /// The files with the comments removed.
+    pub fn remove_comments(&self, file_contents: Vec<FileContent>) -> Vec<FileContent> {
+        file_contents
+            .into_iter()
+            .map(|file| self.remove_comments_from_file(file))
+            .collect()
+    }
+
+    fn remove_comments_from_file(&self, file: FileContent) -> FileContent {
+        // The cached result is valid only if it was created from the same text
+        let source_hash = hash_hex(file.content.as_bytes());
+        let cached = self
+            .load_entry(&file.path)
+            .filter(|entry| entry.comments_source_hash.as_ref() == Some(&source_hash))
+            .and_then(|entry| entry.text_without_comments);
+
+        if let Some(content) = cached {
+            return FileContent {
+                path: file.path,
+                content,
+            };
+        }
+
+        let result = remove_comments_from_file(file);
+
+        self.update(&result.path, |entry| {
+            entry.text_without_comments = Some(result.content.clone());
+            entry.comments_source_hash = Some(source_hash);
+        });
+
+        result
+    }
+
+    /// Loads the cache entry for the file, if it exists and the file has not changed since.
+    fn load_entry(&self, path: &Path) -> Option<CacheEntry> {
+        let key = cache_key(path)?;
+        let json = fs::read_to_string(self.entry_path(&key.path)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&json).ok()?;
+
+        if entry.version == CACHE_VERSION && entry.key == key {
+            Some(entry)
+        } else {
+            None
+        }
+    }
+
+    /// Changes the cache entry for the file with the `change` function and saves it.
+    /// An outdated entry is replaced with a new one.
+    fn update<F: FnOnce(&mut CacheEntry)>(&self, path: &Path, change: F) {
+        let Some(key) = cache_key(path) else {
+            return;
+        };
+
+        let mut entry = self.load_entry(path).unwrap_or_else(|| CacheEntry {
+            version: CACHE_VERSION,
+            key: key.clone(),
+            file: CachedFile::default(),
+            text_without_comments: None,
+            comments_source_hash: None,
+        });
+
+        change(&mut entry);
+        let _ = self.save(&entry); // The cache is optional, so failing to save it is not an error
+    }
+
+    /// Saves the entry to a temporary file first and then renames it, so that
+    /// other processes never see a partially written entry.
+    fn save(&self, entry: &CacheEntry) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let entry_path = self.entry_path(&entry.key.path);
+        let json = serde_json::to_string(entry).map_err(io::Error::other)?;
+        let temp_path = entry_path.with_extension(format!("{}.tmp", std::process::id()));
+        write_private_file(&temp_path, json.as_bytes())?;
+        fs::rename(&temp_path, &entry_path)
+    }
+
+    /// Returns the path to the cache entry file, named after the hash of the file path.
+    fn entry_path(&self, path: &Path) -> PathBuf {
+        let name = hash_hex(path.to_string_lossy().as_bytes());
+        self.dir.join(format!("{}.json", name))
+    }
+}
+
+/// Returns the SHA-256 hash of the bytes as a hex string.
+fn hash_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Writes the file so that only the owner can read it, since files in the cache
+/// directory contain the contents of the processed files.
+pub fn write_private_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut open_options = fs::OpenOptions::new();
+    open_options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        open_options.mode(0o600);
+    }
+
+    let mut file = open_options.open(path)?;
+
+    // The mode is applied only when the file is created, a file written by an earlier version
+    // can have wider permissions
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(contents)
+}
+
+/// Returns the default cache directory: `$XDG_CACHE_HOME/quagga`,
+/// or `~/.cache/quagga` if `XDG_CACHE_HOME` is not set.
+pub fn default_cache_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_CACHE_HOME") {
+        Some(dir) if Path::new(&dir).is_absolute() => Some(PathBuf::from(dir).join("quagga")),
+        _ => home_dir().map(|home| home.join(".cache").join("quagga")),
+    }
+}
+
+/// Creates the cache key from the file metadata.
+///
+/// # Returns
+///
+/// `None` if the metadata can not be read.
+fn cache_key(path: &Path) -> Option<CacheKey> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?;
+    let modified_nanos = modified.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+
+    Some(CacheKey {
+        path: fs::canonicalize(path).ok()?,
+        modified_nanos,
+        size: metadata.len(),
+        inode: inode(&metadata),
+    })
+}
+
+#[cfg(unix)]
+fn inode(metadata: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    metadata.ino()
+}
+
+#[cfg(not(unix))]
+fn inode(_metadata: &fs::Metadata) -> u64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+
+    #[test]
+    fn test_store_and_load() {
+        let td = TempDir::new().unwrap();
+        let cache = FileCache::new(td.path().join("cache"));
+        let path = td.mkfile_with_contents("Cargo.lock", "version = 3");
+
+        assert_eq!(cache.load(&path), CachedFile::default());
+
+        let file = CachedFile {
+            is_text: Some(true),
+            file_class: Some(FileClass::Generated),
+        };
+
+        cache.store(&path, file);
+
+        assert_eq!(cache.load(&path), file);
+    }
+
+    #[test]
+    fn test_store_does_not_cache_text() {
+        let td = TempDir::new().unwrap();
+        let cache = FileCache::new(td.path().join("cache"));
+        let path = td.mkfile_with_contents("secret.txt", "password=hunter2");
+
+        cache.store(
+            &path,
+            CachedFile {
+                is_text: Some(true),
+                file_class: None,
+            },
+        );
+
+        let entry_path = cache.entry_path(&fs::canonicalize(&path).unwrap());
+        let json = fs::read_to_string(&entry_path).unwrap();
+
+        assert!(!json.contains("hunter2"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&entry_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn test_entry_is_invalidated_when_file_changes() {
+        let td = TempDir::new().unwrap();
+        let cache = FileCache::new(td.path().join("cache"));
+        let path = td.mkfile_with_contents("file.txt", "Hello");
+
+        cache.store(
+            &path,
+            CachedFile {
+                is_text: Some(true),
+                file_class: Some(FileClass::Source),
+            },
+        );
+        td.mkfile_with_contents("file.txt", "Hello, world!"); // Changes the size
+
+        assert_eq!(cache.load(&path), CachedFile::default());
+    }
+
+    #[test]
+    fn test_remove_comments_uses_cache() {
+        let td = TempDir::new().unwrap();
+        let cache = FileCache::new(td.path().join("cache"));
+        let path = td.mkfile_with_contents("file.rs", "let x = 1; // comment");
+
+        let file = FileContent {
+            path: path.clone(),
+            content: "let x = 1; // comment".to_string(),
+        };
+
+        let result = cache.remove_comments(vec![file]);
+        assert_eq!(result[0].content, "let x = 1;");
+
+        // Change the cached result to check that it is used
+        let mut entry = cache.load_entry(&path).unwrap();
+        entry.text_without_comments = Some("cached".to_string());
+        cache.save(&entry).unwrap();
+
+        let file = FileContent {
+            path: path.clone(),
+            content: "let x = 1; // comment".to_string(),
+        };
+
+        let result = cache.remove_comments(vec![file]);
+        assert_eq!(result[0].content, "cached");
+
+        // The cached result is not used if the text is different
+        let file = FileContent {
+            path,
+            content: "let y = 2; // comment".to_string(),
+        };
+
+        let result = cache.remove_comments(vec![file]);
+        assert_eq!(result[0].content, "let y = 2;");
+    }
+
+    #[test]
+    fn test_remove_comments_keeps_cached_file() {
+        let td = TempDir::new().unwrap();
+        let cache = FileCache::new(td.path().join("cache"));
+        let path = td.mkfile_with_contents("file.rs", "let key = 1; // comment");
+        let file = CachedFile {
+            is_text: Some(true),
+            file_class: Some(FileClass::Source),
+        };
+        cache.store(&path, file);
+
+        let content = FileContent {
+            path: path.clone(),
+            content: "let key = 1; // comment".to_string(),
+        };
+
+        let result = cache.remove_comments(vec![content]);
+
+        assert_eq!(result[0].content, "let key = 1;");
+        assert_eq!(cache.load(&path), file);
+    }
+
+    #[test]
+    fn test_clear() {
+        let td = TempDir::new().unwrap();
+        let cache = FileCache::new(td.path().join("cache"));
+        let path = td.mkfile_with_contents("file.txt", "Hello");
+        cache.store(
+            &path,
+            CachedFile {
+                is_text: Some(true),
+                file_class: None,
+            },
+        );
+
+        cache.clear().unwrap();
+
+        assert!(!cache.dir().exists());
+        assert_eq!(cache.load(&path), CachedFile::default());
+    }
+
+    #[test]
+    fn test_from_options() {
+        let options = QuaggaOptions {
+            cache: true,
+            cache_dir: Some(PathBuf::from("/tmp/cache")),
+            ..Default::default()
+        };
+
+        let cache = FileCache::from_options(&options).unwrap();
+        assert_eq!(cache.dir(), Path::new("/tmp/cache"));
+
+        let options = QuaggaOptions {
+            cache: false,
+            ..Default::default()
+        };
+
+        assert!(FileCache::from_options(&options).is_none());
+    }
+}
@@ -1,7 +1,34 @@
+use crate::file::cache::FileCache;
 use crate::file::file_content::FileContent;
+use crate::file::transform::{ContentTransform, TransformReport};
 use warrah::comment_remover::remove_all_comments::remove_all_comments;
 use warrah::process::file_path::get_marker_by_file_path;
 
+/// Removes comments from code files, reusing the cached results when the cache is enabled.
+#[derive(Debug, Clone)]
+pub struct RemoveComments {
+    cache: Option<FileCache>,
+}
+
+impl RemoveComments {
+    pub fn new(cache: Option<FileCache>) -> RemoveComments {
+        RemoveComments { cache }
+    }
+}
+
+impl ContentTransform for RemoveComments {
+    fn name(&self) -> &str {
+        "remove-comments"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        match &self.cache {
+            Some(cache) => cache.remove_comments(vec![file]).remove(0),
+            None => remove_comments_from_file(file),
+        }
+    }
+}
+
 pub fn remove_comments(file_contents: Vec<FileContent>) -> Vec<FileContent> {
     file_contents
         .into_iter()
@@ -10,7 +37,7 @@ pub fn remove_comments(file_contents: Vec<FileContent>) -> Vec<FileContent> {
 }
 
 /// Removes comments from a single file if markers are found for its extension.
-fn remove_comments_from_file(file_content: FileContent) -> FileContent {
+pub fn remove_comments_from_file(file_content: FileContent) -> FileContent {
     let markers = match get_marker_by_file_path(&file_content.path) {
         Some(markers) => markers,
         None => return file_content,
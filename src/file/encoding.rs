@@ -0,0 +1,268 @@
+use crate::walk::binary_detector::{is_valid_utf8, number_of_null_bytes};
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+
+/// Parses the name of a text encoding, e.g. `utf-8`, `utf-16le`, `windows-1252`, `latin1`
+/// or `shift_jis`. The names are the labels from the WHATWG Encoding Standard.
+///
+/// # Returns
+///
+/// The encoding, or a message if the name is unknown.
+pub fn parse_encoding(name: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(name.trim().as_bytes()).ok_or_else(|| {
+        format!(
+            "unknown encoding '{}', expected e.g. utf-8, utf-16le, windows-1252 or shift_jis",
+            name
+        )
+    })
+}
+
+/// Detects the encoding of text from its byte order mark (BOM), checking if it is valid UTF-8,
+/// or guessing from the byte frequencies. UTF-16 text is only detected with a BOM.
+///
+/// The bytes may be just the start of the file, cut in the middle of a character.
+///
+/// # Arguments
+///
+/// * `bytes` - The contents of the file, or its start.
+///
+/// # Returns
+///
+/// The encoding, or `None` if the bytes don't look like text in any encoding.
+pub fn detect_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return Some(encoding);
+    }
+
+    if is_valid_utf8(bytes) {
+        return Some(UTF_8);
+    }
+
+    // Apart from UTF-16, text encodings don't use null bytes
+    if number_of_null_bytes(bytes) > 0 {
+        return None;
+    }
+
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, false);
+    let encoding = detector.guess(None, false);
+
+    is_text_in_encoding(bytes, encoding).then_some(encoding)
+}
+
+/// Checks if the bytes are text in the encoding: they can be decoded without errors
+/// and don't contain control characters other than whitespace and escape.
+/// UTF-8 text may contain any control characters except null.
+///
+/// The bytes may be just the start of the file, cut in the middle of a character.
+pub fn is_text_in_encoding(bytes: &[u8], encoding: &'static Encoding) -> bool {
+    if encoding == UTF_8 {
+        let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        return number_of_null_bytes(bytes) == 0 && is_valid_utf8(bytes);
+    }
+
+    let mut decoder = encoding.new_decoder_with_bom_removal();
+
+    let Some(capacity) = decoder.max_utf8_buffer_length_without_replacement(bytes.len()) else {
+        return false;
+    };
+
+    let mut text = String::with_capacity(capacity);
+
+    // The incomplete character at the end is not an error when `last` is false
+    let (result, _) = decoder.decode_to_string_without_replacement(bytes, &mut text, false);
+
+    if !matches!(result, encoding_rs::DecoderResult::InputEmpty) {
+        return false;
+    }
+
+    !text
+        .chars()
+        .any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0C' | '\x1B'))
+}
+
+/// Converts the contents of a text file to UTF-8, removing the byte order mark.
+///
+/// # Arguments
+///
+/// * `bytes` - The contents of the file.
+/// * `encoding` - The encoding of the file, or `None` to detect it.
+///
+/// # Returns
+///
+/// * `Ok((String, &Encoding))` with the text and the encoding it was converted from.
+/// * `Err(Vec<u8>)` with the unchanged bytes if the encoding is not detected
+///   or the bytes are not valid in the encoding.
+pub fn decode_text(
+    bytes: Vec<u8>,
+    encoding: Option<&'static Encoding>,
+) -> Result<(String, &'static Encoding), Vec<u8>> {
+    let encoding = match encoding {
+        Some(encoding) => encoding,
+        None => match detect_encoding(&bytes) {
+            Some(encoding) => encoding,
+            None => return Err(bytes),
+        },
+    };
+
+    // Most files are UTF-8 without BOM, which don't need to be copied
+    if encoding == UTF_8 && !bytes.starts_with(b"\xEF\xBB\xBF") {
+        return String::from_utf8(bytes)
+            .map(|text| (text, encoding))
+            .map_err(|error| error.into_bytes());
+    }
+
+    let (text, had_errors) = encoding.decode_with_bom_removal(&bytes);
+
+    if had_errors {
+        return Err(bytes);
+    }
+
+    Ok((text.into_owned(), encoding))
+}
+
+/// Converts the start of a text file to UTF-8, removing the byte order mark.
+/// The incomplete character at the end is left out. When the encoding is not detected,
+/// invalid UTF-8 sequences are removed.
+///
+/// # Arguments
+///
+/// * `bytes` - The start of the file.
+/// * `encoding` - The encoding of the file, or `None` to detect it.
+///
+/// # Returns
+///
+/// The text of the start of the file.
+pub fn decode_sample(bytes: &[u8], encoding: Option<&'static Encoding>) -> String {
+    let Some(encoding) = encoding.or_else(|| detect_encoding(bytes)) else {
+        return String::from_utf8_lossy(bytes).replace('\u{FFFD}', "");
+    };
+
+    let mut decoder = encoding.new_decoder_with_bom_removal();
+    let capacity = decoder
+        .max_utf8_buffer_length(bytes.len())
+        .unwrap_or(bytes.len());
+    let mut text = String::with_capacity(capacity);
+
+    // The incomplete character at the end is kept in the decoder when `last` is false
+    let _ = decoder.decode_to_string(bytes, &mut text, false);
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::{SHIFT_JIS, UTF_16BE, UTF_16LE, WINDOWS_1252};
+
+    fn utf16le_with_bom(text: &str) -> Vec<u8> {
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn test_parse_encoding() {
+        assert_eq!(parse_encoding("utf-8"), Ok(UTF_8));
+        assert_eq!(parse_encoding("UTF-16LE"), Ok(UTF_16LE));
+        assert_eq!(parse_encoding("latin1"), Ok(WINDOWS_1252));
+        assert_eq!(parse_encoding("shift_jis"), Ok(SHIFT_JIS));
+        assert!(parse_encoding("klingon").is_err());
+    }
+
+    #[test]
+    fn test_detect_encoding_from_bom() {
+        assert_eq!(
+            detect_encoding(&utf16le_with_bom("fn main() {}")),
+            Some(UTF_16LE)
+        );
+        assert_eq!(detect_encoding(&[0xFE, 0xFF, 0x00, 0x61]), Some(UTF_16BE));
+        assert_eq!(detect_encoding(b"\xEF\xBB\xBFtext"), Some(UTF_8));
+    }
+
+    #[test]
+    fn test_detect_encoding_utf8() {
+        assert_eq!(detect_encoding("Grüße".as_bytes()), Some(UTF_8));
+        assert_eq!(detect_encoding(b""), Some(UTF_8));
+    }
+
+    #[test]
+    fn test_detect_encoding_windows_1252() {
+        let (bytes, _, _) = WINDOWS_1252.encode("name = \"Café Müller\"\ncity = \"Zürich\"\n");
+
+        assert_eq!(detect_encoding(&bytes), Some(WINDOWS_1252));
+    }
+
+    #[test]
+    fn test_detect_encoding_shift_jis() {
+        let (bytes, _, _) =
+            SHIFT_JIS.encode("// こんにちは、世界。これはコメントです。\nfn main() {}\n");
+
+        assert_eq!(detect_encoding(&bytes), Some(SHIFT_JIS));
+    }
+
+    #[test]
+    fn test_detect_encoding_binary() {
+        assert_eq!(detect_encoding(&[0x00, 0xC0, 0xC1]), None);
+        assert_eq!(detect_encoding(&[0x01, 0x02, 0xC0, 0x03]), None);
+    }
+
+    #[test]
+    fn test_is_text_in_encoding_allows_cut_character() {
+        let (bytes, _, _) = SHIFT_JIS.encode("こんにちは");
+
+        assert!(is_text_in_encoding(&bytes[..bytes.len() - 1], SHIFT_JIS));
+    }
+
+    #[test]
+    fn test_decode_text() {
+        let (text, encoding) = decode_text(utf16le_with_bom("Hello"), None).unwrap();
+        assert_eq!(text, "Hello");
+        assert_eq!(encoding, UTF_16LE);
+
+        let (text, encoding) = decode_text(b"\xEF\xBB\xBFHello".to_vec(), None).unwrap();
+        assert_eq!(text, "Hello");
+        assert_eq!(encoding, UTF_8);
+
+        let (bytes, _, _) = WINDOWS_1252.encode("Café Müller, Zürich, Genève");
+        let (text, encoding) = decode_text(bytes.into_owned(), None).unwrap();
+        assert_eq!(text, "Café Müller, Zürich, Genève");
+        assert_eq!(encoding, WINDOWS_1252);
+    }
+
+    #[test]
+    fn test_decode_text_with_encoding() {
+        let bytes: Vec<u8> = "Hi"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+
+        assert_eq!(
+            decode_text(bytes, Some(UTF_16LE)),
+            Ok(("Hi".to_string(), UTF_16LE))
+        );
+        assert_eq!(decode_text(vec![0xC0], Some(UTF_8)), Err(vec![0xC0]));
+    }
+
+    #[test]
+    fn test_decode_text_binary() {
+        assert_eq!(
+            decode_text(vec![0x00, 0xC0, 0xC1], None),
+            Err(vec![0x00, 0xC0, 0xC1])
+        );
+    }
+
+    #[test]
+    fn test_decode_sample() {
+        let bytes = utf16le_with_bom("// <auto-generated>\nclass A {}");
+
+        assert_eq!(
+            decode_sample(&bytes, None),
+            "// <auto-generated>\nclass A {}"
+        );
+        assert_eq!(
+            decode_sample(&bytes[..bytes.len() - 1], None),
+            "// <auto-generated>\nclass A {"
+        );
+        assert_eq!(decode_sample("héllo".as_bytes()[..2].as_ref(), None), "h");
+    }
+}
@@ -0,0 +1,245 @@
+use crate::file::file_content::FileContent;
+use crate::file::transform::{ContentTransform, TransformReport};
+use crate::walk::contain::ContentMatcher;
+use std::ops::Range;
+
+/// Reduces the files to the lines that match the `contain` patterns, with `context` lines
+/// around each match.
+#[derive(Debug, Clone)]
+pub struct Excerpt {
+    matcher: ContentMatcher,
+    context: usize,
+}
+
+impl Excerpt {
+    pub fn new(matcher: ContentMatcher, context: usize) -> Excerpt {
+        Excerpt { matcher, context }
+    }
+}
+
+impl ContentTransform for Excerpt {
+    fn name(&self) -> &str {
+        "excerpt"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        excerpt_file(file, &self.matcher, self.context)
+    }
+}
+
+/// Reduces the files to the lines that match the `contain` patterns, with `context` lines
+/// around each match. Files are left unchanged when there are no `contain` patterns.
+///
+/// # Arguments
+///
+/// * `file_contents` - The files to reduce.
+/// * `matcher` - Finds the matching lines.
+/// * `context` - The number of lines to show before and after each match.
+///
+/// # Returns
+///
+/// The files with the content replaced with the excerpts.
+pub fn excerpt_files(
+    file_contents: Vec<FileContent>,
+    matcher: &ContentMatcher,
+    context: usize,
+) -> Vec<FileContent> {
+    file_contents
+        .into_iter()
+        .map(|file| excerpt_file(file, matcher, context))
+        .collect()
+}
+
+/// Reduces a single file to the lines that match the `contain` patterns.
+pub fn excerpt_file(file: FileContent, matcher: &ContentMatcher, context: usize) -> FileContent {
+    if !matcher.has_required() {
+        return file;
+    }
+
+    let matches = matcher.matching_lines(&file.content);
+
+    FileContent {
+        content: excerpt(&file.content, &matches, context),
+        path: file.path,
+    }
+}
+
+/// Renders the lines of the matches with `context` lines around them. Each line is prefixed
+/// with its original line number, and the omitted lines are replaced with an elision marker,
+/// e.g. `... (lines 1-39 omitted)`.
+///
+/// # Arguments
+///
+/// * `content` - The content of a file.
+/// * `matches` - Ranges of zero-based line indices of the matches.
+/// * `context` - The number of lines to show before and after each match.
+///
+/// # Returns
+///
+/// The excerpt text.
+pub fn excerpt(content: &str, matches: &[Range<usize>], context: usize) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let windows = merge_windows(matches, context, lines.len());
+    let width = lines.len().to_string().len();
+    let mut output = Vec::new();
+    let mut next_line = 0;
+
+    for window in windows {
+        if window.start > next_line {
+            output.push(elision_marker(next_line, window.start));
+        }
+
+        for (index, line) in lines[window.clone()].iter().enumerate() {
+            let number = window.start + index + 1;
+            output.push(format!("{:>width$}: {}", number, line, width = width));
+        }
+
+        next_line = window.end;
+    }
+
+    if next_line < lines.len() {
+        output.push(elision_marker(next_line, lines.len()));
+    }
+
+    output.join("\n")
+}
+
+/// Extends the matches by `context` lines on each side and merges the overlapping
+/// and adjacent windows.
+///
+/// # Returns
+///
+/// Sorted, non-overlapping ranges of zero-based line indices, limited to `total_lines`.
+fn merge_windows(
+    matches: &[Range<usize>],
+    context: usize,
+    total_lines: usize,
+) -> Vec<Range<usize>> {
+    let mut windows: Vec<Range<usize>> = matches
+        .iter()
+        .map(|range| {
+            let start = range.start.saturating_sub(context).min(total_lines);
+            let end = range.end.saturating_add(context).min(total_lines);
+            start..end
+        })
+        .filter(|window| !window.is_empty())
+        .collect();
+
+    windows.sort_by_key(|window| window.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+
+    for window in windows {
+        match merged.last_mut() {
+            Some(last) if window.start <= last.end => last.end = last.end.max(window.end),
+            _ => merged.push(window),
+        }
+    }
+
+    merged
+}
+
+/// Returns the marker for the omitted lines, given as a range of zero-based line indices.
+fn elision_marker(start: usize, end: usize) -> String {
+    if end - start == 1 {
+        format!("... (line {} omitted)", end)
+    } else {
+        format!("... (lines {}-{} omitted)", start + 1, end)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::QuaggaOptions;
+    use std::path::PathBuf;
+
+    fn numbered_lines(count: usize) -> String {
+        (1..=count)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_excerpt_with_context() {
+        let content = numbered_lines(20);
+
+        let matched_line = 9..10;
+        let result = excerpt(&content, std::slice::from_ref(&matched_line), 2);
+
+        let expected = "\
+... (lines 1-7 omitted)
+ 8: line 8
+ 9: line 9
+10: line 10
+11: line 11
+12: line 12
+... (lines 13-20 omitted)";
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_excerpt_merges_overlapping_windows() {
+        let content = numbered_lines(12);
+
+        let result = excerpt(&content, &[2..3, 5..6, 10..11], 1);
+
+        let expected = "\
+... (line 1 omitted)
+ 2: line 2
+ 3: line 3
+ 4: line 4
+ 5: line 5
+ 6: line 6
+ 7: line 7
+... (lines 8-9 omitted)
+10: line 10
+11: line 11
+12: line 12";
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_excerpt_no_matches() {
+        assert_eq!(excerpt("a\nb", &[], 3), "... (lines 1-2 omitted)");
+        assert_eq!(excerpt("", &[], 3), "");
+    }
+
+    #[test]
+    fn test_excerpt_file() {
+        let options = QuaggaOptions {
+            contain: vec!["unsafe".to_string()],
+            ..Default::default()
+        };
+
+        let matcher = ContentMatcher::new(&options).unwrap();
+
+        let file = FileContent {
+            path: PathBuf::from("main.rs"),
+            content: "fn main() {\n    unsafe { x() }\n}\n\nfn other() {}".to_string(),
+        };
+
+        let result = excerpt_file(file, &matcher, 1);
+
+        assert_eq!(
+            result.content,
+            "1: fn main() {\n2:     unsafe { x() }\n3: }\n... (lines 4-5 omitted)"
+        );
+    }
+
+    #[test]
+    fn test_excerpt_file_without_contain_patterns() {
+        let matcher = ContentMatcher::new(&QuaggaOptions::default()).unwrap();
+
+        let file = FileContent {
+            path: PathBuf::from("main.rs"),
+            content: "fn main() {}".to_string(),
+        };
+
+        let result = excerpt_file(file, &matcher, 1);
+
+        assert_eq!(result.content, "fn main() {}");
+    }
+}
@@ -1,4 +1,4 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Represents the content of a file along with its path.
 ///
@@ -11,3 +11,9 @@ pub struct FileContent {
     pub path: PathBuf,
     pub content: String,
 }
+
+impl AsRef<Path> for FileContent {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
@@ -1,141 +1,209 @@
-use crate::cli::Cli;
-use crate::file::comment_remover::remove_comments;
+use crate::error::QuaggaError;
+use crate::file::archive::{split_entry_path, ArchiveCache};
+use crate::file::encoding::decode_text;
 use crate::file::file_content::FileContent;
-use crate::file::size::check_total_size;
+use crate::file::notebook::convert_notebook;
+use crate::file::size::{check_total_content_size, check_total_size, keep_regular_files};
+use crate::file::skipped_file::{skip_or_fail, SkippedFile};
+use crate::file::transform::{TransformPipeline, TransformReport};
+use crate::options::{OnError, QuaggaOptions};
 use crate::template::concatenate::concatenate_files;
 use crate::template::template::Template;
+use encoding_rs::Encoding;
 use std::fs;
 use std::io::{self, Read};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-/// Reads and concatenates files using the provided template.
+/// Reads the files from the list, checking their total size before reading.
 ///
 /// # Arguments
 ///
 /// * `files` - A vector of `PathBuf` representing the paths to the files to read.
-/// * `template` - A `Template` struct containing the template sections.
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `skipped` - The list to which the files that can not be read are added,
+///   unless `options.on_error` is `OnError::Fail`.
 ///
 /// # Returns
 ///
-/// A `Result` containing the output prompt text, splitted into parts, if successful,
-/// or an `io::Error` if an error occurs while reading any of the files or if the files vector is empty.
-pub fn read_and_concatenate_files(
+/// A `Result` containing the contents of the files, or a `QuaggaError` if their total size
+/// exceeds the limit or a file can not be read and `options.on_error` is `OnError::Fail`.
+pub fn read_file_list(
     files: Vec<PathBuf>,
-    template: Template,
-    cli: &Cli,
-) -> io::Result<Vec<String>> {
-    if files.is_empty() {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "No files to process",
-        ));
-    }
-
-    check_total_size(files.clone(), cli.max_total_size)?;
-    let mut file_contents: Vec<FileContent> = read_files(files, cli.binary)?;
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<Vec<FileContent>, QuaggaError> {
+    let files = keep_regular_files(files, options.on_error, archives, skipped)?;
+    check_total_size(files.clone(), options, archives)?;
+    let mut file_contents = Vec::new();
 
-    if cli.remove_comments {
-        file_contents = remove_comments(file_contents);
+    for path in files {
+        match read_text_file(path.clone(), options.binary, options.encoding, archives) {
+            Ok(content) => {
+                let content = convert_notebook(&path, content, options.notebook_outputs);
+                file_contents.push(FileContent { path, content });
+            }
+            Err(error) => skip_or_fail(error, options.on_error, skipped)?,
+        }
     }
 
-    let concatenated = concatenate_files(template, file_contents, cli);
-    Ok(concatenated)
+    Ok(file_contents)
 }
 
-/// Reads the contents of the given files and returns a vector of `FileContent`.
+/// Concatenates the contents of files that were already read using the provided template.
 ///
 /// # Arguments
 ///
-/// * `paths` - A vector of `PathBuf` representing the file paths.
-/// * `force` - A boolean indicating whether to force reading a file when it is not valid UTF-8 text
-///             by removing removing invalid UTF-8 sequences.
+/// * `file_contents` - The paths and contents of the files.
+/// * `template` - A `Template` struct containing the template sections.
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `pipeline` - The transforms applied to the contents of the files.
+/// * `skipped` - Files that were left out because they could not be read.
+/// * `report` - Collects what the transforms did to the files.
 ///
 /// # Returns
 ///
-/// A `Result` containing a vector of `FileContent` if successful, or an `io::Error` if an error occurs.
-pub fn read_files(paths: Vec<PathBuf>, force: bool) -> io::Result<Vec<FileContent>> {
-    let mut file_contents = Vec::new();
+/// A `Result` containing the output prompt text, splitted into parts, if successful,
+/// or a `QuaggaError` if there are no files or if their total size exceeds the limit.
+pub fn concatenate_file_contents(
+    file_contents: Vec<FileContent>,
+    template: Template,
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    pipeline: &TransformPipeline,
+    skipped: &[SkippedFile],
+    report: &mut TransformReport,
+) -> Result<Vec<String>, QuaggaError> {
+    if file_contents.is_empty() {
+        return Err(no_files_error(skipped));
+    }
 
-    for path in paths {
-        let content = read_text_file(path.clone(), force)?;
+    check_total_content_size(&file_contents, options.max_total_size)?;
 
-        file_contents.push(FileContent {
-            path: path.clone(),
-            content,
-        });
+    let file_contents = pipeline.apply_all(file_contents, report);
+    let listed_skipped = skipped_files_to_list(options, skipped);
+    let concatenated =
+        concatenate_files(template, file_contents, options, archives, listed_skipped);
+    Ok(concatenated)
+}
+
+/// Returns the skipped files that are listed in the prompt with the `<skipped-files>` tag.
+/// Skipped files are only listed in the warn mode.
+pub fn skipped_files_to_list<'a>(
+    options: &QuaggaOptions,
+    skipped: &'a [SkippedFile],
+) -> &'a [SkippedFile] {
+    match options.on_error {
+        OnError::Warn => skipped,
+        _ => &[],
     }
+}
 
-    Ok(file_contents)
+pub fn no_files_error(skipped: &[SkippedFile]) -> QuaggaError {
+    QuaggaError::NoFiles {
+        skipped: skipped.to_vec(),
+    }
 }
 
-/// Reads and returns the content of the given text file.
-/// It tries to read the file as UTF-8 text first. If it fails and `force` is true
-/// then it reads the file as binary data and removes invalid UTF-8 sequences.
+/// Reads and returns the content of the given text file converted to UTF-8.
+/// It detects the encoding of the file unless the `encoding` is given.
+/// If the file is not valid text and `force` is true then it removes invalid UTF-8 sequences.
 ///
 /// # Arguments
 ///
 /// * `path` - A path to a text file.
-/// * `force` - A boolean indicating whether to force reading the file when it is not valid UTF-8 text
-///             by removing removing invalid UTF-8 sequences.
+/// * `force` - A boolean indicating whether to force reading the file when it is not valid text
+///   by removing removing invalid UTF-8 sequences.
+/// * `encoding` - The encoding of the file, or `None` to detect it.
+/// * `archives` - The cache of the archives read during the run.
 ///
 /// # Returns
 ///
-/// A `Result` containing a the content of the text file or error if the file cannot be read.
-pub fn read_text_file(path: PathBuf, force: bool) -> io::Result<String> {
-    let mut file = fs::File::open(&path).map_err(|e| {
-        io::Error::new(
-            e.kind(),
-            format!("Failed to open file {}: {}", path.display(), e),
-        )
-    })?;
-
-    let mut content = String::new();
+/// A `Result` containing a the content of the text file or `QuaggaError::FileRead` if the file cannot be read.
+pub fn read_text_file(
+    path: PathBuf,
+    force: bool,
+    encoding: Option<&'static Encoding>,
+    archives: &ArchiveCache,
+) -> Result<String, QuaggaError> {
+    let bytes = read_file_bytes(&path, archives)?;
+    bytes_to_text(path, bytes, force, encoding)
+}
 
-    // Try reading the file as UTF-8 text first
-    match file.read_to_string(&mut content) {
-        Ok(_) => {
-            return Ok(content);
-        }
-        Err(e) => {
-            if force {
-                // If the file is not valid UTF-8 text, try reading it as binary data
-                return force_read_text_file(path);
-            } else {
-                return Err(io::Error::new(
-                    e.kind(),
-                    format!("Failed to read file {}: {}", path.display(), e),
-                ));
-            }
+/// Reads the entire file as binary data.
+///
+/// # Arguments
+///
+/// * `path` - A path to a file, or to a file inside an archive, e.g. `project.zip!/src/main.rs`.
+/// * `archives` - The cache of the archives read during the run.
+///
+/// # Returns
+///
+/// A `Result` containing the bytes of the file or `QuaggaError::FileRead` if the file cannot be read.
+pub fn read_file_bytes(path: &Path, archives: &ArchiveCache) -> Result<Vec<u8>, QuaggaError> {
+    let result = match split_entry_path(path) {
+        Some((archive, name)) => archives
+            .read_entry(&archive, &name)
+            .map(|bytes| bytes.to_vec()),
+        None => {
+            let mut bytes = Vec::new();
+            fs::File::open(path)
+                .and_then(|mut file| file.read_to_end(&mut bytes))
+                .map(|_| bytes)
         }
-    }
+    };
+
+    result.map_err(|source| QuaggaError::FileRead {
+        path: path.to_path_buf(),
+        source,
+    })
 }
 
-/// Reads the file as binary data and then convets it to UTF-8 by removing invalid UTF-8 sequences.
+/// Converts the bytes read from a file into UTF-8 text.
 ///
 /// # Arguments
 ///
-/// * `path` - A path to a file.
+/// * `path` - The path to the file the bytes were read from, used in the error.
+/// * `bytes` - The contents of the file.
+/// * `force` - A boolean indicating whether to remove invalid UTF-8 sequences
+///   instead of returning an error when the bytes are not valid text.
+/// * `encoding` - The encoding of the bytes, or `None` to detect it.
 ///
 /// # Returns
 ///
-/// A `Result` containing a the content of the text file or error if the file cannot be read.
-pub fn force_read_text_file(path: PathBuf) -> io::Result<String> {
-    let mut file = fs::File::open(&path)?;
-    let mut bytes = Vec::new();
-
-    file.read_to_end(&mut bytes).map_err(|e| {
-        io::Error::new(
-            e.kind(),
-            format!("Failed to read as binary {}: {}", path.display(), e),
-        )
-    })?;
+/// A `Result` containing the text or `QuaggaError::FileRead` if the bytes are not valid text.
+pub fn bytes_to_text(
+    path: PathBuf,
+    bytes: Vec<u8>,
+    force: bool,
+    encoding: Option<&'static Encoding>,
+) -> Result<String, QuaggaError> {
+    match decode_text(bytes, encoding) {
+        Ok((content, _)) => Ok(content),
+        Err(bytes) if force => Ok(remove_invalid_utf8(&bytes)),
+        Err(_) => {
+            let message = match encoding {
+                Some(encoding) => format!("stream did not contain valid {}", encoding.name()),
+                None => "stream did not contain text in a known encoding".to_string(),
+            };
+
+            Err(QuaggaError::FileRead {
+                path,
+                source: io::Error::new(io::ErrorKind::InvalidData, message),
+            })
+        }
+    }
+}
 
+/// Converts the bytes to UTF-8 text by removing invalid UTF-8 sequences.
+fn remove_invalid_utf8(bytes: &[u8]) -> String {
     // Replaces invalid UTF-8 sequences with the Unicode replacement character \u{FFFD}.
-    let content = String::from_utf8_lossy(&bytes);
+    let content = String::from_utf8_lossy(bytes);
 
     // Removes the replacement character to make the string a valid UTF-8 text
-    let cleaned_content = content.replace("\u{FFFD}", "");
-    return Ok(cleaned_content);
+    content.replace("\u{FFFD}", "")
 }
 
 #[cfg(test)]
@@ -143,112 +211,22 @@ mod tests {
     use super::*;
     use crate::template::template::{PromptTemplate, Template};
     use crate::test_utils::temp_dir::TempDir;
-    use clap::Parser; // Add this import
-
-    #[test]
-    fn test_read_and_concatenate_files() {
-        let td = TempDir::new().unwrap();
-
-        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
-        let file2_path = td.mkfile_with_contents("file32.txt", "World!");
-        let files = vec![file1_path.clone(), file2_path.clone()];
-
-        let template = Template {
-            prompt: PromptTemplate {
-                header: "Header".to_string(),
-                file: "File: <file-path>\nContent:\n<file-content>\n---".to_string(),
-                footer: "Footer".to_string(),
-            },
-            part: Default::default(),
-        };
-
-        let cli = Cli::parse_from(&["test"]);
-
-        let result = read_and_concatenate_files(files, template, &cli);
-
-        assert!(result.is_ok());
-        let content = result.unwrap();
-        assert_eq!(content.len(), 1);
-
-        let expected = format!(
-            "\
-Header
-File: {}
-Content:
-Hello
----
-File: {}
-Content:
-World!
----
-Footer",
-            file1_path.display(),
-            file2_path.display()
-        );
-
-        assert_eq!(content[0], expected);
-    }
-
-    #[test]
-    fn test_read_and_concatenate_files_with_nonexistent_file() {
-        let td = TempDir::new().unwrap();
-        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
-        let file2_path = td.path().join("nonexistent.txt");
-        let files = vec![file1_path, file2_path];
-        let cli = Cli::parse_from(&["test"]);
-
-        let result = read_and_concatenate_files(files, Template::default(), &cli);
-
-        assert!(result.is_err());
-    }
+    use encoding_rs::{UTF_16LE, UTF_8, WINDOWS_1252};
 
     #[test]
-    fn test_read_and_concatenate_files_total_size_exceeds_limit() {
-        let td = TempDir::new().unwrap();
-
-        let file_content = "1234567890a"; // 11 bytes
-        let file1_path = td.mkfile_with_contents("file1.txt", file_content);
-        let files = vec![file1_path.clone()];
-
-        let template = Template {
-            prompt: PromptTemplate {
-                header: "Header".to_string(),
-                file: "<file-content>".to_string(),
-                footer: "Footer".to_string(),
-            },
-            part: Default::default(),
-        };
-
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.max_total_size = 10; // Set max_total_size to 10 bytes
-
-        let result = read_and_concatenate_files(files, template, &cli);
-
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err().to_string();
-        assert!(err_msg.contains("exceeds the maximum"));
-    }
-
-    #[test]
-    fn test_read_and_concatenate_files_no_files_error() {
-        let template = Template::default();
-        let cli = Cli::parse_from(&["test"]);
-
-        let result = read_and_concatenate_files(vec![], template, &cli);
-
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err().to_string();
-        assert_eq!(err_msg, "No files to process");
-    }
-
-    #[test]
-    fn test_read_files_with_invalid_utf8_force_false() {
+    fn test_read_file_list_with_invalid_utf8_force_false() {
         let td = TempDir::new().unwrap();
         let bytes = [0xC0, 0xC1]; // Invalid UTF-8 sequences
         let path = td.mkfile_with_bytes("invalid_utf_8.txt", &bytes);
         let files = vec![path.clone()];
 
-        let result = read_files(files, false);
+        let options = QuaggaOptions {
+            encoding: Some(UTF_8),
+            on_error: OnError::Fail,
+            ..Default::default()
+        };
+
+        let result = read_file_list(files, &options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_err());
         let err_msg = result.unwrap_err().to_string();
@@ -263,14 +241,21 @@ Footer",
     }
 
     #[test]
-    fn test_read_files_with_invalid_utf8_force_true() {
+    fn test_read_file_list_with_invalid_utf8_force_true() {
         let td = TempDir::new().unwrap();
         // Mix of valid UTF-8 and invalid bytes
         let bytes = b"Valid text \xFF\xFE Invalid bytes \xC0\xC1 End.";
         let path = td.mkfile_with_bytes("invalid_utf8.txt", bytes);
         let files = vec![path.clone()];
 
-        let result = read_files(files, true);
+        let options = QuaggaOptions {
+            binary: true,
+            encoding: Some(UTF_8),
+            on_error: OnError::Fail,
+            ..Default::default()
+        };
+
+        let result = read_file_list(files, &options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -280,38 +265,16 @@ Footer",
     }
 
     #[test]
-    fn test_force_read_text_file_valid_utf8() {
-        let td = TempDir::new().unwrap();
-        let path = td.mkfile_with_contents("valid_utf8.txt", "This is a valid UTF-8 string.");
-
-        let result = force_read_text_file(path.clone());
-
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "This is a valid UTF-8 string.");
-    }
-
-    #[test]
-    fn test_force_read_text_file_invalid_utf8() {
+    fn test_read_text_file_invalid_utf8_force_true_detected() {
         let td = TempDir::new().unwrap();
-        // Mix of valid UTF-8 and invalid bytes
-        let bytes = b"Valid text \xFF\xFE Invalid bytes \xC0\xC1 End.";
+        // Mix of valid UTF-8 and bytes that are not text in any encoding
+        let bytes = b"Valid text \x00\xFF Invalid bytes \xC0\xC1 End.";
         let path = td.mkfile_with_bytes("invalid_utf8.txt", bytes);
 
-        let result = force_read_text_file(path.clone());
-
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "Valid text  Invalid bytes  End.");
-    }
-
-    #[test]
-    fn test_force_read_text_file_empty_file() {
-        let td = TempDir::new().unwrap();
-        let path = td.mkfile_with_contents("empty.txt", "");
-
-        let result = force_read_text_file(path.clone());
+        let result = read_text_file(path.clone(), true, None, &ArchiveCache::default());
 
         assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "");
+        assert_eq!(result.unwrap(), "Valid text \0 Invalid bytes  End.");
     }
 
     #[test]
@@ -319,7 +282,7 @@ Footer",
         let td = TempDir::new().unwrap();
         let path = td.mkfile_with_contents("valid_utf8.txt", "This is a valid UTF-8 string.");
 
-        let result = read_text_file(path.clone(), false);
+        let result = read_text_file(path.clone(), false, None, &ArchiveCache::default());
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "This is a valid UTF-8 string.");
@@ -330,7 +293,7 @@ Footer",
         let td = TempDir::new().unwrap();
         let path = td.mkfile_with_contents("another_valid_utf8.txt", "Another valid UTF-8 string.");
 
-        let result = read_text_file(path.clone(), true);
+        let result = read_text_file(path.clone(), true, None, &ArchiveCache::default());
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "Another valid UTF-8 string.");
@@ -342,7 +305,7 @@ Footer",
         let bytes = [0xC0, 0xC1, 0xFF];
         let path = td.mkfile_with_bytes("invalid_utf8_force_false.txt", &bytes);
 
-        let result = read_text_file(path.clone(), false);
+        let result = read_text_file(path.clone(), false, Some(UTF_8), &ArchiveCache::default());
 
         assert!(result.is_err());
         let err_msg = result.unwrap_err().to_string();
@@ -361,18 +324,75 @@ Footer",
         let bytes = b"Valid text \xFF\xFE Invalid bytes \xC0\xC1 End.";
         let path = td.mkfile_with_bytes("invalid_utf8_force_true.txt", bytes);
 
-        let result = read_text_file(path.clone(), true);
+        let result = read_text_file(path.clone(), true, Some(UTF_8), &ArchiveCache::default());
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "Valid text  Invalid bytes  End.");
     }
 
+    #[test]
+    fn test_read_text_file_detects_encoding() {
+        let td = TempDir::new().unwrap();
+        let text = "name = \"Café Müller\"\ncity = \"Zürich\"\n";
+        let (bytes, _, _) = WINDOWS_1252.encode(text);
+        let path = td.mkfile_with_bytes("windows_1252.toml", &bytes);
+
+        let result = read_text_file(path, false, None, &ArchiveCache::default());
+
+        assert_eq!(result.unwrap(), text);
+    }
+
+    #[test]
+    fn test_read_text_file_utf16_with_bom() {
+        let td = TempDir::new().unwrap();
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(
+            "fn main() {}"
+                .encode_utf16()
+                .flat_map(|unit| unit.to_le_bytes()),
+        );
+        let path = td.mkfile_with_bytes("main.rs", &bytes);
+
+        let result = read_text_file(path, false, None, &ArchiveCache::default());
+
+        assert_eq!(result.unwrap(), "fn main() {}");
+    }
+
+    #[test]
+    fn test_read_text_file_with_encoding() {
+        let td = TempDir::new().unwrap();
+        let bytes: Vec<u8> = "fn main() {}"
+            .encode_utf16()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+        let path = td.mkfile_with_bytes("main.rs", &bytes);
+
+        let result = read_text_file(path, false, Some(UTF_16LE), &ArchiveCache::default());
+
+        assert_eq!(result.unwrap(), "fn main() {}");
+    }
+
+    #[test]
+    fn test_read_text_file_unknown_encoding() {
+        let td = TempDir::new().unwrap();
+        let path = td.mkfile_with_bytes("binary.txt", &[0x00, 0xC0, 0xC1]);
+
+        let result = read_text_file(path.clone(), false, None, &ArchiveCache::default());
+
+        let expected = format!(
+            "Failed to read file {}: stream did not contain text in a known encoding",
+            path.display()
+        );
+
+        assert_eq!(result.unwrap_err().to_string(), expected);
+    }
+
     #[test]
     fn test_read_text_file_empty_file() {
         let td = TempDir::new().unwrap();
         let path = td.mkfile_with_contents("empty.txt", "");
 
-        let result = read_text_file(path.clone(), false);
+        let result = read_text_file(path.clone(), false, None, &ArchiveCache::default());
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "");
@@ -382,54 +402,76 @@ Footer",
     fn test_read_text_file_non_existent() {
         let non_existent_path = PathBuf::from("/path/to/non/existent/file.txt");
 
-        let result = read_text_file(non_existent_path.clone(), false);
+        let result = read_text_file(
+            non_existent_path.clone(),
+            false,
+            None,
+            &ArchiveCache::default(),
+        );
 
         assert!(result.is_err());
         let msg = result.unwrap_err().to_string();
-        assert!(msg.contains("Failed to open file /path/to/non/existent/file.txt"));
+        assert!(msg.contains("Failed to read file /path/to/non/existent/file.txt"));
     }
 
     #[test]
-    fn test_read_and_concatenate_files_with_comments_removal() {
-        let td = TempDir::new().unwrap();
-
-        let file1_path = td.mkfile_with_contents("file1.rs", "let x = 1; // comment");
-        let file2_path = td.mkfile_with_contents("file2.txt", "Unchanged content");
-        let files = vec![file1_path.clone(), file2_path.clone()];
-
+    fn test_concatenate_file_contents_with_outline() {
         let template = Template {
             prompt: PromptTemplate {
-                header: "Header".to_string(),
-                file: "File: <file-path>\nContent:\n<file-content>\n---".to_string(),
-                footer: "Footer".to_string(),
+                header: "".to_string(),
+                file: "<file-content>".to_string(),
+                footer: "".to_string(),
             },
             part: Default::default(),
         };
 
-        let cli = Cli::parse_from(&["test", "--remove-comments"]);
+        let file_contents = || {
+            vec![FileContent {
+                path: PathBuf::from("main.rs"),
+                content: "fn main() {\n    // todo\n}".to_string(),
+            }]
+        };
 
-        let result = read_and_concatenate_files(files, template, &cli);
+        let options = QuaggaOptions {
+            outline: true,
+            ..Default::default()
+        };
 
-        assert!(result.is_ok());
-        let content = result.unwrap();
-        assert_eq!(content.len(), 1);
+        let result = concatenate_file_contents(
+            file_contents(),
+            template.clone(),
+            &options,
+            &ArchiveCache::default(),
+            &TransformPipeline::from_options(&options).unwrap(),
+            &[],
+            &mut TransformReport::default(),
+        )
+        .unwrap();
 
-        let expected = format!(
-            "\
-Header
-File: {}
-Content:
-let x = 1;
----
-File: {}
-Content:
-Unchanged content
----
-Footer",
-            file1_path.display(),
-            file2_path.display()
-        );
+        assert_eq!(result[0], "fn main() { ... }\n");
+
+        // The excerpts are not outlined
+        let options = QuaggaOptions {
+            outline: true,
+            contain: vec!["todo".to_string()],
+            excerpt: Some(0),
+            ..Default::default()
+        };
 
-        assert_eq!(content[0], expected);
+        let result = concatenate_file_contents(
+            file_contents(),
+            template,
+            &options,
+            &ArchiveCache::default(),
+            &TransformPipeline::from_options(&options).unwrap(),
+            &[],
+            &mut TransformReport::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            result[0],
+            "... (line 1 omitted)\n2:     // todo\n... (line 3 omitted)\n"
+        );
     }
 }
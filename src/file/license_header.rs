@@ -0,0 +1,384 @@
+use crate::file::archive::ArchiveCache;
+use crate::file::file_content::FileContent;
+use crate::file::file_reader::read_text_file;
+use crate::file::transform::{ContentTransform, TransformKind, TransformReport};
+use crate::options::QuaggaOptions;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+/// The words that mark a comment as a license header, in lowercase.
+const LICENSE_WORDS: &[&str] = &[
+    "copyright",
+    "license",
+    "licence",
+    "spdx-license-identifier",
+    "permission is hereby granted",
+    "all rights reserved",
+];
+
+/// The markers of line comments that can form a license header.
+const LINE_COMMENT_MARKERS: &[&str] = &["//", "#", "--", ";"];
+
+/// The markers of documentation comments, which are never part of a license header.
+const DOC_COMMENT_MARKERS: &[&str] = &["///", "//!"];
+
+/// The start and end markers of block comments that can form a license header.
+const BLOCK_COMMENT_MARKERS: &[(&str, &str)] = &[("/*", "*/"), ("<!--", "-->"), ("(*", "*)")];
+
+/// The number of files that must start with the same comment for it to be removed
+/// as a shared header, even if it does not mention a license.
+pub const MIN_SHARED_HEADER_FILES: usize = 3;
+
+/// The number of lines a shared header must have, so that short comments
+/// such as `// @ts-check` are kept.
+const MIN_SHARED_HEADER_LINES: usize = 2;
+
+/// Removes the license comment at the start of files, and the comments
+/// that start many of the files.
+#[derive(Debug, Clone, Default)]
+pub struct StripLicenseHeader {
+    shared: SharedHeaders,
+}
+
+impl StripLicenseHeader {
+    /// Creates the transform that also removes the `shared` header comments.
+    pub fn new(shared: SharedHeaders) -> StripLicenseHeader {
+        StripLicenseHeader { shared }
+    }
+}
+
+impl ContentTransform for StripLicenseHeader {
+    fn name(&self) -> &str {
+        "strip-license-header"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        FileContent {
+            content: strip_header(&file.content, &self.shared),
+            path: file.path,
+        }
+    }
+}
+
+/// The comments that the same files start with, such as a license notice
+/// copied to every file of a project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SharedHeaders {
+    /// The headers, with the whitespace at the ends of lines removed.
+    headers: HashSet<String>,
+}
+
+impl SharedHeaders {
+    /// Finds the comments of at least two lines that at least `MIN_SHARED_HEADER_FILES`
+    /// of the contents start with.
+    ///
+    /// # Arguments
+    ///
+    /// * `contents` - The contents of the files.
+    pub fn find<'a, I>(contents: I) -> SharedHeaders
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+
+        for content in contents {
+            let lines: Vec<&str> = content.split_inclusive('\n').collect();
+            let start = shebang_end(&lines);
+
+            let Some(end) = leading_comment_end(&lines, start) else {
+                continue;
+            };
+
+            if end - start >= MIN_SHARED_HEADER_LINES {
+                *counts.entry(normalize(&lines[start..end])).or_default() += 1;
+            }
+        }
+
+        let headers = counts
+            .into_iter()
+            .filter(|(_, count)| *count >= MIN_SHARED_HEADER_FILES)
+            .map(|(header, _)| header)
+            .collect();
+
+        SharedHeaders { headers }
+    }
+
+    /// Reads the files and finds their shared headers. The files that
+    /// can not be read are left out.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The paths to the files.
+    /// * `options` - The options the files are read with.
+    /// * `archives` - The cache of the archives read during the run.
+    pub fn read(
+        files: &[PathBuf],
+        options: &QuaggaOptions,
+        archives: &ArchiveCache,
+    ) -> SharedHeaders {
+        let contents: Vec<String> = files
+            .iter()
+            .filter_map(|path| {
+                read_text_file(path.clone(), options.binary, options.encoding, archives).ok()
+            })
+            .collect();
+
+        SharedHeaders::find(contents.iter().map(String::as_str))
+    }
+
+    /// Returns the number of shared headers.
+    pub fn len(&self) -> usize {
+        self.headers.len()
+    }
+
+    /// Returns `true` if no header is shared.
+    pub fn is_empty(&self) -> bool {
+        self.headers.is_empty()
+    }
+
+    fn contains(&self, lines: &[&str]) -> bool {
+        !self.headers.is_empty() && self.headers.contains(&normalize(lines))
+    }
+}
+
+/// Checks if license headers are removed with `--strip-license-headers`
+/// or `--transform strip-license-header`.
+pub fn strips_license_headers(options: &QuaggaOptions) -> bool {
+    options.strip_license_headers
+        || options
+            .transforms
+            .iter()
+            .any(|spec| spec.kind == TransformKind::StripLicenseHeader)
+}
+
+/// Removes the comment at the start of the content, if it mentions a copyright or
+/// a license, together with the blank lines after it. A shebang line (`#!`) is kept.
+///
+/// # Arguments
+///
+/// * `content` - The content of a file.
+///
+/// # Returns
+///
+/// The content without the license header, or the unchanged content if there is none.
+pub fn strip_license_header(content: &str) -> String {
+    strip_header(content, &SharedHeaders::default())
+}
+
+/// Removes the comment at the start of the content like `strip_license_header`,
+/// and also if it is one of the `shared` headers.
+pub fn strip_header(content: &str, shared: &SharedHeaders) -> String {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let start = shebang_end(&lines);
+
+    let Some(end) = leading_comment_end(&lines, start) else {
+        return content.to_string();
+    };
+
+    let header = lines[start..end].concat().to_lowercase();
+    let is_license = LICENSE_WORDS.iter().any(|word| header.contains(word));
+
+    if !is_license && !shared.contains(&lines[start..end]) {
+        return content.to_string();
+    }
+
+    let after_blank = (end..lines.len())
+        .find(|&index| !lines[index].trim().is_empty())
+        .unwrap_or(lines.len());
+
+    let mut output = lines[..start].concat();
+    output.push_str(&lines[after_blank..].concat());
+    output
+}
+
+/// Returns the index of the first line after the shebang (`#!`), or zero if there is none.
+fn shebang_end(lines: &[&str]) -> usize {
+    usize::from(lines.first().is_some_and(|line| line.starts_with("#!")))
+}
+
+/// Joins the lines with the whitespace at their ends removed, so that the same header
+/// with different line endings is considered equal.
+fn normalize(lines: &[&str]) -> String {
+    lines
+        .iter()
+        .map(|line| line.trim_end())
+        .collect::<Vec<&str>>()
+        .join("\n")
+}
+
+/// Returns the index after the last line of the comment starting at `start`,
+/// or `None` if the line at `start` does not start a comment. The comment is either
+/// a block comment or consecutive line comments with the same marker.
+pub fn leading_comment_end(lines: &[&str], start: usize) -> Option<usize> {
+    let first = lines.get(start)?.trim_start();
+
+    if let Some((start_marker, end_marker)) = BLOCK_COMMENT_MARKERS
+        .iter()
+        .find(|(start_marker, _)| first.starts_with(start_marker))
+    {
+        let end = (start..lines.len()).find(|&index| {
+            let line = if index == start {
+                // The end marker must come after the start marker on the first line
+                &first[start_marker.len()..]
+            } else {
+                lines[index]
+            };
+
+            line.contains(end_marker)
+        })?;
+
+        return Some(end + 1);
+    }
+
+    if DOC_COMMENT_MARKERS
+        .iter()
+        .any(|marker| first.starts_with(marker))
+    {
+        return None;
+    }
+
+    let marker = LINE_COMMENT_MARKERS
+        .iter()
+        .find(|marker| first.starts_with(**marker))?;
+
+    let end = (start..lines.len())
+        .find(|&index| {
+            let line = lines[index].trim_start();
+            !line.starts_with(marker)
+                || line.starts_with("#!")
+                || DOC_COMMENT_MARKERS.iter().any(|doc| line.starts_with(doc))
+        })
+        .unwrap_or(lines.len());
+
+    Some(end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_license_header_line_comments() {
+        let content = "\
+// Copyright 2024 Acme Inc.
+// Licensed under the MIT License.
+
+//! The crate docs.
+fn main() {}
+";
+
+        assert_eq!(
+            strip_license_header(content),
+            "//! The crate docs.\nfn main() {}\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_license_header_block_comment() {
+        let content = "\
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one
+ * or more contributor license agreements.
+ */
+package org.example;
+";
+
+        assert_eq!(strip_license_header(content), "package org.example;\n");
+    }
+
+    #[test]
+    fn test_strip_license_header_keeps_shebang() {
+        let content = "\
+#!/usr/bin/env python3
+# SPDX-License-Identifier: GPL-2.0-or-later
+# Copyright (C) 2020 Example
+
+import sys
+";
+
+        assert_eq!(
+            strip_license_header(content),
+            "#!/usr/bin/env python3\nimport sys\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_license_header_keeps_other_comments() {
+        let content = "// Entry point of the server.\nfn main() {}\n";
+        assert_eq!(strip_license_header(content), content);
+
+        let content = "/// Copyright notice for the users.\nfn copyright() {}\n";
+        assert_eq!(strip_license_header(content), content);
+
+        let content = "fn main() {}\n// Copyright 2024\n";
+        assert_eq!(strip_license_header(content), content);
+
+        assert_eq!(strip_license_header(""), "");
+    }
+
+    #[test]
+    fn test_strip_license_header_unclosed_block_comment() {
+        let content = "/* Copyright 2024\nfn main() {}\n";
+        assert_eq!(strip_license_header(content), content);
+    }
+
+    #[test]
+    fn test_shared_headers() {
+        let header = "/*\n * Part of the Acme platform.\n */\n";
+        let with_header = format!("{}\nfn main() {{}}\n", header);
+        let crlf_header = with_header.replace('\n', "\r\n");
+        let short = "// @ts-check\nlet a = 1;\n";
+
+        let contents = [
+            with_header.as_str(),
+            with_header.as_str(),
+            short,
+            short,
+            short,
+            "fn other() {}\n",
+        ];
+
+        let shared = SharedHeaders::find(contents);
+        assert!(shared.is_empty());
+
+        let shared = SharedHeaders::find([
+            with_header.as_str(),
+            with_header.as_str(),
+            crlf_header.as_str(),
+        ]);
+
+        assert_eq!(shared.len(), 1);
+        assert_eq!(strip_header(&with_header, &shared), "fn main() {}\n");
+        assert_eq!(strip_header(&crlf_header, &shared), "fn main() {}\r\n");
+
+        // Other comments are kept
+        let content = "/*\n * The entry point.\n */\nfn main() {}\n";
+        assert_eq!(strip_header(content, &shared), content);
+    }
+
+    #[test]
+    fn test_strip_license_header_mit() {
+        let content = "\
+# Permission is hereby granted, free of charge, to any person obtaining a copy
+# of this software and associated documentation files.
+import os
+";
+
+        assert_eq!(strip_license_header(content), "import os\n");
+    }
+
+    #[test]
+    fn test_strips_license_headers() {
+        assert!(!strips_license_headers(&QuaggaOptions::default()));
+
+        assert!(strips_license_headers(&QuaggaOptions {
+            strip_license_headers: true,
+            ..Default::default()
+        }));
+
+        assert!(strips_license_headers(&QuaggaOptions {
+            transforms: vec!["strip-license-header:*.rs".parse().unwrap()],
+            ..Default::default()
+        }));
+    }
+}
@@ -0,0 +1,443 @@
+use crate::file::file_content::FileContent;
+use crate::file::outline::{lines_ending_in_string, Language};
+use crate::file::transform::{ContentTransform, TransformReport};
+use std::path::Path;
+
+/// Replaces runs of blank lines with a single blank line.
+#[derive(Debug, Clone, Copy)]
+pub struct CollapseBlankLines;
+
+impl ContentTransform for CollapseBlankLines {
+    fn name(&self) -> &str {
+        "collapse-blank-lines"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        FileContent {
+            content: collapse_blank_lines(&file.content),
+            path: file.path,
+        }
+    }
+}
+
+/// Removes the whitespace at the ends of lines.
+#[derive(Debug, Clone, Copy)]
+pub struct TrimTrailingWhitespace;
+
+impl ContentTransform for TrimTrailingWhitespace {
+    fn name(&self) -> &str {
+        "trim-trailing-whitespace"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        FileContent {
+            content: trim_trailing_whitespace(&file.content),
+            path: file.path,
+        }
+    }
+}
+
+/// Replaces tabs with spaces up to the next tab stop. Makefiles and tab-separated
+/// values are unchanged, since the tabs are part of their syntax.
+#[derive(Debug, Clone, Copy)]
+pub struct TabsToSpaces {
+    width: usize,
+}
+
+impl TabsToSpaces {
+    /// Creates the transform with `width` spaces between tab stops.
+    pub fn new(width: usize) -> TabsToSpaces {
+        TabsToSpaces { width }
+    }
+}
+
+impl ContentTransform for TabsToSpaces {
+    fn name(&self) -> &str {
+        "tabs-to-spaces"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        if has_significant_tabs(&file.path) {
+            return file;
+        }
+
+        FileContent {
+            content: tabs_to_spaces(&file.content, self.width),
+            path: file.path,
+        }
+    }
+}
+
+/// Shortens the lines longer than the maximum length and notes how many characters
+/// were left out, e.g. `{"data": ... (1024 characters truncated)`.
+#[derive(Debug, Clone, Copy)]
+pub struct TruncateLines {
+    max_length: usize,
+}
+
+impl TruncateLines {
+    /// Creates the transform that keeps the first `max_length` characters of each line.
+    pub fn new(max_length: usize) -> TruncateLines {
+        TruncateLines { max_length }
+    }
+}
+
+impl ContentTransform for TruncateLines {
+    fn name(&self) -> &str {
+        "truncate-lines"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        FileContent {
+            content: truncate_lines(&file.content, self.max_length),
+            path: file.path,
+        }
+    }
+}
+
+/// Reduces the whitespace without changing the meaning of the content: removes the whitespace
+/// at the ends of lines, replaces runs of blank lines with a single blank line and optionally
+/// replaces leading spaces with tabs.
+///
+/// The indentation of Python, YAML and makefiles is kept, since it is part of their syntax,
+/// and so is the whitespace at the ends of lines in Markdown and makefiles. In the languages
+/// that can be outlined, the lines inside multiline strings are kept as well.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Compact {
+    tab_width: Option<usize>,
+}
+
+impl Compact {
+    /// Creates the transform that also replaces each `tab_width` leading spaces with a tab,
+    /// or keeps the indentation if it is `None`.
+    pub fn new(tab_width: Option<usize>) -> Compact {
+        Compact { tab_width }
+    }
+}
+
+impl ContentTransform for Compact {
+    fn name(&self) -> &str {
+        "compact"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        let trim = !has_significant_trailing_whitespace(&file.path);
+        let tab_width = self
+            .tab_width
+            .filter(|_| !has_significant_indentation(&file.path));
+
+        let in_string = Language::from_path(&file.path)
+            .map(|language| lines_ending_in_string(&file.content, language))
+            .unwrap_or_default();
+
+        let ends_in_string = |index: usize| in_string.get(index) == Some(&true);
+
+        let mut content = String::with_capacity(file.content.len());
+        let mut previous_blank = false;
+
+        for (index, line) in file.content.split_inclusive('\n').enumerate() {
+            // The whitespace at the start of a line inside a string, and at the end of a line
+            // whose line break is inside a string, is part of the string
+            let starts_in_string = index > 0 && ends_in_string(index - 1);
+            let mut line = line.to_string();
+
+            if trim && !ends_in_string(index) {
+                line = trim_trailing_whitespace(&line);
+            }
+
+            if let Some(width) = tab_width.filter(|_| !starts_in_string) {
+                line = leading_spaces_to_tabs(&line, width);
+            }
+
+            let is_blank = !starts_in_string && line.trim().is_empty();
+
+            if !(is_blank && previous_blank) {
+                content.push_str(&line);
+            }
+
+            previous_blank = is_blank;
+        }
+
+        FileContent {
+            content,
+            path: file.path,
+        }
+    }
+}
+
+/// Splits the line into its text and its line ending (`\n`, `\r\n` or none).
+pub fn split_line_ending(line: &str) -> (&str, &str) {
+    let text = line.trim_end_matches(['\n', '\r']);
+    (text, &line[text.len()..])
+}
+
+/// Replaces runs of blank lines with a single blank line. Lines with only whitespace
+/// are considered blank.
+pub fn collapse_blank_lines(content: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut previous_blank = false;
+
+    for line in content.split_inclusive('\n') {
+        let is_blank = line.trim().is_empty();
+
+        if !(is_blank && previous_blank) {
+            output.push_str(line);
+        }
+
+        previous_blank = is_blank;
+    }
+
+    output
+}
+
+/// Removes spaces and tabs at the ends of lines, keeping the line endings.
+pub fn trim_trailing_whitespace(content: &str) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let (text, ending) = split_line_ending(line);
+            format!("{}{}", text.trim_end(), ending)
+        })
+        .collect()
+}
+
+/// Replaces tabs with spaces up to the next tab stop, `width` columns apart.
+pub fn tabs_to_spaces(content: &str, width: usize) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut column = 0;
+
+    for char in content.chars() {
+        match char {
+            '\t' => {
+                let spaces = width - column % width;
+                output.extend(std::iter::repeat_n(' ', spaces));
+                column += spaces;
+            }
+            '\n' => {
+                output.push(char);
+                column = 0;
+            }
+            _ => {
+                output.push(char);
+                column += 1;
+            }
+        }
+    }
+
+    output
+}
+
+/// Replaces each `width` spaces at the start of lines with a tab. The spaces
+/// that don't make a full tab are kept, and so is the content if `width` is zero.
+pub fn leading_spaces_to_tabs(content: &str, width: usize) -> String {
+    if width == 0 {
+        return content.to_string();
+    }
+
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            let tabs = spaces / width;
+
+            format!("{}{}", "\t".repeat(tabs), &line[tabs * width..])
+        })
+        .collect()
+}
+
+/// Keeps the first `max_length` characters of the lines that are longer,
+/// followed by the number of characters left out.
+pub fn truncate_lines(content: &str, max_length: usize) -> String {
+    content
+        .split_inclusive('\n')
+        .map(|line| {
+            let (text, ending) = split_line_ending(line);
+            let length = text.chars().count();
+
+            if length <= max_length {
+                return line.to_string();
+            }
+
+            let kept: String = text.chars().take(max_length).collect();
+            let truncated = length - max_length;
+            let noun = if truncated == 1 {
+                "character"
+            } else {
+                "characters"
+            };
+
+            format!("{} ... ({} {} truncated){}", kept, truncated, noun, ending)
+        })
+        .collect()
+}
+
+/// Checks if the tabs in the file are part of its syntax: makefiles and tab-separated values.
+fn has_significant_tabs(path: &Path) -> bool {
+    is_makefile(path) || file_name(path).ends_with(".tsv")
+}
+
+/// Checks if the indentation of the file is part of its syntax: Python, YAML, makefiles
+/// and tab-separated values.
+fn has_significant_indentation(path: &Path) -> bool {
+    let name = file_name(path);
+
+    has_significant_tabs(path)
+        || [".py", ".pyi", ".pyw", ".yml", ".yaml"]
+            .iter()
+            .any(|extension| name.ends_with(extension))
+}
+
+/// Checks if the whitespace at the ends of lines is part of the syntax: line breaks
+/// in Markdown and values of variables in makefiles.
+fn has_significant_trailing_whitespace(path: &Path) -> bool {
+    let name = file_name(path);
+    is_makefile(path) || name.ends_with(".md") || name.ends_with(".markdown")
+}
+
+fn is_makefile(path: &Path) -> bool {
+    let name = file_name(path);
+
+    name == "makefile" || name == "gnumakefile" || name.ends_with(".mk") || name.ends_with(".mak")
+}
+
+/// Returns the lowercase file name.
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn file(path: &str, content: &str) -> FileContent {
+        FileContent {
+            path: PathBuf::from(path),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_collapse_blank_lines() {
+        let content = "one\n\n\n  \ntwo\r\n\r\n\r\nthree\n\n";
+        assert_eq!(collapse_blank_lines(content), "one\n\ntwo\r\n\r\nthree\n\n");
+        assert_eq!(collapse_blank_lines(""), "");
+    }
+
+    #[test]
+    fn test_trim_trailing_whitespace() {
+        let content = "one  \ntwo\t\r\n  three \nfour";
+        assert_eq!(
+            trim_trailing_whitespace(content),
+            "one\ntwo\r\n  three\nfour"
+        );
+    }
+
+    #[test]
+    fn test_tabs_to_spaces() {
+        assert_eq!(tabs_to_spaces("\tone\n\t\ttwo", 4), "    one\n        two");
+        assert_eq!(tabs_to_spaces("ab\tc", 4), "ab  c");
+        assert_eq!(tabs_to_spaces("a\tb", 2), "a b");
+    }
+
+    #[test]
+    fn test_tabs_to_spaces_keeps_makefiles() {
+        let transform = TabsToSpaces::new(4);
+        let mut report = TransformReport::default();
+
+        let result = transform.transform(file("Makefile", "all:\n\tcargo build"), &mut report);
+        assert_eq!(result.content, "all:\n\tcargo build");
+
+        let result = transform.transform(file("data.tsv", "a\tb"), &mut report);
+        assert_eq!(result.content, "a\tb");
+
+        let result = transform.transform(file("main.go", "\treturn"), &mut report);
+        assert_eq!(result.content, "    return");
+    }
+
+    #[test]
+    fn test_truncate_lines() {
+        let content = "short\nabcdefghij\r\nnext";
+
+        assert_eq!(
+            truncate_lines(content, 5),
+            "short\nabcde ... (5 characters truncated)\r\nnext"
+        );
+
+        assert_eq!(truncate_lines("ééé", 2), "éé ... (1 character truncated)");
+    }
+
+    #[test]
+    fn test_leading_spaces_to_tabs() {
+        let content = "fn main() {\n    if x {\n        run();\n   }\n  \t y\n}\n";
+
+        assert_eq!(
+            leading_spaces_to_tabs(content, 4),
+            "fn main() {\n\tif x {\n\t\trun();\n   }\n  \t y\n}\n"
+        );
+
+        assert_eq!(leading_spaces_to_tabs("  a\n    b", 2), "\ta\n\t\tb");
+    }
+
+    #[test]
+    fn test_compact() {
+        let content = "fn main() {  \n\n\n    run();\t\n}\n";
+        let mut report = TransformReport::default();
+
+        let result = Compact::new(None).transform(file("main.rs", content), &mut report);
+        assert_eq!(result.content, "fn main() {\n\n    run();\n}\n");
+
+        let result = Compact::new(Some(4)).transform(file("main.rs", content), &mut report);
+        assert_eq!(result.content, "fn main() {\n\n\trun();\n}\n");
+    }
+
+    #[test]
+    fn test_compact_keeps_significant_whitespace() {
+        let compact = Compact::new(Some(4));
+        let mut report = TransformReport::default();
+
+        let content = "def run():  \n\n\n    if x:\n        return\n";
+        let result = compact.transform(file("app.py", content), &mut report);
+        assert_eq!(result.content, "def run():\n\n    if x:\n        return\n");
+
+        let content = "jobs:\n    build:\n        runs-on: linux\n";
+        let result = compact.transform(file("ci.yaml", content), &mut report);
+        assert_eq!(result.content, content);
+
+        let content = "NAME = app \nall:\n\tbuild    $(NAME)\n";
+        let result = compact.transform(file("Makefile", content), &mut report);
+        assert_eq!(result.content, content);
+
+        let content = "First line  \nsecond line\n\n\n    code\n";
+        let result = compact.transform(file("README.md", content), &mut report);
+        assert_eq!(result.content, "First line  \nsecond line\n\n\tcode\n");
+    }
+
+    #[test]
+    fn test_compact_keeps_multiline_strings() {
+        let compact = Compact::new(Some(4));
+        let mut report = TransformReport::default();
+
+        let content =
+            "def run():  \n    text = \"\"\"one  \n\n\n    two\"\"\"  \n\n\n    return text\n";
+        let result = compact.transform(file("app.py", content), &mut report);
+        assert_eq!(
+            result.content,
+            "def run():\n    text = \"\"\"one  \n\n\n    two\"\"\"\n\n    return text\n"
+        );
+
+        let content = "let a = r\"one  \n\n\n    two\";  \n\n\n    run();\n";
+        let result = compact.transform(file("main.rs", content), &mut report);
+        assert_eq!(
+            result.content,
+            "let a = r\"one  \n\n\n    two\";\n\n\trun();\n"
+        );
+
+        // Other languages are not parsed, so their strings are compacted
+        let content = "a = <<EOS  \n\n\nEOS\n";
+        let result = compact.transform(file("app.rb", content), &mut report);
+        assert_eq!(result.content, "a = <<EOS\n\nEOS\n");
+    }
+}
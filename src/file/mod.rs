@@ -1,4 +1,17 @@
+pub mod archive;
+pub mod cache;
 pub mod comment_remover;
+pub mod encoding;
+pub mod excerpt;
 pub mod file_content;
 pub mod file_reader;
+pub mod license_header;
+pub mod line_transforms;
+pub mod notebook;
+pub mod outline;
+pub mod path_style;
+pub mod redact;
 pub mod size;
+pub mod skipped_file;
+pub mod sort;
+pub mod transform;
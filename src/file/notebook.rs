@@ -0,0 +1,261 @@
+use serde_json::Value;
+use std::path::Path;
+
+/// The MIME types of the outputs that are shown as text, in the order of preference.
+/// Other outputs, such as images and HTML, are dropped.
+const TEXT_OUTPUT_TYPES: &[&str] = &["text/plain", "text/markdown"];
+
+/// Checks if the file is a Jupyter notebook by its extension.
+pub fn is_notebook(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("ipynb"))
+}
+
+/// Converts the content of the file to readable text if it is a Jupyter notebook,
+/// or returns it unchanged otherwise.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `content` - The content of the file.
+/// * `outputs` - The maximum number of characters shown from each text output of the cells,
+///   or `None` to leave the outputs out.
+pub fn convert_notebook(path: &Path, content: String, outputs: Option<usize>) -> String {
+    if !is_notebook(path) {
+        return content;
+    }
+
+    notebook_to_text(&content, outputs).unwrap_or(content)
+}
+
+/// Converts the JSON of a Jupyter notebook into text, cell by cell. Each cell starts
+/// with a `# %% [markdown]`, `# %% [code]` or `# %% [raw]` line, and the text outputs
+/// of a code cell follow it under `# %% [output]`. Images are dropped.
+///
+/// # Arguments
+///
+/// * `json` - The content of the notebook file.
+/// * `outputs` - The maximum number of characters shown from each text output,
+///   or `None` to leave the outputs out.
+///
+/// # Returns
+///
+/// The text of the notebook, or `None` if the content is not a notebook in the nbformat 4 format.
+pub fn notebook_to_text(json: &str, outputs: Option<usize>) -> Option<String> {
+    let notebook: Value = serde_json::from_str(json).ok()?;
+    let cells = notebook.get("cells")?.as_array()?;
+    let mut sections = Vec::new();
+
+    for cell in cells {
+        let cell_type = cell
+            .get("cell_type")
+            .and_then(Value::as_str)
+            .unwrap_or("raw");
+
+        let source = cell.get("source").map(multiline_text).unwrap_or_default();
+        sections.push(format!("# %% [{}]\n{}", cell_type, source.trim_end()));
+
+        let Some(max_length) = outputs else {
+            continue;
+        };
+
+        let texts: Vec<String> = cell
+            .get("outputs")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(output_text)
+            .map(|text| truncate(text.trim_end(), max_length))
+            .filter(|text| !text.is_empty())
+            .collect();
+
+        if !texts.is_empty() {
+            sections.push(format!("# %% [output]\n{}", texts.join("\n")));
+        }
+    }
+
+    let mut text = sections.join("\n\n");
+    text.push('\n');
+    Some(text)
+}
+
+/// Returns the text of a cell output, or `None` if it has no text, e.g. an image.
+fn output_text(output: &Value) -> Option<String> {
+    match output.get("output_type")?.as_str()? {
+        "stream" => output.get("text").map(multiline_text),
+        "execute_result" | "display_data" => {
+            let data = output.get("data")?;
+
+            TEXT_OUTPUT_TYPES
+                .iter()
+                .find_map(|mime_type| data.get(*mime_type))
+                .map(multiline_text)
+        }
+        "error" => {
+            let name = output.get("ename")?.as_str()?;
+            let value = output.get("evalue").and_then(Value::as_str).unwrap_or("");
+            Some(format!("{}: {}", name, value))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the text of a notebook field, which is either a string or a list of lines.
+fn multiline_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Keeps the first `max_length` characters of the text, followed by the number
+/// of characters left out.
+fn truncate(text: &str, max_length: usize) -> String {
+    let length = text.chars().count();
+
+    if length <= max_length {
+        return text.to_string();
+    }
+
+    let kept: String = text.chars().take(max_length).collect();
+    let truncated = length - max_length;
+    let noun = if truncated == 1 {
+        "character"
+    } else {
+        "characters"
+    };
+
+    format!(
+        "{}\n... ({} {} truncated)",
+        kept.trim_end(),
+        truncated,
+        noun
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTEBOOK: &str = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": ["# Analysis\n", "\n", "Loads the data."]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [
+    {"name": "stdout", "output_type": "stream", "text": ["Loaded 3 rows\n"]},
+    {
+     "data": {"image/png": "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mNk", "text/plain": ["<Figure size 640x480>"]},
+     "output_type": "display_data"
+    },
+    {
+     "data": {"text/plain": "[1, 2, 3]"},
+     "execution_count": 1,
+     "output_type": "execute_result"
+    }
+   ],
+   "source": "import data\ndata.load()"
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 2,
+   "metadata": {},
+   "outputs": [
+    {"data": {"image/png": "iVBORw0KGgo"}, "output_type": "display_data"},
+    {"ename": "ValueError", "evalue": "bad value", "output_type": "error", "traceback": ["\u001b[0;31m"]}
+   ],
+   "source": ["plot()"]
+  }
+ ],
+ "metadata": {"kernelspec": {"language": "python", "name": "python3"}},
+ "nbformat": 4,
+ "nbformat_minor": 5
+}"##;
+
+    #[test]
+    fn test_notebook_to_text_without_outputs() {
+        let expected = "\
+# %% [markdown]
+# Analysis
+
+Loads the data.
+
+# %% [code]
+import data
+data.load()
+
+# %% [code]
+plot()
+";
+
+        assert_eq!(notebook_to_text(NOTEBOOK, None).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_notebook_to_text_with_outputs() {
+        let expected = "\
+# %% [markdown]
+# Analysis
+
+Loads the data.
+
+# %% [code]
+import data
+data.load()
+
+# %% [output]
+Loaded 3 rows
+<Figure size 640x480>
+[1, 2, 3]
+
+# %% [code]
+plot()
+
+# %% [output]
+ValueError: bad value
+";
+
+        assert_eq!(notebook_to_text(NOTEBOOK, Some(100)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_notebook_to_text_truncates_outputs() {
+        let text = notebook_to_text(NOTEBOOK, Some(6)).unwrap();
+
+        assert!(text.contains("# %% [output]\nLoaded\n... (7 characters truncated)\n"));
+        assert!(text.contains("\n[1, 2,\n... (3 characters truncated)\n"));
+    }
+
+    #[test]
+    fn test_notebook_to_text_invalid() {
+        assert_eq!(notebook_to_text("not json", None), None);
+        assert_eq!(notebook_to_text(r#"{"worksheets": []}"#, None), None);
+    }
+
+    #[test]
+    fn test_convert_notebook() {
+        let path = Path::new("analysis.IPYNB");
+        let result = convert_notebook(path, NOTEBOOK.to_string(), None);
+        assert!(result.starts_with("# %% [markdown]\n"));
+
+        // Other files and invalid notebooks are unchanged
+        let content = "{\"cells\": []}".to_string();
+        assert_eq!(
+            convert_notebook(Path::new("cells.json"), content.clone(), None),
+            content
+        );
+
+        let content = "not json".to_string();
+        assert_eq!(
+            convert_notebook(Path::new("broken.ipynb"), content.clone(), None),
+            content
+        );
+    }
+}
@@ -0,0 +1,1036 @@
+use crate::file::file_content::FileContent;
+use crate::file::transform::{ContentTransform, TransformReport};
+use std::path::Path;
+
+/// The text that replaces the body of a function in the languages with braces.
+const ELIDED_BODY: &str = "{ ... }";
+
+/// The words that start a statement with a block, which is not a function body.
+const CONTROL_KEYWORDS: &[&str] = &[
+    "if",
+    "else",
+    "for",
+    "while",
+    "do",
+    "switch",
+    "try",
+    "catch",
+    "finally",
+    "with",
+    "synchronized",
+    "return",
+];
+
+/// The words that start a block with members, which is outlined instead of removed.
+const TYPE_KEYWORDS: &[&str] = &["class", "interface", "enum", "record", "new"];
+
+/// The languages that can be outlined.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Language {
+    Rust,
+    JavaScript,
+    Go,
+    Java,
+    Python,
+}
+
+impl Language {
+    /// Returns the language of the file from its extension, or `None` if the file
+    /// can not be outlined. TypeScript files use the JavaScript rules.
+    pub fn from_path(path: &Path) -> Option<Language> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+
+        match extension.as_str() {
+            "rs" => Some(Language::Rust),
+            "js" | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "mts" | "cts" => {
+                Some(Language::JavaScript)
+            }
+            "go" => Some(Language::Go),
+            "java" => Some(Language::Java),
+            "py" | "pyi" => Some(Language::Python),
+            _ => None,
+        }
+    }
+}
+
+/// Replaces the bodies of functions with `{ ... }` (or `...` in Python), keeping the
+/// type definitions, function signatures, trait and impl headers and doc comments.
+/// Files in other languages are unchanged.
+#[derive(Debug, Clone, Copy)]
+pub struct Outline;
+
+impl ContentTransform for Outline {
+    fn name(&self) -> &str {
+        "outline"
+    }
+
+    fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+        let Some(language) = Language::from_path(&file.path) else {
+            return file;
+        };
+
+        FileContent {
+            content: outline(&file.content, language),
+            path: file.path,
+        }
+    }
+}
+
+/// Returns the outline of the source code.
+///
+/// # Arguments
+///
+/// * `content` - The source code.
+/// * `language` - The language of the source code.
+///
+/// # Returns
+///
+/// The source code with the bodies of functions replaced with `{ ... }`,
+/// or with `...` after the docstring in Python.
+pub fn outline(content: &str, language: Language) -> String {
+    match language {
+        Language::Python => outline_python(content),
+        _ => outline_braces(content, language),
+    }
+}
+
+/// Finds the line breaks that are inside multiline strings, such as Python docstrings,
+/// Rust raw strings or JavaScript template literals. The whitespace around these
+/// line breaks is part of the string.
+///
+/// # Returns
+///
+/// For each line of the content that ends with a line break, whether the line break
+/// is inside a string.
+pub fn lines_ending_in_string(content: &str, language: Language) -> Vec<bool> {
+    if language == Language::Python {
+        return content
+            .split_inclusive('\n')
+            .filter(|line| line.ends_with('\n'))
+            .scan(None, |open_quote, line| {
+                *open_quote = triple_quote_state(line, *open_quote);
+                Some(open_quote.is_some())
+            })
+            .collect();
+    }
+
+    let chars: Vec<char> = content.chars().collect();
+    let mut lines = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        match literal_end(&chars, index, language) {
+            Some((end, is_comment)) => {
+                let line_breaks = chars[index..end].iter().filter(|&&char| char == '\n');
+                lines.extend(line_breaks.map(|_| !is_comment));
+                index = end;
+            }
+            None => {
+                if chars[index] == '\n' {
+                    lines.push(false);
+                }
+
+                index += 1;
+            }
+        }
+    }
+
+    lines
+}
+
+/// Outlines the languages with blocks in braces. The header of each block, the code since
+/// the previous `;`, `{` or `}`, decides if the block is a function body, which is removed,
+/// or a type, impl or module, whose members are outlined.
+fn outline_braces(content: &str, language: Language) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut output = String::with_capacity(content.len());
+    let mut header = String::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        if let Some((end, is_comment)) = literal_end(&chars, index, language) {
+            output.extend(&chars[index..end]);
+
+            // The contents of strings could look like code, so only a placeholder is kept
+            if !is_comment {
+                header.push_str("\"\"");
+            }
+
+            index = end;
+            continue;
+        }
+
+        let char = chars[index];
+
+        match char {
+            '{' if is_function_header(&header, language) => {
+                output.push_str(ELIDED_BODY);
+                index = matching_brace_end(&chars, index, language);
+                header.clear();
+                continue;
+            }
+            '{' | '}' | ';' => {
+                output.push(char);
+                header.clear();
+            }
+            _ => {
+                output.push(char);
+                header.push(char);
+            }
+        }
+
+        index += 1;
+    }
+
+    output
+}
+
+/// Returns the index after the `}` that closes the block opened at `start`,
+/// or the end of the code if the block is not closed.
+fn matching_brace_end(chars: &[char], start: usize, language: Language) -> usize {
+    let mut depth = 0;
+    let mut index = start;
+
+    while index < chars.len() {
+        if let Some((end, _)) = literal_end(chars, index, language) {
+            index = end;
+            continue;
+        }
+
+        match chars[index] {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+
+                if depth == 0 {
+                    return index + 1;
+                }
+            }
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    chars.len()
+}
+
+/// Checks if a comment or a string starts at `start`.
+///
+/// # Returns
+///
+/// The index after the end of the comment or string and whether it is a comment,
+/// or `None` if neither starts at `start`.
+fn literal_end(chars: &[char], start: usize, language: Language) -> Option<(usize, bool)> {
+    let char = chars[start];
+    let next = chars.get(start + 1).copied();
+
+    match (char, next) {
+        ('/', Some('/')) => {
+            let end = find_from(chars, start, |char| char == '\n').unwrap_or(chars.len());
+            Some((end, true))
+        }
+        ('/', Some('*')) => Some((block_comment_end(chars, start, language), true)),
+        ('"', _) => Some((quoted_end(chars, start, '"', false), false)),
+        ('\'', _) if language == Language::Rust => {
+            rust_char_end(chars, start).map(|end| (end, false))
+        }
+        ('\'', _) => Some((quoted_end(chars, start, '\'', true), false)),
+        ('`', _) if language == Language::JavaScript => {
+            Some((quoted_end(chars, start, '`', false), false))
+        }
+        ('`', _) if language == Language::Go => Some((raw_end(chars, start), false)),
+        ('r' | 'b', _) if language == Language::Rust => {
+            rust_raw_string_end(chars, start).map(|end| (end, false))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the index after the closing `quote` of the string that starts at `start`.
+/// Escaped quotes are skipped. Strings in single quotes end at the end of the line
+/// when `ends_at_line` is true, so that apostrophes in code do not hide the rest of the file.
+fn quoted_end(chars: &[char], start: usize, quote: char, ends_at_line: bool) -> usize {
+    let mut index = start + 1;
+
+    while index < chars.len() {
+        match chars[index] {
+            '\\' => index += 1,
+            '\n' if ends_at_line => return index,
+            char if char == quote => return index + 1,
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    chars.len()
+}
+
+/// Returns the index after the closing backtick of a Go raw string, which has no escapes.
+fn raw_end(chars: &[char], start: usize) -> usize {
+    find_from(chars, start + 1, |char| char == '`').map_or(chars.len(), |end| end + 1)
+}
+
+/// Returns the index after the end of the block comment that starts at `start`.
+/// Block comments can be nested in Rust.
+fn block_comment_end(chars: &[char], start: usize, language: Language) -> usize {
+    let mut depth = 0;
+    let mut index = start;
+
+    while index + 1 < chars.len() {
+        match (chars[index], chars[index + 1]) {
+            ('/', '*') if depth == 0 || language == Language::Rust => {
+                depth += 1;
+                index += 2;
+            }
+            ('*', '/') => {
+                depth -= 1;
+                index += 2;
+
+                if depth == 0 {
+                    return index;
+                }
+            }
+            _ => index += 1,
+        }
+    }
+
+    chars.len()
+}
+
+/// Returns the index after the end of a Rust character literal, such as `'{'` or `'\n'`,
+/// or `None` if the quote starts a lifetime, such as `'a`.
+fn rust_char_end(chars: &[char], start: usize) -> Option<usize> {
+    match chars.get(start + 1) {
+        Some('\\') => Some(quoted_end(chars, start, '\'', true)),
+        Some(_) if chars.get(start + 2) == Some(&'\'') => Some(start + 3),
+        _ => None,
+    }
+}
+
+/// Returns the index after the end of a Rust raw string, such as `r#"..."#` or `br"..."`,
+/// or `None` if a raw string does not start at `start`.
+fn rust_raw_string_end(chars: &[char], start: usize) -> Option<usize> {
+    if start > 0 && is_identifier_char(chars[start - 1]) {
+        return None;
+    }
+
+    let mut index = start;
+
+    if chars[index] == 'b' {
+        index += 1;
+    }
+
+    if chars.get(index) != Some(&'r') {
+        return None;
+    }
+
+    index += 1;
+    let hashes = chars[index..]
+        .iter()
+        .take_while(|&&char| char == '#')
+        .count();
+    index += hashes;
+
+    if chars.get(index) != Some(&'"') {
+        return None;
+    }
+
+    let closing: Vec<char> = std::iter::once('"')
+        .chain(std::iter::repeat_n('#', hashes))
+        .collect();
+
+    let end = (index + 1..chars.len())
+        .find(|&position| chars[position..].starts_with(&closing))
+        .map_or(chars.len(), |position| position + closing.len());
+
+    Some(end)
+}
+
+/// Returns the index of the first character from `start` that matches the `predicate`.
+fn find_from(chars: &[char], start: usize, predicate: impl Fn(char) -> bool) -> Option<usize> {
+    (start..chars.len()).find(|&index| predicate(chars[index]))
+}
+
+fn is_identifier_char(char: char) -> bool {
+    char.is_alphanumeric() || char == '_' || char == '$'
+}
+
+/// Checks if the block with the header is the body of a function.
+///
+/// # Arguments
+///
+/// * `header` - The code before the `{`, since the previous `;`, `{` or `}`,
+///   without comments and with placeholders instead of strings.
+/// * `language` - The language of the code.
+fn is_function_header(header: &str, language: Language) -> bool {
+    let header = header.trim();
+
+    if header.is_empty() {
+        return false;
+    }
+
+    let has_word = |word: &str| {
+        header
+            .split(|char: char| !is_identifier_char(char))
+            .any(|part| part == word)
+    };
+
+    match language {
+        Language::Rust => has_word("fn"),
+        Language::Go => has_word("func"),
+        Language::JavaScript => {
+            has_word("function") || header.ends_with("=>") || is_method_header(header)
+        }
+        Language::Java => header.ends_with("->") || is_method_header(header),
+        Language::Python => false,
+    }
+}
+
+/// Checks if the header is a method or a function with a name followed by parameters,
+/// e.g. `public static int sum(int a, int b) throws Exception` or `async load(id: string): Promise<User>`.
+fn is_method_header(header: &str) -> bool {
+    let words: Vec<&str> = header
+        .split(|char: char| !is_identifier_char(char))
+        .filter(|word| !word.is_empty())
+        .collect();
+
+    let Some(first) = words.first() else {
+        return false;
+    };
+
+    if CONTROL_KEYWORDS.contains(first) || words.iter().any(|word| TYPE_KEYWORDS.contains(word)) {
+        return false;
+    }
+
+    let Some((open, close)) = last_parentheses(header) else {
+        return false;
+    };
+
+    let tail = &header[close + 1..];
+
+    if tail.contains(['(', '=']) {
+        return false;
+    }
+
+    // The parameters follow the name of the function or its type parameters
+    header[..open]
+        .trim_end()
+        .ends_with(|char: char| is_identifier_char(char) || char == '>')
+}
+
+/// Returns the positions of the last pair of top-level parentheses in the header,
+/// or `None` if the header has no parentheses or they are not balanced.
+fn last_parentheses(header: &str) -> Option<(usize, usize)> {
+    let mut depth = 0;
+    let mut open = None;
+    let mut last = None;
+
+    for (index, char) in header.char_indices() {
+        match char {
+            '(' => {
+                if depth == 0 {
+                    open = Some(index);
+                }
+
+                depth += 1;
+            }
+            ')' => {
+                if depth == 0 {
+                    return None;
+                }
+
+                depth -= 1;
+
+                if depth == 0 {
+                    last = open.map(|open| (open, index));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if depth != 0 {
+        return None;
+    }
+
+    last
+}
+
+/// Outlines Python code. The body of each `def` is replaced with `...`,
+/// keeping its docstring. The bodies of classes are outlined.
+fn outline_python(content: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut output: Vec<String> = Vec::new();
+    let mut open_quote = None;
+    let mut index = 0;
+
+    while index < lines.len() {
+        let line = lines[index];
+
+        if open_quote.is_some() || !is_python_def(line) {
+            open_quote = triple_quote_state(line, open_quote);
+            output.push(line.to_string());
+            index += 1;
+            continue;
+        }
+
+        let indent = indentation(line);
+        let header_end = python_header_end(&lines, index);
+        output.extend(
+            lines[index..=header_end]
+                .iter()
+                .map(|line| line.to_string()),
+        );
+        index = header_end + 1;
+
+        // A function with the body on the same line, e.g. `def name(self): return self._name`
+        if !ends_with_colon(lines[header_end]) {
+            continue;
+        }
+
+        let body_end = python_block_end(&lines, index, indent);
+        let body = &lines[index..body_end];
+        let Some(first) = body.iter().position(|line| !line.trim().is_empty()) else {
+            continue;
+        };
+
+        let docstring_end = python_docstring_end(body, first);
+        output.extend(body[..docstring_end].iter().map(|line| line.to_string()));
+
+        let body_indent = &body[first][..indentation(body[first])];
+        output.push(format!("{}...", body_indent));
+        index = body_end;
+    }
+
+    let mut outlined = output.join("\n");
+
+    if content.ends_with('\n') {
+        outlined.push('\n');
+    }
+
+    outlined
+}
+
+/// Checks if the line starts a Python function.
+fn is_python_def(line: &str) -> bool {
+    let trimmed = line.trim_start();
+    trimmed.starts_with("def ") || trimmed.starts_with("async def ")
+}
+
+/// Returns the number of bytes of the leading whitespace of the line.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Returns the index of the last line of the function signature starting at `start`,
+/// where all parentheses of the parameters are closed.
+fn python_header_end(lines: &[&str], start: usize) -> usize {
+    let mut depth: i32 = 0;
+
+    for (index, line) in lines.iter().enumerate().skip(start) {
+        for char in line.chars() {
+            match char {
+                '(' | '[' | '{' => depth += 1,
+                ')' | ']' | '}' => depth -= 1,
+                '#' => break,
+                _ => {}
+            }
+        }
+
+        if depth <= 0 {
+            return index;
+        }
+    }
+
+    lines.len() - 1
+}
+
+/// Checks if the last line of the signature ends with `:`, ignoring a comment after it.
+fn ends_with_colon(line: &str) -> bool {
+    let tail = match line.rfind(')') {
+        Some(position) => &line[position..],
+        None => line,
+    };
+
+    let code = tail.split('#').next().unwrap_or(tail);
+    code.trim_end().ends_with(':')
+}
+
+/// Returns the index after the last line of the block that starts at `start`,
+/// which consists of the lines indented more than `indent`. Empty lines at the end
+/// of the block are not included, and the lines of multiline strings are always included.
+fn python_block_end(lines: &[&str], start: usize, indent: usize) -> usize {
+    let mut open_quote = None;
+    let mut end = start;
+    let mut index = start;
+
+    while index < lines.len() {
+        let line = lines[index];
+        let is_blank = line.trim().is_empty();
+
+        if open_quote.is_none() && !is_blank && indentation(line) <= indent {
+            break;
+        }
+
+        open_quote = triple_quote_state(line, open_quote);
+        index += 1;
+
+        if !is_blank {
+            end = index;
+        }
+    }
+
+    end
+}
+
+/// Returns the index after the last line of the docstring, if the first statement
+/// of the body at `first` is a string, or `first` otherwise.
+fn python_docstring_end(body: &[&str], first: usize) -> usize {
+    let line = body[first].trim_start();
+    let unprefixed = line.trim_start_matches(['r', 'R', 'u', 'U', 'b', 'B', 'f', 'F']);
+
+    if !unprefixed.starts_with(['"', '\'']) {
+        return first;
+    }
+
+    let mut open_quote = triple_quote_state(line, None);
+    let mut index = first + 1;
+
+    while open_quote.is_some() && index < body.len() {
+        open_quote = triple_quote_state(body[index], open_quote);
+        index += 1;
+    }
+
+    index
+}
+
+/// Returns the triple quote of the multiline string that is still open
+/// after the line, or `None` if no string is open.
+///
+/// # Arguments
+///
+/// * `line` - A line of Python code.
+/// * `open_quote` - The triple quote of the string that is open before the line.
+fn triple_quote_state(line: &str, mut open_quote: Option<&'static str>) -> Option<&'static str> {
+    let mut rest = line;
+
+    loop {
+        match open_quote {
+            Some(quote) => match rest.find(quote) {
+                Some(position) => {
+                    rest = &rest[position + quote.len()..];
+                    open_quote = None;
+                }
+                None => return open_quote,
+            },
+            None => {
+                let next = ["\"\"\"", "'''"]
+                    .into_iter()
+                    .filter_map(|quote| rest.find(quote).map(|position| (position, quote)))
+                    .min();
+
+                match next {
+                    Some((position, quote)) => {
+                        rest = &rest[position + quote.len()..];
+                        open_quote = Some(quote);
+                    }
+                    None => return None,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn file(path: &str, content: &str) -> FileContent {
+        FileContent {
+            path: PathBuf::from(path),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(
+            Language::from_path(Path::new("src/main.rs")),
+            Some(Language::Rust)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("app.TSX")),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("main.go")),
+            Some(Language::Go)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("App.java")),
+            Some(Language::Java)
+        );
+        assert_eq!(
+            Language::from_path(Path::new("app.py")),
+            Some(Language::Python)
+        );
+        assert_eq!(Language::from_path(Path::new("README.md")), None);
+        assert_eq!(Language::from_path(Path::new("Makefile")), None);
+    }
+
+    #[test]
+    fn test_outline_rust() {
+        let code = r##"use std::fmt;
+
+/// A point on a plane.
+#[derive(Debug)]
+pub struct Point<'a> {
+    pub name: &'a str,
+}
+
+impl<'a> Point<'a> {
+    /// Creates a point.
+    pub fn new(name: &'a str) -> Self {
+        let brace = '{';
+        let text = r#"}"#;
+        Point { name }
+    }
+
+    fn helper(&self) where Self: Sized {
+        // }
+        /* { nested /* } */ } */
+    }
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+
+    fn describe(&self) -> String {
+        format!("{}", self.area())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_new() {
+        assert!(true);
+    }
+}
+"##;
+
+        let expected = r#"use std::fmt;
+
+/// A point on a plane.
+#[derive(Debug)]
+pub struct Point<'a> {
+    pub name: &'a str,
+}
+
+impl<'a> Point<'a> {
+    /// Creates a point.
+    pub fn new(name: &'a str) -> Self { ... }
+
+    fn helper(&self) where Self: Sized { ... }
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+
+    fn describe(&self) -> String { ... }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_new() { ... }
+}
+"#;
+
+        assert_eq!(outline(code, Language::Rust), expected);
+    }
+
+    #[test]
+    fn test_outline_javascript() {
+        let code = r#"import { load } from "./load";
+
+/** The user profile. */
+export interface User {
+  id: string;
+  tags: string[];
+}
+
+export class UserService extends Service<User> {
+  private cache = {};
+
+  constructor(api) {
+    super(api);
+  }
+
+  /** Loads a user by id. */
+  async load(id: string): Promise<User> {
+    if (id) {
+      return this.api.get(`/users/${id}`);
+    }
+  }
+
+  onChange = (user) => {
+    this.cache[user.id] = user;
+  };
+}
+
+export function format(user) {
+  return "{" + user.id;
+}
+
+const config = {
+  retries: 3,
+};
+"#;
+
+        let expected = r#"import { load } from "./load";
+
+/** The user profile. */
+export interface User {
+  id: string;
+  tags: string[];
+}
+
+export class UserService extends Service<User> {
+  private cache = {};
+
+  constructor(api) { ... }
+
+  /** Loads a user by id. */
+  async load(id: string): Promise<User> { ... }
+
+  onChange = (user) => { ... };
+}
+
+export function format(user) { ... }
+
+const config = {
+  retries: 3,
+};
+"#;
+
+        assert_eq!(outline(code, Language::JavaScript), expected);
+    }
+
+    #[test]
+    fn test_outline_go() {
+        let code = r#"package server
+
+// Server handles the requests.
+type Server struct {
+	Addr string
+}
+
+// Start starts the server.
+func (s *Server) Start(ctx context.Context) (int, error) {
+	if s.Addr == "" {
+		return 0, errors.New("}")
+	}
+	return 1, nil
+}
+
+var query = `{`
+"#;
+
+        let expected = r#"package server
+
+// Server handles the requests.
+type Server struct {
+	Addr string
+}
+
+// Start starts the server.
+func (s *Server) Start(ctx context.Context) (int, error) { ... }
+
+var query = `{`
+"#;
+
+        assert_eq!(outline(code, Language::Go), expected);
+    }
+
+    #[test]
+    fn test_outline_java() {
+        let code = r#"package app;
+
+/**
+ * A bank account.
+ */
+public class Account implements Comparable<Account> {
+    private final String id;
+
+    public Account(String id) {
+        this.id = id;
+    }
+
+    /** Returns the balance. */
+    @Override
+    public <T> long balance(T unit) throws IOException {
+        synchronized (this) {
+            return 0;
+        }
+    }
+
+    public enum Kind {
+        SAVINGS, CHECKING;
+
+        Kind() {
+            System.out.println('}');
+        }
+    }
+
+    private Runnable task = () -> {
+        run();
+    };
+}
+"#;
+
+        let expected = r#"package app;
+
+/**
+ * A bank account.
+ */
+public class Account implements Comparable<Account> {
+    private final String id;
+
+    public Account(String id) { ... }
+
+    /** Returns the balance. */
+    @Override
+    public <T> long balance(T unit) throws IOException { ... }
+
+    public enum Kind {
+        SAVINGS, CHECKING;
+
+        Kind() { ... }
+    }
+
+    private Runnable task = () -> { ... };
+}
+"#;
+
+        assert_eq!(outline(code, Language::Java), expected);
+    }
+
+    #[test]
+    fn test_lines_ending_in_string() {
+        let content = "let a = r#\"one  \n\n\ntwo\"#;  \n/* x\n */\nlet b = \"c\";\n";
+        assert_eq!(
+            lines_ending_in_string(content, Language::Rust),
+            vec![true, true, true, false, false, false, false]
+        );
+
+        let content = "const a = `one\n  two`;\n// `\nb();";
+        assert_eq!(
+            lines_ending_in_string(content, Language::JavaScript),
+            vec![true, false, false]
+        );
+
+        let content = "def f():\n    \"\"\"Doc.  \n\n    \"\"\"\n    return 1\n";
+        assert_eq!(
+            lines_ending_in_string(content, Language::Python),
+            vec![false, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn test_outline_python() {
+        let code = r#"import os
+
+
+@dataclass
+class User:
+    """A user."""
+
+    name: str
+
+    def greet(self, greeting: str = "Hi") -> str:
+        """Returns the greeting.
+
+        def not_a_function():
+        """
+        message = f"{greeting}, {self.name}"
+        return message
+
+    async def load(
+        self,
+        id: int,
+    ) -> "User":  # comment
+        text = """
+not indented
+"""
+        return self
+
+    def name(self): return self._name
+
+
+def main():
+    def inner():
+        pass
+
+    main()
+
+
+if __name__ == "__main__":
+    main()
+"#;
+
+        let expected = r#"import os
+
+
+@dataclass
+class User:
+    """A user."""
+
+    name: str
+
+    def greet(self, greeting: str = "Hi") -> str:
+        """Returns the greeting.
+
+        def not_a_function():
+        """
+        ...
+
+    async def load(
+        self,
+        id: int,
+    ) -> "User":  # comment
+        ...
+
+    def name(self): return self._name
+
+
+def main():
+    ...
+
+
+if __name__ == "__main__":
+    main()
+"#;
+
+        assert_eq!(outline(code, Language::Python), expected);
+    }
+
+    #[test]
+    fn test_outline_transform() {
+        let files = [
+            file("main.rs", "fn main() {\n    run();\n}\n"),
+            file("notes.txt", "fn main() {\n    run();\n}\n"),
+        ];
+
+        let result: Vec<FileContent> = files
+            .into_iter()
+            .map(|file| Outline.transform(file, &mut TransformReport::default()))
+            .collect();
+
+        assert_eq!(result[0].content, "fn main() { ... }\n");
+        assert_eq!(result[1].content, "fn main() {\n    run();\n}\n");
+    }
+}
@@ -0,0 +1,245 @@
+use crate::file::archive::{entry_path, is_archive, split_entry_path};
+use crate::options::{PathStyle, QuaggaOptions};
+use std::path::{self, Path, PathBuf};
+
+/// The name shown instead of the root directory with `PathStyle::Anonymized`.
+pub const ANONYMIZED_ROOT: &str = "project";
+
+/// The directory shown for files outside the root with `PathStyle::Anonymized`.
+pub const ANONYMIZED_EXTERNAL: &str = "external";
+
+/// Formats the paths to files for the prompt and the file lists in the `PathStyle`.
+#[derive(Debug, Clone)]
+pub struct PathFormatter {
+    root: PathBuf,
+    absolute_root: PathBuf,
+    is_archive: bool,
+    style: PathStyle,
+}
+
+impl PathFormatter {
+    /// Creates the formatter for the files found in the `root` directory or archive.
+    pub fn new(root: &Path, style: PathStyle) -> PathFormatter {
+        PathFormatter {
+            root: root.to_path_buf(),
+            absolute_root: path::absolute(root).unwrap_or_else(|_| root.to_path_buf()),
+            is_archive: is_archive(root),
+            style,
+        }
+    }
+
+    /// Creates the formatter with the `root` and `path_style` options.
+    pub fn from_options(options: &QuaggaOptions) -> PathFormatter {
+        PathFormatter::new(&options.root, options.path_style)
+    }
+
+    /// Formats the path to a file:
+    ///
+    /// * `Relative` - `src/main.rs`.
+    /// * `Absolute` - `/home/alice/project/src/main.rs`.
+    /// * `RootName` - `project/src/main.rs`.
+    /// * `Anonymized` - `project/src/main.rs`, where `project` is always the same name.
+    ///
+    /// Files outside the root are shown as they were given, or as an absolute path
+    /// with `Absolute`. `Anonymized` shows only their file name, e.g. `external/notes.txt`.
+    pub fn format(&self, path: &Path) -> PathBuf {
+        let Some(relative) = self.relative(path) else {
+            return match self.style {
+                PathStyle::Absolute => path::absolute(path).unwrap_or_else(|_| path.to_path_buf()),
+                PathStyle::Anonymized => {
+                    let name = path.file_name().unwrap_or(path.as_os_str());
+                    Path::new(ANONYMIZED_EXTERNAL).join(name)
+                }
+                PathStyle::Relative | PathStyle::RootName => path.to_path_buf(),
+            };
+        };
+
+        match self.style {
+            PathStyle::Relative => relative,
+            PathStyle::Absolute | PathStyle::RootName | PathStyle::Anonymized => {
+                let root = self.root_label();
+
+                if self.is_archive {
+                    entry_path(&root, &relative.to_string_lossy())
+                } else {
+                    root.join(relative)
+                }
+            }
+        }
+    }
+
+    /// Formats the path to a file like `format` and converts it to a string.
+    pub fn display(&self, path: &Path) -> String {
+        self.format(path).display().to_string()
+    }
+
+    /// Returns the name of the root shown at the top of the tree: `.` for `Relative`,
+    /// the absolute path for `Absolute`, the directory name for `RootName`,
+    /// and `project` for `Anonymized`.
+    pub fn root_label(&self) -> PathBuf {
+        match self.style {
+            PathStyle::Relative => PathBuf::from("."),
+            PathStyle::Absolute => self.absolute_root.clone(),
+            PathStyle::RootName => self
+                .absolute_root
+                .file_name()
+                .map(PathBuf::from)
+                .unwrap_or_else(|| self.absolute_root.clone()),
+            PathStyle::Anonymized => PathBuf::from(ANONYMIZED_ROOT),
+        }
+    }
+
+    /// Formats the path to a file for the tree. The files in the root are placed
+    /// under the `root_label`, including the files inside a root archive.
+    pub fn tree_path(&self, path: &Path) -> PathBuf {
+        match self.relative(path) {
+            Some(relative) => self.root_label().join(relative),
+            None => self.format(path),
+        }
+    }
+
+    /// Returns the path to the file relative to the root, or `None` if the file
+    /// is not in the root. The paths to files inside a root archive are the paths
+    /// inside the archive.
+    fn relative(&self, path: &Path) -> Option<PathBuf> {
+        if self.is_archive {
+            let (archive, name) = split_entry_path(path)?;
+            let is_root = archive == self.root
+                || path::absolute(&archive).is_ok_and(|archive| archive == self.absolute_root);
+
+            return is_root.then(|| PathBuf::from(name));
+        }
+
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => path::absolute(path)
+                .ok()?
+                .strip_prefix(&self.absolute_root)
+                .ok()?
+                .to_path_buf(),
+        };
+
+        // The root itself is a file, not a directory with files
+        if relative.as_os_str().is_empty() {
+            return None;
+        }
+
+        Some(relative)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+
+    fn format(root: &str, style: PathStyle, path: &str) -> String {
+        PathFormatter::new(Path::new(root), style).display(Path::new(path))
+    }
+
+    #[test]
+    fn test_format_relative() {
+        let style = PathStyle::Relative;
+
+        assert_eq!(
+            format("/home/alice/acme", style, "/home/alice/acme/src/main.rs"),
+            "src/main.rs"
+        );
+        assert_eq!(format(".", style, "./src/main.rs"), "src/main.rs");
+        assert_eq!(format(".", style, "src/main.rs"), "src/main.rs");
+        assert_eq!(format("src", style, "src/lib.rs"), "lib.rs");
+        assert_eq!(
+            format("/home/alice/acme", style, "/tmp/notes.txt"),
+            "/tmp/notes.txt"
+        );
+    }
+
+    #[test]
+    fn test_format_absolute() {
+        let style = PathStyle::Absolute;
+        let current_dir = std::env::current_dir().unwrap();
+
+        assert_eq!(
+            format("/home/alice/acme", style, "/home/alice/acme/src/main.rs"),
+            "/home/alice/acme/src/main.rs"
+        );
+
+        assert_eq!(
+            format(".", style, "./src/main.rs"),
+            current_dir.join("src/main.rs").display().to_string()
+        );
+
+        assert_eq!(
+            format("/home/alice/acme", style, "notes.txt"),
+            current_dir.join("notes.txt").display().to_string()
+        );
+    }
+
+    #[test]
+    fn test_format_root_name() {
+        let style = PathStyle::RootName;
+
+        assert_eq!(
+            format("/home/alice/acme", style, "/home/alice/acme/src/main.rs"),
+            "acme/src/main.rs"
+        );
+
+        assert_eq!(format("/", style, "/etc/hosts"), "/etc/hosts");
+        assert_eq!(
+            format("/home/alice/acme", style, "/tmp/notes.txt"),
+            "/tmp/notes.txt"
+        );
+    }
+
+    #[test]
+    fn test_format_anonymized() {
+        let style = PathStyle::Anonymized;
+
+        assert_eq!(
+            format("/home/alice/acme", style, "/home/alice/acme/src/main.rs"),
+            "project/src/main.rs"
+        );
+
+        assert_eq!(
+            format("/home/alice/acme", style, "/home/alice/notes/todo.txt"),
+            "external/todo.txt"
+        );
+    }
+
+    #[test]
+    fn test_format_archive_entries() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip("acme.zip", &[("src/main.rs", "fn main() {}")]);
+        let path = entry_path(&archive, "src/main.rs");
+
+        let formatter = PathFormatter::new(&archive, PathStyle::Relative);
+        assert_eq!(formatter.display(&path), "src/main.rs");
+
+        let formatter = PathFormatter::new(&archive, PathStyle::RootName);
+        assert_eq!(formatter.display(&path), "acme.zip!/src/main.rs");
+        assert_eq!(
+            formatter.tree_path(&path),
+            PathBuf::from("acme.zip/src/main.rs")
+        );
+
+        let formatter = PathFormatter::new(&archive, PathStyle::Absolute);
+        assert_eq!(formatter.format(&path), path);
+    }
+
+    #[test]
+    fn test_root_label_and_tree_path() {
+        let root = Path::new("/home/alice/acme");
+        let path = Path::new("/home/alice/acme/src/main.rs");
+
+        let formatter = PathFormatter::new(root, PathStyle::Relative);
+        assert_eq!(formatter.root_label(), PathBuf::from("."));
+        assert_eq!(formatter.tree_path(path), PathBuf::from("./src/main.rs"));
+
+        let formatter = PathFormatter::new(root, PathStyle::RootName);
+        assert_eq!(formatter.root_label(), PathBuf::from("acme"));
+        assert_eq!(formatter.tree_path(path), PathBuf::from("acme/src/main.rs"));
+
+        let formatter = PathFormatter::new(root, PathStyle::Anonymized);
+        assert_eq!(formatter.root_label(), PathBuf::from("project"));
+    }
+}
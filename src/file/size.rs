@@ -1,34 +1,73 @@
+use crate::error::QuaggaError;
+use crate::file::archive::{split_entry_path, ArchiveCache};
+use crate::file::file_content::FileContent;
+use crate::file::file_reader::read_text_file;
+use crate::file::notebook::{convert_notebook, is_notebook};
+use crate::file::skipped_file::{skip_or_fail, SkippedFile};
+use crate::options::{OnError, QuaggaOptions};
 use std::fs;
-use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Checks if the total size of the files exceeds the specified maximum size.
-///
+/// Jupyter notebooks are counted by the size of the text they are converted to.
 ///
 /// # Arguments
 ///
 /// * `file_paths` - A slice of `PathBuf` representing the file paths.
+/// * `options` - Options for generating the prompt, with the maximum allowed total size in bytes.
+/// * `archives` - The cache of the archives read during the run.
+///
+/// # Returns
+///
+/// * `Ok(())` - If the total size is within the limit.
+/// * `Err(QuaggaError::TotalSizeExceeded)` - If the total size exceeds the limit.
+/// * `Err(QuaggaError)` - If an error occurs during size calculation.
+pub fn check_total_size(
+    file_paths: Vec<PathBuf>,
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+) -> Result<(), QuaggaError> {
+    let max_total_size = options.max_total_size;
+    let mut total_size = 0u64;
+
+    for path in file_paths {
+        total_size += prompt_size(&path, options, archives)?;
+    }
+
+    if total_size > max_total_size {
+        return Err(QuaggaError::TotalSizeExceeded {
+            total: total_size,
+            limit: max_total_size,
+        });
+    }
+    Ok(())
+}
+
+/// Checks if the total size of the contents of the files that were already read
+/// exceeds the specified maximum size.
+///
+/// # Arguments
+///
+/// * `files` - The contents of the files.
 /// * `max_total_size` - The maximum allowed total size in bytes.
 ///
 /// # Returns
 ///
 /// * `Ok(())` - If the total size is within the limit.
-/// * `Err(io::Error)` - If the total size exceeds the limit or an error occurs during size calculation.
-pub fn check_total_size(file_paths: Vec<PathBuf>, max_total_size: u64) -> io::Result<()> {
-    let total_size = calculate_total_size(file_paths)?;
+/// * `Err(QuaggaError::TotalSizeExceeded)` - If the total size exceeds the limit.
+pub fn check_total_content_size(
+    files: &[FileContent],
+    max_total_size: u64,
+) -> Result<(), QuaggaError> {
+    let total_size: u64 = files.iter().map(|file| file.content.len() as u64).sum();
 
     if total_size > max_total_size {
-        return Err(io::Error::new(
-            io::ErrorKind::Other,
-            format!(
-                r#"Total size of files ({}) exceeds the maximum allowed size ({}).
-Use --max-total-size=BYTES option to increase the limit.
-"#,
-                human_readable_size(total_size),
-                human_readable_size(max_total_size)
-            ),
-        ));
+        return Err(QuaggaError::TotalSizeExceeded {
+            total: total_size,
+            limit: max_total_size,
+        });
     }
+
     Ok(())
 }
 
@@ -37,34 +76,122 @@ Use --max-total-size=BYTES option to increase the limit.
 /// # Arguments
 ///
 /// * `file_paths` - A vector of `PathBuf` representing the file paths.
+/// * `archives` - The cache of the archives read during the run.
 ///
 /// # Returns
 ///
 /// * `Ok(u64)` - The total size of the files in bytes.
-/// * `Err(io::Error)` - An error occurred while accessing a file's metadata.
-pub fn calculate_total_size(file_paths: Vec<PathBuf>) -> io::Result<u64> {
+/// * `Err(QuaggaError)` - An error occurred while accessing a file's metadata
+///   or the path is not a regular file.
+pub fn calculate_total_size(
+    file_paths: Vec<PathBuf>,
+    archives: &ArchiveCache,
+) -> Result<u64, QuaggaError> {
     let mut total_size = 0u64;
 
     for path in file_paths {
-        let metadata = fs::metadata(&path).map_err(|e| {
-            io::Error::new(
-                e.kind(),
-                format!("Failed to read metadata for file {}: {}", path.display(), e),
-            )
-        })?;
-
-        // Check if the path points to a regular file
-        if !metadata.is_file() {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("Path is not a regular file: {}", path.display()),
-            ));
+        total_size += file_size(&path, archives)?;
+    }
+
+    Ok(total_size)
+}
+
+/// Returns the size of the file, or the size of the text of a Jupyter notebook.
+/// Notebooks that can not be read as text are counted by their file size.
+fn prompt_size(
+    path: &Path,
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+) -> Result<u64, QuaggaError> {
+    let size = file_size(path, archives)?;
+
+    if !is_notebook(path) {
+        return Ok(size);
+    }
+
+    match read_text_file(
+        path.to_path_buf(),
+        options.binary,
+        options.encoding,
+        archives,
+    ) {
+        Ok(content) => Ok(convert_notebook(path, content, options.notebook_outputs).len() as u64),
+        Err(_) => Ok(size),
+    }
+}
+
+/// Returns the size of a regular file, or of a file inside an archive.
+///
+/// # Arguments
+///
+/// * `path` - A path to a file, or to a file inside an archive, e.g. `project.zip!/src/main.rs`.
+/// * `archives` - The cache of the archives read during the run.
+///
+/// # Returns
+///
+/// * `Ok(u64)` - The size of the file in bytes.
+/// * `Err(QuaggaError)` - The metadata of the file can not be read or the path is not a regular file.
+pub fn file_size(path: &Path, archives: &ArchiveCache) -> Result<u64, QuaggaError> {
+    if let Some((archive, name)) = split_entry_path(path) {
+        return match archives.find_entry(&archive, &name) {
+            Ok(entry) => Ok(entry.size),
+            Err(source) => Err(QuaggaError::FileMetadata {
+                path: path.to_path_buf(),
+                source,
+            }),
+        };
+    }
+
+    let metadata = match fs::metadata(path) {
+        Ok(metadata) => metadata,
+        Err(source) => {
+            return Err(QuaggaError::FileMetadata {
+                path: path.to_path_buf(),
+                source,
+            })
         }
+    };
 
-        total_size += metadata.len();
+    // Check if the path points to a regular file
+    if !metadata.is_file() {
+        return Err(QuaggaError::NotAFile {
+            path: path.to_path_buf(),
+        });
     }
 
-    Ok(total_size)
+    Ok(metadata.len())
+}
+
+/// Keeps only the paths that point to regular files with readable metadata.
+///
+/// # Arguments
+///
+/// * `file_paths` - A vector of `PathBuf` representing the file paths.
+/// * `on_error` - What to do when the metadata of a file can not be read
+///   or the path is not a regular file.
+/// * `archives` - The cache of the archives read during the run.
+/// * `skipped` - The list to which the skipped files are added.
+///
+/// # Returns
+///
+/// * `Ok(Vec<PathBuf>)` - The paths to the regular files.
+/// * `Err(QuaggaError)` - If a file can not be accessed and `on_error` is `OnError::Fail`.
+pub fn keep_regular_files(
+    file_paths: Vec<PathBuf>,
+    on_error: OnError,
+    archives: &ArchiveCache,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<Vec<PathBuf>, QuaggaError> {
+    let mut regular_files = Vec::new();
+
+    for path in file_paths {
+        match file_size(&path, archives) {
+            Ok(_) => regular_files.push(path),
+            Err(error) => skip_or_fail(error, on_error, skipped)?,
+        }
+    }
+
+    Ok(regular_files)
 }
 
 /// Converts bytes into a human-readable string with appropriate units.
@@ -100,6 +227,13 @@ mod tests {
     use super::*;
     use crate::test_utils::temp_dir::TempDir;
 
+    fn max_total_size(max_total_size: u64) -> QuaggaOptions {
+        QuaggaOptions {
+            max_total_size,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_calculate_total_size_with_valid_files() {
         let td = TempDir::new().unwrap();
@@ -110,7 +244,7 @@ mod tests {
 
         let files = vec![file1_path, file2_path];
 
-        let result = calculate_total_size(files).unwrap();
+        let result = calculate_total_size(files, &ArchiveCache::default()).unwrap();
         assert_eq!(result, 15);
     }
 
@@ -123,7 +257,7 @@ mod tests {
 
         let files = vec![file1_path, file2_path];
 
-        let result = calculate_total_size(files).unwrap();
+        let result = calculate_total_size(files, &ArchiveCache::default()).unwrap();
         assert_eq!(result, 0);
     }
 
@@ -136,7 +270,7 @@ mod tests {
 
         let files = vec![file1_path, file2_path];
 
-        let result = calculate_total_size(files);
+        let result = calculate_total_size(files, &ArchiveCache::default());
 
         assert!(result.is_err());
     }
@@ -150,9 +284,9 @@ mod tests {
 
         let files = vec![dir_path];
 
-        let result = calculate_total_size(files);
+        let result = calculate_total_size(files, &ArchiveCache::default());
 
-        assert!(result.is_err());
+        assert!(matches!(result, Err(QuaggaError::NotAFile { .. })));
     }
 
     #[test]
@@ -165,7 +299,7 @@ mod tests {
         let files = vec![file1_path, file2_path];
 
         // Total size is 15 bytes, set max_total_size to 20 bytes
-        let result = check_total_size(files, 20);
+        let result = check_total_size(files, &max_total_size(20), &ArchiveCache::default());
 
         assert!(result.is_ok());
     }
@@ -180,14 +314,15 @@ mod tests {
         let files = vec![file1_path, file2_path];
 
         // Total size is 15 bytes, set max_total_size to 10 bytes
-        let result = check_total_size(files, 10);
-
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err().to_string();
-
-        assert!(
-            err_msg.contains("Total size of files (15 B) exceeds the maximum allowed size (10 B).")
-        );
+        let result = check_total_size(files, &max_total_size(10), &ArchiveCache::default());
+
+        assert!(matches!(
+            result,
+            Err(QuaggaError::TotalSizeExceeded {
+                total: 15,
+                limit: 10
+            })
+        ));
     }
 
     #[test]
@@ -200,11 +335,106 @@ mod tests {
         let files = vec![file1_path, file2_path];
 
         // Total size is 10 bytes, set max_total_size to 10 bytes
-        let result = check_total_size(files, 10);
+        let result = check_total_size(files, &max_total_size(10), &ArchiveCache::default());
 
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn test_check_total_size_counts_notebook_text() {
+        let td = TempDir::new().unwrap();
+        let image = "iVBORw0KGgo".repeat(100);
+        let notebook = format!(
+            r#"{{"cells": [{{"cell_type": "code", "source": "plot()", "outputs": [{{"output_type": "display_data", "data": {{"image/png": "{}"}}}}]}}]}}"#,
+            image
+        );
+        let path = td.mkfile_with_contents("plot.ipynb", &notebook);
+
+        // The notebook is over 1 KB, but its text is "# %% [code]\nplot()\n"
+        assert!(check_total_size(
+            vec![path.clone()],
+            &max_total_size(19),
+            &ArchiveCache::default()
+        )
+        .is_ok());
+
+        assert!(matches!(
+            check_total_size(vec![path], &max_total_size(18), &ArchiveCache::default()),
+            Err(QuaggaError::TotalSizeExceeded {
+                total: 19,
+                limit: 18
+            })
+        ));
+    }
+
+    #[test]
+    fn test_check_total_content_size() {
+        let files = vec![
+            FileContent {
+                path: PathBuf::from("file1.txt"),
+                content: "12345".to_string(),
+            },
+            FileContent {
+                path: PathBuf::from("file2.txt"),
+                content: "123456".to_string(),
+            },
+        ];
+
+        assert!(check_total_content_size(&files, 11).is_ok());
+
+        assert!(matches!(
+            check_total_content_size(&files, 10),
+            Err(QuaggaError::TotalSizeExceeded {
+                total: 11,
+                limit: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_keep_regular_files() {
+        let td = TempDir::new().unwrap();
+        let file_path = td.mkfile("file.txt");
+        td.mkdir("subdir");
+        let dir_path = td.path().join("subdir");
+        let nonexistent_path = td.path().join("nonexistent.txt");
+        let mut skipped = Vec::new();
+
+        let result = keep_regular_files(
+            vec![
+                file_path.clone(),
+                dir_path.clone(),
+                nonexistent_path.clone(),
+            ],
+            OnError::Warn,
+            &ArchiveCache::default(),
+            &mut skipped,
+        )
+        .unwrap();
+
+        assert_eq!(result, vec![file_path]);
+        assert_eq!(skipped.len(), 2);
+        assert_eq!(skipped[0].path, dir_path);
+        assert_eq!(skipped[0].reason, "not a regular file");
+        assert_eq!(skipped[1].path, nonexistent_path);
+    }
+
+    #[test]
+    fn test_keep_regular_files_fail() {
+        let td = TempDir::new().unwrap();
+        let nonexistent_path = td.path().join("nonexistent.txt");
+        let mut skipped = Vec::new();
+
+        let result = keep_regular_files(
+            vec![nonexistent_path],
+            OnError::Fail,
+            &ArchiveCache::default(),
+            &mut skipped,
+        );
+
+        assert!(matches!(result, Err(QuaggaError::FileMetadata { .. })));
+    }
+
     #[test]
     fn test_human_readable_size() {
         assert_eq!(human_readable_size(500), "500 B");
@@ -224,11 +454,22 @@ mod tests {
 
         let files = vec![file1_path, nonexistent_file_path.clone()];
 
-        let result = calculate_total_size(files);
+        let result = calculate_total_size(files, &ArchiveCache::default());
 
         assert!(result.is_err());
         let error = result.unwrap_err();
-        assert_eq!(error.kind(), io::ErrorKind::NotFound);
-        assert!(error.to_string().contains(&format!("Failed to read metadata for file {}", nonexistent_file_path.display())));
+
+        match &error {
+            QuaggaError::FileMetadata { path, source } => {
+                assert_eq!(path, &nonexistent_file_path);
+                assert_eq!(source.kind(), std::io::ErrorKind::NotFound);
+            }
+            _ => panic!("Unexpected error: {:?}", error),
+        }
+
+        assert!(error.to_string().contains(&format!(
+            "Failed to read metadata for file {}",
+            nonexistent_file_path.display()
+        )));
     }
 }
@@ -0,0 +1,169 @@
+use crate::error::QuaggaError;
+use crate::options::OnError;
+use std::path::{Path, PathBuf};
+
+/// A file that was left out of the prompt because it could not be read.
+///
+/// # Fields
+///
+/// * `path` - The file path.
+/// * `reason` - Why the file could not be read (e.g. "Permission denied").
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedFile {
+    pub path: PathBuf,
+    pub reason: String,
+}
+
+impl SkippedFile {
+    /// Creates a `SkippedFile` from an error that is about a single file.
+    ///
+    /// # Returns
+    ///
+    /// `None` if the error is not about a single file (e.g. the total size is exceeded).
+    pub fn from_error(error: &QuaggaError) -> Option<SkippedFile> {
+        let path = SkippedFile::path_of_error(error)?;
+
+        let reason = match error {
+            QuaggaError::FileRead { source, .. } | QuaggaError::FileMetadata { source, .. } => {
+                source.to_string()
+            }
+            _ => "not a regular file".to_string(),
+        };
+
+        Some(SkippedFile {
+            path: path.to_path_buf(),
+            reason,
+        })
+    }
+
+    /// Returns the path of the file if the error is about a single file.
+    pub fn path_of_error(error: &QuaggaError) -> Option<&Path> {
+        match error {
+            QuaggaError::FileRead { path, .. }
+            | QuaggaError::FileMetadata { path, .. }
+            | QuaggaError::NotAFile { path } => Some(path),
+            _ => None,
+        }
+    }
+}
+
+/// Handles an error that occurred while processing a single file.
+/// Unless `on_error` is `OnError::Fail`, the file is added to the `skipped` list
+/// and the processing can continue.
+///
+/// # Arguments
+///
+/// * `error` - The error that occurred.
+/// * `on_error` - What to do when a file can not be read.
+/// * `skipped` - The list of skipped files.
+///
+/// # Returns
+///
+/// * `Ok(())` if the file was skipped.
+/// * `Err(QuaggaError)` with the original error if the processing needs to stop.
+pub fn skip_or_fail(
+    error: QuaggaError,
+    on_error: OnError,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<(), QuaggaError> {
+    if on_error == OnError::Fail {
+        return Err(error);
+    }
+
+    match SkippedFile::from_error(&error) {
+        Some(skipped_file) => {
+            skipped.push(skipped_file);
+            Ok(())
+        }
+        None => Err(error),
+    }
+}
+
+/// Formats the skipped files, one per line, as `path: reason`.
+pub fn format_skipped_files(skipped: &[SkippedFile]) -> String {
+    skipped
+        .iter()
+        .map(|file| format!("{}: {}", file.path.display(), file.reason))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+
+    fn read_error(path: &str) -> QuaggaError {
+        QuaggaError::FileRead {
+            path: PathBuf::from(path),
+            source: io::Error::new(io::ErrorKind::PermissionDenied, "Permission denied"),
+        }
+    }
+
+    #[test]
+    fn test_skip_or_fail_warn() {
+        let mut skipped = Vec::new();
+
+        let result = skip_or_fail(read_error("file.txt"), OnError::Warn, &mut skipped);
+
+        assert!(result.is_ok());
+
+        assert_eq!(
+            skipped,
+            vec![SkippedFile {
+                path: PathBuf::from("file.txt"),
+                reason: "Permission denied".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_skip_or_fail_skip() {
+        let mut skipped = Vec::new();
+
+        let result = skip_or_fail(read_error("file.txt"), OnError::Skip, &mut skipped);
+
+        assert!(result.is_ok());
+        assert_eq!(skipped.len(), 1);
+    }
+
+    #[test]
+    fn test_skip_or_fail_fail() {
+        let mut skipped = Vec::new();
+
+        let result = skip_or_fail(read_error("file.txt"), OnError::Fail, &mut skipped);
+
+        assert!(matches!(result, Err(QuaggaError::FileRead { .. })));
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn test_skip_or_fail_not_a_file_error() {
+        let mut skipped = Vec::new();
+        let error = QuaggaError::TotalSizeExceeded { total: 2, limit: 1 };
+
+        let result = skip_or_fail(error, OnError::Skip, &mut skipped);
+
+        assert!(matches!(result, Err(QuaggaError::TotalSizeExceeded { .. })));
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn test_format_skipped_files() {
+        let skipped = vec![
+            SkippedFile {
+                path: PathBuf::from("a.txt"),
+                reason: "Permission denied".to_string(),
+            },
+            SkippedFile {
+                path: PathBuf::from("b.txt"),
+                reason: "not a regular file".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            format_skipped_files(&skipped),
+            "a.txt: Permission denied\nb.txt: not a regular file"
+        );
+    }
+}
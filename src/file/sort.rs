@@ -0,0 +1,497 @@
+use crate::error::QuaggaError;
+use crate::file::archive::ArchiveCache;
+use crate::file::size::file_size;
+use crate::options::{QuaggaOptions, SortOrder};
+use crate::walk::modified::modified_time;
+use ignore::overrides::{Override, OverrideBuilder};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// Orders the files for the prompt according to the `sort`, `reverse` and `first` options.
+/// Files that compare equal keep their current order, which is the path order for the files
+/// found by walking the directory tree, and the given order for piped paths.
+///
+/// # Arguments
+///
+/// * `files` - The files to sort.
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run, used for the sizes
+///   and modification times of the files in archives.
+///
+/// # Returns
+///
+/// * `Ok(())` if the files were sorted.
+/// * `Err(QuaggaError)` if any of the `first` patterns is invalid.
+pub fn sort_files<T: AsRef<Path>>(
+    files: &mut [T],
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+) -> Result<(), QuaggaError> {
+    if let Some(order) = options.sort {
+        sort_by_order(files, order, &options.root, archives);
+    }
+
+    if options.reverse {
+        files.reverse();
+    }
+
+    if !options.first.is_empty() {
+        let priorities = build_priority_patterns(&options.root, &options.first)?;
+        files.sort_by_cached_key(|file| priority(file.as_ref(), &priorities));
+    }
+
+    Ok(())
+}
+
+/// Sorts the files with a stable sort, using a key for each order.
+fn sort_by_order<T: AsRef<Path>>(
+    files: &mut [T],
+    order: SortOrder,
+    root: &Path,
+    archives: &ArchiveCache,
+) {
+    match order {
+        SortOrder::Path => files.sort_by(|a, b| a.as_ref().cmp(b.as_ref())),
+        SortOrder::Size => {
+            files.sort_by_cached_key(|file| Reverse(file_size(file.as_ref(), archives).ok()))
+        }
+        SortOrder::Mtime => {
+            files.sort_by_cached_key(|file| Reverse(modified_time(file.as_ref(), archives).ok()))
+        }
+        SortOrder::GitRecency => {
+            let commit_times = git_commit_times(root);
+
+            files.sort_by_cached_key(|file| {
+                // Files that were never committed are considered the most recent
+                let time = file
+                    .as_ref()
+                    .canonicalize()
+                    .ok()
+                    .and_then(|path| commit_times.get(&path).copied())
+                    .unwrap_or(i64::MAX);
+
+                Reverse(time)
+            })
+        }
+        SortOrder::Depth => files.sort_by_cached_key(|file| depth(file.as_ref())),
+        SortOrder::Type => files.sort_by_cached_key(|file| extension(file.as_ref())),
+        SortOrder::Unsorted => {}
+    }
+}
+
+/// Returns the number of directories and the file name in the path.
+fn depth(path: &Path) -> usize {
+    path.components()
+        .filter(|component| matches!(component, Component::Normal(_)))
+        .count()
+}
+
+/// Returns the lowercase file extension. Files without extension come first.
+fn extension(path: &Path) -> Option<String> {
+    path.extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+}
+
+/// Returns the time of the latest commit for each file under `root` in the git repository containing it.
+/// The map is empty if `root` is not in a git repository or git is not installed.
+///
+/// # Returns
+///
+/// A map of canonical file paths to the commit times in seconds since the Unix epoch.
+fn git_commit_times(root: &Path) -> HashMap<PathBuf, i64> {
+    let mut times = HashMap::new();
+
+    let Some(top_level) = run_git(root, &["rev-parse", "--show-toplevel"]) else {
+        return times;
+    };
+
+    let top_level = PathBuf::from(top_level.trim());
+
+    // Without `core.quotepath=false`, paths with non-ASCII characters are quoted and escaped
+    let Some(log) = run_git(
+        root,
+        &[
+            "-c",
+            "core.quotepath=false",
+            "log",
+            "--format=%x00%ct",
+            "--name-only",
+            "--no-renames",
+            "--",
+            ".",
+        ],
+    ) else {
+        return times;
+    };
+
+    let mut commit_time = 0;
+
+    // The log lists the most recent commits first, so the first time seen for a file is the latest
+    for line in log.lines() {
+        if let Some(time) = line.strip_prefix('\0') {
+            commit_time = time.trim().parse().unwrap_or(0);
+        } else if !line.is_empty() {
+            times.entry(top_level.join(line)).or_insert(commit_time);
+        }
+    }
+
+    times
+}
+
+/// Runs git in the `dir` directory and returns its output, or `None` if it fails.
+fn run_git(dir: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok()
+}
+
+/// Builds a matcher for each of the `first` patterns, in order.
+fn build_priority_patterns(root: &Path, patterns: &[String]) -> Result<Vec<Override>, QuaggaError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            let mut builder = OverrideBuilder::new(root);
+            builder.add(pattern)?;
+            Ok(builder.build()?)
+        })
+        .collect()
+}
+
+/// Returns the index of the first pattern that matches the path,
+/// or the number of patterns if none match.
+fn priority(path: &Path, patterns: &[Override]) -> usize {
+    patterns
+        .iter()
+        .position(|pattern| pattern.matched(path, false).is_whitelist())
+        .unwrap_or(patterns.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+    use std::fs::File;
+    use std::time::{Duration, SystemTime};
+
+    fn file_with_time(td: &TempDir, name: &str, seconds: u64) -> PathBuf {
+        let path = td.mkfile(name);
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(seconds);
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(modified).unwrap();
+        path
+    }
+
+    fn names(td: &TempDir, files: &[PathBuf]) -> Vec<String> {
+        files
+            .iter()
+            .map(|path| {
+                path.strip_prefix(td.path())
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    fn sorted(td: &TempDir, mut files: Vec<PathBuf>, options: QuaggaOptions) -> Vec<String> {
+        sort_files(&mut files, &options, &ArchiveCache::default()).unwrap();
+        names(td, &files)
+    }
+
+    #[test]
+    fn test_sort_files_by_mtime() {
+        let td = TempDir::new().unwrap();
+
+        let files = vec![
+            file_with_time(&td, "a.txt", 1000),
+            file_with_time(&td, "b.txt", 3000),
+            file_with_time(&td, "c.txt", 1000),
+            file_with_time(&td, "d.txt", 2000),
+        ];
+
+        let options = QuaggaOptions {
+            sort: Some(SortOrder::Mtime),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sorted(&td, files, options),
+            vec!["b.txt", "d.txt", "a.txt", "c.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_by_path() {
+        let td = TempDir::new().unwrap();
+        let files = vec![td.mkfile("b.txt"), td.mkfile("a.txt")];
+
+        let options = QuaggaOptions {
+            sort: Some(SortOrder::Path),
+            ..Default::default()
+        };
+
+        assert_eq!(sorted(&td, files, options), vec!["a.txt", "b.txt"]);
+    }
+
+    #[test]
+    fn test_sort_files_keeps_order_by_default() {
+        let td = TempDir::new().unwrap();
+        let files = vec![td.mkfile("b.txt"), td.mkfile("a.txt")];
+
+        assert_eq!(
+            sorted(&td, files, QuaggaOptions::default()),
+            vec!["b.txt", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_by_size() {
+        let td = TempDir::new().unwrap();
+
+        let files = vec![
+            td.mkfile_with_contents("small.txt", "a"),
+            td.mkfile_with_contents("large.txt", "abc"),
+            td.mkfile_with_contents("medium.txt", "ab"),
+        ];
+
+        let options = QuaggaOptions {
+            sort: Some(SortOrder::Size),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sorted(&td, files, options),
+            vec!["large.txt", "medium.txt", "small.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_by_depth() {
+        let td = TempDir::new().unwrap();
+        td.mkdir("src");
+        td.mkdir("src/walk");
+
+        let files = vec![
+            td.mkfile("src/walk/walker.rs"),
+            td.mkfile("src/main.rs"),
+            td.mkfile("README.md"),
+        ];
+
+        let options = QuaggaOptions {
+            sort: Some(SortOrder::Depth),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sorted(&td, files, options),
+            vec!["README.md", "src/main.rs", "src/walk/walker.rs"]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_by_type() {
+        let td = TempDir::new().unwrap();
+
+        let files = vec![
+            td.mkfile("b.rs"),
+            td.mkfile("a.md"),
+            td.mkfile("Makefile"),
+            td.mkfile("a.RS"),
+        ];
+
+        let options = QuaggaOptions {
+            sort: Some(SortOrder::Type),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sorted(&td, files, options),
+            vec!["Makefile", "a.md", "b.rs", "a.RS"]
+        );
+    }
+
+    /// Runs git in the temporary directory with the commit date, returns false if it fails.
+    fn run_test_git(td: &TempDir, args: &[&str], date: &str) -> bool {
+        let status = Command::new("git")
+            .arg("-C")
+            .arg(td.path())
+            .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+            .args(args)
+            .env("GIT_AUTHOR_DATE", date)
+            .env("GIT_COMMITTER_DATE", date)
+            .status();
+
+        matches!(status, Ok(status) if status.success())
+    }
+
+    #[test]
+    fn test_sort_files_by_git_recency() {
+        let td = TempDir::new().unwrap();
+        let old = td.mkfile_with_contents("old.txt", "old");
+        let new = td.mkfile_with_contents("new.txt", "new");
+        let untracked = td.mkfile_with_contents("untracked.txt", "untracked");
+        let git = |args: &[&str], date: &str| run_test_git(&td, args, date);
+
+        if !git(&["init", "-q"], "") {
+            // Git is not installed
+            return;
+        }
+
+        assert!(git(&["add", "old.txt"], ""));
+        assert!(git(&["commit", "-q", "-m", "old"], "2020-01-01T00:00:00Z"));
+        assert!(git(&["add", "new.txt"], ""));
+        assert!(git(&["commit", "-q", "-m", "new"], "2021-01-01T00:00:00Z"));
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            sort: Some(SortOrder::GitRecency),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sorted(&td, vec![old, new, untracked], options),
+            vec!["untracked.txt", "new.txt", "old.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_by_git_recency_non_ascii_path() {
+        let td = TempDir::new().unwrap();
+        let old = td.mkfile_with_contents("old.txt", "old");
+        let new = td.mkfile_with_contents("nouveauté.txt", "new");
+        let older = td.mkfile_with_contents("ältere.txt", "older");
+        let git = |args: &[&str], date: &str| run_test_git(&td, args, date);
+
+        if !git(&["init", "-q"], "") {
+            // Git is not installed
+            return;
+        }
+
+        assert!(git(&["add", "ältere.txt"], ""));
+        assert!(git(
+            &["commit", "-q", "-m", "older"],
+            "2019-01-01T00:00:00Z"
+        ));
+        assert!(git(&["add", "old.txt"], ""));
+        assert!(git(&["commit", "-q", "-m", "old"], "2020-01-01T00:00:00Z"));
+        assert!(git(&["add", "nouveauté.txt"], ""));
+        assert!(git(&["commit", "-q", "-m", "new"], "2021-01-01T00:00:00Z"));
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            sort: Some(SortOrder::GitRecency),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sorted(&td, vec![old, older, new], options),
+            vec!["nouveauté.txt", "old.txt", "ältere.txt"]
+        );
+    }
+
+    #[test]
+    fn test_git_commit_times_only_under_root() {
+        let td = TempDir::new().unwrap();
+        td.mkdir("src");
+        td.mkfile_with_contents("src/main.rs", "fn main() {}");
+        td.mkfile_with_contents("README.md", "Read me");
+        let git = |args: &[&str], date: &str| run_test_git(&td, args, date);
+
+        if !git(&["init", "-q"], "") {
+            // Git is not installed
+            return;
+        }
+
+        assert!(git(&["add", "src/main.rs"], ""));
+        assert!(git(&["commit", "-q", "-m", "src"], "2020-01-01T00:00:00Z"));
+        assert!(git(&["add", "README.md"], ""));
+        assert!(git(&["commit", "-q", "-m", "docs"], "2021-01-01T00:00:00Z"));
+
+        let times = git_commit_times(&td.path().join("src"));
+        let names: Vec<String> = times
+            .keys()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+
+        assert_eq!(names, vec!["main.rs"]);
+        assert_eq!(
+            times.values().copied().collect::<Vec<_>>(),
+            vec![1577836800]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_reverse() {
+        let td = TempDir::new().unwrap();
+        let files = vec![td.mkfile("a.txt"), td.mkfile("b.txt"), td.mkfile("c.txt")];
+
+        let options = QuaggaOptions {
+            sort: Some(SortOrder::Path),
+            reverse: true,
+            ..Default::default()
+        };
+
+        assert_eq!(sorted(&td, files, options), vec!["c.txt", "b.txt", "a.txt"]);
+    }
+
+    #[test]
+    fn test_sort_files_first() {
+        let td = TempDir::new().unwrap();
+        td.mkdir("src");
+        td.mkdir("docs");
+
+        let files = vec![
+            td.mkfile("Cargo.toml"),
+            td.mkfile("docs/README.md"),
+            td.mkfile("README.md"),
+            td.mkfile("src/lib.rs"),
+            td.mkfile("src/main.rs"),
+        ];
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            sort: Some(SortOrder::Path),
+            reverse: true,
+            first: vec!["README*".to_string(), "src/main.rs".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            sorted(&td, files, options),
+            vec![
+                "docs/README.md",
+                "README.md",
+                "src/main.rs",
+                "src/lib.rs",
+                "Cargo.toml"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_invalid_first_pattern() {
+        let options = QuaggaOptions {
+            first: vec!["{".to_string()],
+            ..Default::default()
+        };
+
+        let result = sort_files(
+            &mut [PathBuf::from("a.txt")],
+            &options,
+            &ArchiveCache::default(),
+        );
+
+        assert!(result.is_err());
+    }
+}
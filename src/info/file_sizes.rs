@@ -1,60 +1,186 @@
-use crate::file::size::human_readable_size;
-use std::fs;
-use std::io;
+use crate::file::archive::ArchiveCache;
+use crate::file::encoding::{decode_text, detect_encoding, is_text_in_encoding};
+use crate::file::file_reader::read_file_bytes;
+use crate::file::license_header::{strip_header, SharedHeaders};
+use crate::file::path_style::PathFormatter;
+use crate::file::size::{file_size, human_readable_size};
+use encoding_rs::{Encoding, UTF_8};
 use std::path::PathBuf;
 
 struct FileWithSize {
     path: PathBuf,
     size: u64,
+
+    /// The name of the encoding of the file, or `None` for UTF-8.
+    encoding: Option<&'static str>,
+
+    /// The number of bytes in the license header removed with `--strip-license-headers`.
+    header_size: u64,
 }
 
-pub fn get_formatted_file_sizes(file_paths: Vec<PathBuf>) -> io::Result<String> {
-    let files_with_sizes = collect_file_sizes(file_paths)?;
+/// Lists the files from largest to smallest, with their sizes and
+/// the encodings of files that are not in UTF-8.
+///
+/// # Arguments
+///
+/// * `file_paths` - The paths to the files.
+/// * `encoding` - The encoding of the files from the `--encoding` option,
+///   or `None` to detect the encoding of each file.
+/// * `paths` - Formats the file paths in the `--path-style`.
+/// * `strip_license_headers` - Whether to show the sizes of the license headers that
+///   are removed from the files, and the number of files they are removed from.
+/// * `archives` - The cache of the archives read during the run.
+pub fn get_formatted_file_sizes(
+    file_paths: Vec<PathBuf>,
+    encoding: Option<&'static Encoding>,
+    paths: &PathFormatter,
+    strip_license_headers: bool,
+    archives: &ArchiveCache,
+) -> String {
+    let files_with_sizes =
+        collect_file_sizes(file_paths, encoding, strip_license_headers, archives);
     let sorted_files = sort_files_by_size(files_with_sizes);
-    Ok(format_files_with_sizes(sorted_files))
+
+    if !strip_license_headers {
+        return format_files_with_sizes(sorted_files, paths);
+    }
+
+    let summary = format_removed_headers(&sorted_files);
+    let list = format_files_with_sizes(sorted_files, paths);
+
+    if list.is_empty() {
+        return summary;
+    }
+
+    format!("{}\n{}", list, summary)
 }
 
-fn collect_file_sizes(file_paths: Vec<PathBuf>) -> io::Result<Vec<FileWithSize>> {
-    file_paths
-        .into_iter()
-        .filter_map(|path| {
-            fs::metadata(&path).ok().and_then(|metadata| {
-                if metadata.is_file() {
-                    Some(Ok(FileWithSize {
-                        path,
-                        size: metadata.len(),
-                    }))
-                } else {
-                    None
-                }
-            })
-        })
-        .collect()
+fn collect_file_sizes(
+    file_paths: Vec<PathBuf>,
+    encoding: Option<&'static Encoding>,
+    strip_license_headers: bool,
+    archives: &ArchiveCache,
+) -> Vec<FileWithSize> {
+    let mut files = Vec::new();
+    let mut texts = Vec::new();
+
+    for path in file_paths {
+        let Ok(size) = file_size(&path, archives) else {
+            continue;
+        };
+
+        let bytes = read_file_bytes(&path, archives).ok();
+        let encoding_name = bytes
+            .as_deref()
+            .and_then(|bytes| encoding_name(bytes, encoding));
+
+        let text = bytes
+            .filter(|_| strip_license_headers)
+            .and_then(|bytes| decode_text(bytes, encoding).ok())
+            .map(|(text, _)| text);
+
+        files.push(FileWithSize {
+            path,
+            size,
+            encoding: encoding_name,
+            header_size: 0,
+        });
+
+        texts.push(text);
+    }
+
+    if strip_license_headers {
+        let shared = SharedHeaders::find(texts.iter().flatten().map(String::as_str));
+
+        for (file, text) in files.iter_mut().zip(&texts) {
+            if let Some(text) = text {
+                file.header_size = (text.len() - strip_header(text, &shared).len()) as u64;
+            }
+        }
+    }
+
+    files
+}
+
+/// Returns the name of the given or detected encoding of the file, `binary` if the file
+/// is not text, or `None` if the file is in UTF-8.
+fn encoding_name(bytes: &[u8], encoding: Option<&'static Encoding>) -> Option<&'static str> {
+    let encoding = match encoding {
+        Some(encoding) => is_text_in_encoding(bytes, encoding).then_some(encoding),
+        None => detect_encoding(bytes),
+    };
+
+    match encoding {
+        Some(encoding) if encoding == UTF_8 => None,
+        Some(encoding) => Some(encoding.name()),
+        None => Some("binary"),
+    }
 }
 
 fn sort_files_by_size(mut files: Vec<FileWithSize>) -> Vec<FileWithSize> {
-    files.sort_by(|a, b| b.size.cmp(&a.size));
+    files.sort_by_key(|file| std::cmp::Reverse(file.size));
     files
 }
 
-fn format_files_with_sizes(files: Vec<FileWithSize>) -> String {
+fn format_files_with_sizes(files: Vec<FileWithSize>, paths: &PathFormatter) -> String {
     files
         .into_iter()
         .map(|file| {
-            format!(
+            let line = format!(
                 "[{}] {}",
                 human_readable_size(file.size),
-                file.path.display()
-            )
+                paths.display(&file.path)
+            );
+
+            let mut notes = Vec::new();
+
+            if let Some(encoding) = file.encoding {
+                notes.push(encoding.to_string());
+            }
+
+            if file.header_size > 0 {
+                notes.push(format!(
+                    "license header {}",
+                    human_readable_size(file.header_size)
+                ));
+            }
+
+            if notes.is_empty() {
+                line
+            } else {
+                format!("{} ({})", line, notes.join(", "))
+            }
         })
         .collect::<Vec<String>>()
         .join("\n")
 }
 
+/// Formats the number of license headers removed and their total size.
+fn format_removed_headers(files: &[FileWithSize]) -> String {
+    let removed: Vec<u64> = files
+        .iter()
+        .map(|file| file.header_size)
+        .filter(|size| *size > 0)
+        .collect();
+
+    format!(
+        "Removed {} license header(s), {}",
+        removed.len(),
+        human_readable_size(removed.iter().sum())
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::PathStyle;
     use crate::test_utils::temp_dir::TempDir;
+    use encoding_rs::WINDOWS_1252;
+    use std::path::Path;
+
+    fn absolute(td: &TempDir) -> PathFormatter {
+        PathFormatter::new(td.path(), PathStyle::Absolute)
+    }
 
     #[test]
     fn test_get_formatted_file_sizes() {
@@ -63,19 +189,21 @@ mod tests {
         let path2 = td.mkfile_with_contents("file2.txt", &"B".repeat(2000));
         let path3 = td.mkfile_with_contents("file3.txt", &"C".repeat(500));
 
-        let file_paths = vec![path1.clone(), path2.clone(), path3.clone()];
-        let result = get_formatted_file_sizes(file_paths).unwrap();
-
-        let expected = format!(
-            "\
-[1.95 KB] {}
-[1000 B] {}
-[500 B] {}",
-            path2.display(),
-            path1.display(),
-            path3.display()
+        let file_paths = vec![path1, path2, path3];
+        let formatter = PathFormatter::new(td.path(), PathStyle::Relative);
+        let result = get_formatted_file_sizes(
+            file_paths,
+            None,
+            &formatter,
+            false,
+            &ArchiveCache::default(),
         );
 
+        let expected = "\
+[1.95 KB] file2.txt
+[1000 B] file1.txt
+[500 B] file3.txt";
+
         assert_eq!(result, expected);
     }
 
@@ -83,8 +211,89 @@ mod tests {
     fn test_get_formatted_file_sizes_empty() {
         let file_paths = vec![];
 
-        let result = get_formatted_file_sizes(file_paths).unwrap();
+        let formatter = PathFormatter::new(Path::new("."), PathStyle::Relative);
+        let result = get_formatted_file_sizes(
+            file_paths,
+            None,
+            &formatter,
+            false,
+            &ArchiveCache::default(),
+        );
 
         assert_eq!(result, "");
     }
+
+    #[test]
+    fn test_get_formatted_file_sizes_shows_encoding() {
+        let td = TempDir::new().unwrap();
+        let (bytes, _, _) = WINDOWS_1252.encode("name = \"Café Müller\"\ncity = \"Zürich\"\n");
+        let latin = td.mkfile_with_bytes("latin.toml", &bytes);
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend("Hello".encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        let utf16 = td.mkfile_with_bytes("utf16.txt", &utf16);
+        let binary = td.mkfile_with_bytes("data.bin", &[0x00, 0xC0, 0xC1]);
+        let utf8 = td.mkfile_with_contents("utf8.txt", "Hi");
+
+        let file_paths = vec![latin.clone(), utf16.clone(), binary.clone(), utf8.clone()];
+        let result = get_formatted_file_sizes(
+            file_paths,
+            None,
+            &absolute(&td),
+            false,
+            &ArchiveCache::default(),
+        );
+
+        let expected = format!(
+            "\
+[37 B] {} (windows-1252)
+[12 B] {} (UTF-16LE)
+[3 B] {} (binary)
+[2 B] {}",
+            latin.display(),
+            utf16.display(),
+            binary.display(),
+            utf8.display()
+        );
+
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_get_formatted_file_sizes_with_encoding() {
+        let td = TempDir::new().unwrap();
+        let path = td.mkfile_with_contents("file.txt", "Hi");
+
+        let result = get_formatted_file_sizes(
+            vec![path.clone()],
+            Some(WINDOWS_1252),
+            &absolute(&td),
+            false,
+            &ArchiveCache::default(),
+        );
+
+        assert_eq!(result, format!("[2 B] {} (windows-1252)", path.display()));
+    }
+
+    #[test]
+    fn test_get_formatted_file_sizes_with_license_headers() {
+        let td = TempDir::new().unwrap();
+        let header = "// Copyright 2024 Acme Inc.\n// SPDX-License-Identifier: MIT\n\n";
+        let path1 = td.mkfile_with_contents("main.rs", &format!("{}fn main() {{}}\n", header));
+        let path2 = td.mkfile_with_contents("notes.txt", "Hello");
+
+        let result = get_formatted_file_sizes(
+            vec![path1, path2],
+            None,
+            &PathFormatter::new(td.path(), PathStyle::Relative),
+            true,
+            &ArchiveCache::default(),
+        );
+
+        let expected = "\
+[74 B] main.rs (license header 61 B)
+[5 B] notes.txt
+Removed 1 license header(s), 61 B";
+
+        assert_eq!(result, expected);
+    }
 }
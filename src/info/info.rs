@@ -1,11 +1,18 @@
 use crate::cli::Cli;
+use crate::error::QuaggaError;
+use crate::file::archive::ArchiveCache;
+use crate::file::cache::{default_cache_dir, FileCache};
+use crate::file::license_header::strips_license_headers;
+use crate::file::path_style::PathFormatter;
+use crate::file::skipped_file::SkippedFile;
 use crate::info::file_sizes::get_formatted_file_sizes;
 use crate::info::show_paths::format_file_paths;
 use crate::info::size::get_total_size;
 use crate::info::tree::file_paths_to_tree;
+use crate::options::QuaggaOptions;
 use crate::template::copy::copy_template;
+use crate::walk::archive_walker::expand_archives;
 use crate::walk::file_walker::get_all_files;
-use std::error::Error;
 use std::path::PathBuf;
 
 /// Generates info output for options like `--paths` or `--tree` that do
@@ -15,8 +22,9 @@ use std::path::PathBuf;
 ///
 /// * `cli` - Command line arguments.
 /// * `paths` - An optional `Vec<PathBuf>` representing a list of file paths.
-///             When present, the program will simply concatenate the paths and return them,
-///             without traversing the root directory.
+///   When present, the program will simply concatenate the paths and return them,
+///   without traversing the root directory.
+/// * `skipped` - The list to which the files that can not be read are added.
 ///
 /// # Returns
 ///
@@ -24,44 +32,92 @@ use std::path::PathBuf;
 pub fn info_output(
     cli: &Cli,
     paths: Option<Vec<PathBuf>>,
-) -> Result<Option<String>, Box<dyn Error>> {
-    if !cli.paths && !cli.tree && !cli.copy_template && !cli.size && !cli.file_sizes {
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<Option<String>, QuaggaError> {
+    if !is_info_output(cli) {
         return Ok(None);
     }
 
+    if cli.clear_cache {
+        return clear_cache(default_cache_dir()).map(Some);
+    }
+
     if cli.copy_template {
-        let output = copy_template(&cli.root.clone())?;
+        let output = copy_template(&cli.root)?;
         return Ok(Some(output));
     }
 
-    let files = get_paths(cli, paths)?;
+    let options = QuaggaOptions::from(cli);
+    let archives = ArchiveCache::default();
+    let files = get_paths(&options, &archives, paths, skipped)?;
+    let formatter = PathFormatter::new(&cli.root, cli.path_style);
 
     let mut output = Vec::new();
 
     if cli.tree {
-        output.push(file_paths_to_tree(files.clone(), Some(cli.root.clone())));
+        output.push(file_paths_to_tree(files.clone(), Some(&formatter)));
     }
 
     if cli.paths {
-        output.push(format_file_paths(files.clone()));
+        output.push(format_file_paths(files.clone(), &formatter));
     }
 
     if cli.file_sizes {
-        output.push(get_formatted_file_sizes(files.clone())?);
+        output.push(get_formatted_file_sizes(
+            files.clone(),
+            cli.encoding,
+            &formatter,
+            strips_license_headers(&options),
+            &archives,
+        ));
     }
 
     if cli.size {
-        output.push(get_total_size(files.clone())?);
+        output.push(get_total_size(files.clone(), &archives)?);
     }
 
     Ok(Some(output.join("\n\n")))
 }
 
-fn get_paths(cli: &Cli, paths: Option<Vec<PathBuf>>) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+/// Checks if any of the options that show info instead of the prompt is used.
+pub fn is_info_output(cli: &Cli) -> bool {
+    cli.paths || cli.tree || cli.copy_template || cli.size || cli.file_sizes || cli.clear_cache
+}
+
+/// Removes all entries from the on-disk cache.
+///
+/// # Arguments
+///
+/// * `dir` - The cache directory, `None` if it could not be determined.
+///
+/// # Returns
+///
+/// A message describing what was removed, or an error if the directory could not be removed.
+fn clear_cache(dir: Option<PathBuf>) -> Result<String, QuaggaError> {
+    let Some(dir) = dir else {
+        return Ok("Cache directory could not be determined, nothing to clear.".to_string());
+    };
+
+    FileCache::new(&dir)
+        .clear()
+        .map_err(|source| QuaggaError::CacheClear {
+            path: dir.clone(),
+            source,
+        })?;
+
+    Ok(format!("Cleared cache at {}", dir.display()))
+}
+
+fn get_paths(
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    paths: Option<Vec<PathBuf>>,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<Vec<PathBuf>, QuaggaError> {
     let files = if let Some(paths) = paths {
-        paths
+        expand_archives(paths, options, archives, skipped)?
     } else {
-        get_all_files(cli)?
+        get_all_files(options, archives, skipped)?
     };
 
     Ok(files)
@@ -76,13 +132,13 @@ mod tests {
     #[test]
     fn test_info_output_all_options() {
         let td = TempDir::new().unwrap();
-        let file1 = td.mkfile_with_contents("file1.txt", "Hello");
-        let file2 = td.mkfile_with_contents("file2.txt", "World");
+        td.mkfile_with_contents("file1.txt", "Hello");
+        td.mkfile_with_contents("file2.txt", "World");
 
-        let mut cli = Cli::parse_from(&["test", "--paths", "--tree", "--size"]);
+        let mut cli = Cli::parse_from(["test", "--paths", "--tree", "--size"]);
         cli.root = td.path_buf();
 
-        let result = info_output(&cli, None).unwrap().unwrap();
+        let result = info_output(&cli, None, &mut Vec::new()).unwrap().unwrap();
         let parts: Vec<&str> = result.split("\n\n").collect();
 
         assert_eq!(parts.len(), 3);
@@ -90,27 +146,16 @@ mod tests {
         // Check tree output
         // ----------
 
-        let expected = format!(
-            "{}
+        let expected = ".
 ├── file1.txt
-└── file2.txt",
-            cli.root.display()
-        );
+└── file2.txt";
 
         assert_eq!(parts[0], expected);
 
         // Check paths output
         // ----------
 
-        assert_eq!(
-            parts[1],
-            format!(
-                "{}
-{}",
-                file1.display(),
-                file2.display()
-            )
-        );
+        assert_eq!(parts[1], "file1.txt\nfile2.txt");
 
         // Check size output
         // ----------
@@ -124,10 +169,10 @@ mod tests {
         td.mkfile_with_contents("file1.txt", "Hello");
         td.mkfile_with_contents("file2.txt", "World");
 
-        let mut cli = Cli::parse_from(&["test", "--size"]);
+        let mut cli = Cli::parse_from(["test", "--size"]);
         cli.root = td.path_buf();
 
-        let result = info_output(&cli, None).unwrap().unwrap();
+        let result = info_output(&cli, None, &mut Vec::new()).unwrap().unwrap();
         let parts: Vec<&str> = result.split("\n\n").collect();
 
         assert_eq!(parts.len(), 1);
@@ -139,20 +184,33 @@ mod tests {
     #[test]
     fn test_info_output_no_options() {
         let td = TempDir::new().unwrap();
-        let mut cli = Cli::parse_from(&["test"]);
+        let mut cli = Cli::parse_from(["test"]);
         cli.root = td.path_buf();
 
-        let result = info_output(&cli, None).unwrap();
+        let result = info_output(&cli, None, &mut Vec::new()).unwrap();
         assert!(result.is_none());
     }
 
     #[test]
     fn test_info_output_copy_template() {
         let td = TempDir::new().unwrap();
-        let mut cli = Cli::parse_from(&["test", "--copy-template"]);
+        let mut cli = Cli::parse_from(["test", "--copy-template"]);
         cli.root = td.path_buf();
 
-        let result = info_output(&cli, None).unwrap().unwrap();
+        let result = info_output(&cli, None, &mut Vec::new()).unwrap().unwrap();
         assert!(result.contains("Template was copied to"));
     }
+
+    #[test]
+    fn test_clear_cache() {
+        let td = TempDir::new().unwrap();
+        let cache_dir = td.path().join("cache");
+        td.mkdir("cache");
+        td.mkfile("cache/entry.json");
+
+        let result = clear_cache(Some(cache_dir.clone())).unwrap();
+
+        assert_eq!(result, format!("Cleared cache at {}", cache_dir.display()));
+        assert!(!cache_dir.exists());
+    }
 }
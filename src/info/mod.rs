@@ -1,4 +1,5 @@
 pub mod file_sizes;
+#[allow(clippy::module_inception)]
 pub mod info;
 pub mod show_paths;
 pub mod size;
@@ -1,16 +1,18 @@
+use crate::file::path_style::PathFormatter;
 use std::path::PathBuf;
 
-/// Converts a list file paths to string. The paths are sorted.
+/// Converts a list file paths to string. The paths are sorted after they are formatted.
 ///
 /// # Arguments
 ///
-/// * `sorted_paths` - A list of file paths.
+/// * `file_paths` - A list of file paths.
+/// * `paths` - Formats the file paths in the `--path-style`.
 ///
 /// # Returns
 ///
 /// A string containing the file paths separated by newlines.
-pub fn format_file_paths(file_paths: Vec<PathBuf>) -> String {
-    let mut sorted_paths = file_paths.clone();
+pub fn format_file_paths(file_paths: Vec<PathBuf>, paths: &PathFormatter) -> String {
+    let mut sorted_paths: Vec<PathBuf> = file_paths.iter().map(|path| paths.format(path)).collect();
     sorted_paths.sort();
 
     let file_paths: Vec<String> = sorted_paths
@@ -24,6 +26,12 @@ pub fn format_file_paths(file_paths: Vec<PathBuf>) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::PathStyle;
+    use std::path::Path;
+
+    fn relative() -> PathFormatter {
+        PathFormatter::new(Path::new("."), PathStyle::Relative)
+    }
 
     #[test]
     fn test_format_file_paths() {
@@ -32,7 +40,7 @@ mod tests {
         let path3 = PathBuf::from("file3.txt");
 
         let files = vec![path3.clone(), path1.clone(), path2.clone()];
-        let output = format_file_paths(files);
+        let output = format_file_paths(files, &relative());
 
         let expected = format!(
             "{}\n{}\n{}",
@@ -47,7 +55,20 @@ mod tests {
     #[test]
     fn test_format_file_paths_no_paths() {
         let files: Vec<PathBuf> = vec![];
-        let output = format_file_paths(files);
+        let output = format_file_paths(files, &relative());
         assert_eq!(output, "");
     }
+
+    #[test]
+    fn test_format_file_paths_with_root_name() {
+        let files = vec![
+            PathBuf::from("/home/alice/acme/src/main.rs"),
+            PathBuf::from("/home/alice/acme/Cargo.toml"),
+        ];
+
+        let formatter = PathFormatter::new(Path::new("/home/alice/acme"), PathStyle::RootName);
+        let output = format_file_paths(files, &formatter);
+
+        assert_eq!(output, "acme/Cargo.toml\nacme/src/main.rs");
+    }
 }
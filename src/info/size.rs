@@ -1,9 +1,10 @@
+use crate::error::QuaggaError;
+use crate::file::archive::ArchiveCache;
 use crate::file::size::{calculate_total_size, human_readable_size};
-use std::error::Error;
 use std::path::PathBuf;
 
-pub fn get_total_size(files: Vec<PathBuf>) -> Result<String, Box<dyn Error>> {
-    let total_size = calculate_total_size(files)?;
+pub fn get_total_size(files: Vec<PathBuf>, archives: &ArchiveCache) -> Result<String, QuaggaError> {
+    let total_size = calculate_total_size(files, archives)?;
     Ok(human_readable_size(total_size))
 }
 
@@ -22,7 +23,7 @@ mod tests {
 
         let files = vec![td.path().join("file1.txt"), td.path().join("file2.txt")];
 
-        let result = get_total_size(files);
+        let result = get_total_size(files, &ArchiveCache::default());
 
         assert!(result.is_ok());
         assert_eq!(result.unwrap(), "11 B");
@@ -1,3 +1,4 @@
+use crate::file::path_style::PathFormatter;
 use std::cmp::Ordering;
 use std::collections::BTreeMap;
 use std::path::PathBuf;
@@ -18,9 +19,10 @@ use std::path::PathBuf;
 /// # Arguments
 ///
 /// * `paths` - A vector of `PathBuf` objects representing the file paths to include in the tree.
-/// * `root` - An optional `PathBuf` object representing the root directory. This argument helps
-///            make the tree more compact. For example, if the root path is `/dir1/dir2` and it
-///            contains the file `/dir1/dir2/file.txt`, then the top tree node will be `/dir1/dir2`:
+/// * `root` - Formats the paths and the root directory in the `--path-style`, or `None`
+///   to show the paths as they are. The root helps make the tree more compact.
+///   For example, with `PathStyle::Absolute`, if the root path is `/dir1/dir2` and it
+///   contains the file `/dir1/dir2/file.txt`, then the top tree node will be `/dir1/dir2`:
 ///
 /// ```text
 /// /dir1/dir2
@@ -33,7 +35,15 @@ use std::path::PathBuf;
 /// # Returns
 ///
 /// A `String` containing the ASCII tree representation of the file paths.
-pub fn file_paths_to_tree(paths: Vec<PathBuf>, root: Option<PathBuf>) -> String {
+pub fn file_paths_to_tree(paths: Vec<PathBuf>, root: Option<&PathFormatter>) -> String {
+    let (paths, root) = match root {
+        Some(formatter) => (
+            paths.iter().map(|path| formatter.tree_path(path)).collect(),
+            Some(formatter.root_label()),
+        ),
+        None => (paths, None),
+    };
+
     let tree = build_tree_structure(&paths, &root);
     let mut output = String::new();
     build_tree(&tree, String::new(), &mut output, true);
@@ -59,7 +69,7 @@ fn build_tree_structure(paths: &Vec<PathBuf>, root: &Option<PathBuf>) -> BTreeMa
 
         let relative_path = if let Some(root) = root {
             // Check if the path can be made relative to the root
-            if let Ok(stripped) = path.strip_prefix(&root) {
+            if let Ok(stripped) = path.strip_prefix(root) {
                 // Use the full root path as the node key
                 // For example, if root path is /dir1/dir2 and it contains the file /dir1/dir2/dir3/file.txt
                 // then the tree node will be /dir1/dir2 (i.e. we don't need to split the path into individual components /, dir1 and dir2)
@@ -168,6 +178,16 @@ fn build_tree(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::PathStyle;
+    use std::path::Path;
+
+    fn absolute(root: &Path) -> PathFormatter {
+        PathFormatter::new(root, PathStyle::Absolute)
+    }
+
+    fn relative(root: &Path) -> PathFormatter {
+        PathFormatter::new(root, PathStyle::Relative)
+    }
 
     #[test]
     fn test_file_paths_to_tree() {
@@ -202,7 +222,7 @@ mod tests {
 
         let root = PathBuf::from("/dir1/dir2");
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
 
         let expected = r#"/dir1/dir2
 ├── docs
@@ -246,7 +266,7 @@ mod tests {
     fn test_empty_paths() {
         let paths = vec![];
         let root = PathBuf::from("/dir1");
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
         assert_eq!(result, "");
     }
 
@@ -254,7 +274,7 @@ mod tests {
     fn test_root_directory_only() {
         let paths = vec![PathBuf::from("/dir1")];
         let root = PathBuf::from("/dir1");
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
         assert_eq!(result, "/dir1");
     }
 
@@ -263,7 +283,7 @@ mod tests {
         let paths = vec![PathBuf::from("/dir1/file.txt")];
         let root = PathBuf::from("/dir1");
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
 
         let expected = r#"/dir1
 └── file.txt"#;
@@ -276,7 +296,7 @@ mod tests {
         let paths = vec![PathBuf::from("/dir1/level1/level2/level3/level4/file.txt")];
         let root = PathBuf::from("/dir1");
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
 
         let expected = r#"/dir1
 └── level1
@@ -296,7 +316,7 @@ mod tests {
         ];
         let root = PathBuf::from("/dir1");
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
 
         let expected = r#"/dir1
 ├── dirA
@@ -316,7 +336,7 @@ mod tests {
 
         let root = PathBuf::from("/dir1");
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
 
         let expected = r#"/dir1
 ├── File.txt
@@ -334,7 +354,7 @@ mod tests {
 
         let root = PathBuf::from("/dir1");
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
 
         let expected = r#"/dir1
 ├── dir with space
@@ -348,7 +368,7 @@ mod tests {
         let paths = vec![PathBuf::from("./file1.txt"), PathBuf::from("./file2.txt")];
         let root = PathBuf::from(".");
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&relative(&root)));
 
         let expected = r#".
 ├── file1.txt
@@ -362,7 +382,7 @@ mod tests {
         let paths = vec![PathBuf::from("/file1.txt"), PathBuf::from("/file2.txt")];
         let root = PathBuf::from("dir"); // Root is different from paths
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
 
         let expected = r#"/
 ├── file1.txt
@@ -379,7 +399,7 @@ mod tests {
         ];
         let root = PathBuf::from("dir"); // Root is different from paths
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&relative(&root)));
 
         let expected = r#"dir1
 └── dir2
@@ -400,7 +420,7 @@ mod tests {
 
         let root = PathBuf::from("/dir1/dir2");
 
-        let result = file_paths_to_tree(paths, Some(root));
+        let result = file_paths_to_tree(paths, Some(&absolute(&root)));
 
         // Since the root "/dir1/dir2" dir contains the files "/dir1/dir2/file1.txt" and "/dir1/dir2/file2.txt"
         // the dir "/dir1/dir2" will be use as tree node.
@@ -436,4 +456,34 @@ mod tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_file_paths_to_tree_with_path_styles() {
+        let paths = vec![
+            PathBuf::from("/home/alice/acme/src/main.rs"),
+            PathBuf::from("/home/alice/acme/README.md"),
+        ];
+
+        let root = Path::new("/home/alice/acme");
+
+        let formatter = PathFormatter::new(root, PathStyle::Relative);
+        let result = file_paths_to_tree(paths.clone(), Some(&formatter));
+
+        assert_eq!(
+            result,
+            r#".
+├── src
+│   └── main.rs
+└── README.md"#
+        );
+
+        let formatter = PathFormatter::new(root, PathStyle::RootName);
+        let result = file_paths_to_tree(paths.clone(), Some(&formatter));
+        assert!(result.starts_with("acme\n├── src"));
+
+        let formatter = PathFormatter::new(root, PathStyle::Anonymized);
+        let result = file_paths_to_tree(paths, Some(&formatter));
+        assert!(result.starts_with("project\n├── src"));
+        assert!(!result.contains("alice"));
+    }
 }
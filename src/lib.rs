@@ -1,8 +1,22 @@
 pub mod cli;
+pub mod error;
 pub mod file;
 pub mod info;
+pub mod options;
 pub mod output;
 pub mod processor;
+pub mod quagga;
 pub mod template;
 pub mod test_utils;
 pub mod walk;
+
+pub use encoding_rs::Encoding;
+pub use error::QuaggaError;
+pub use file::file_content::FileContent;
+pub use file::redact::Redaction;
+pub use file::transform::{
+    ContentTransform, TransformKind, TransformPipeline, TransformReport, TransformSavings,
+    TransformSpec,
+};
+pub use options::QuaggaOptions;
+pub use quagga::{Prompt, Quagga, QuaggaBuilder, WriteSummary};
@@ -1,14 +1,6 @@
-mod cli;
-mod file;
-mod info;
-mod output;
-mod processor;
-mod template;
-mod test_utils;
-mod walk;
 use clap::Parser;
-use cli::Cli;
-use processor::generate_prompt_and_output;
+use quagga::cli::Cli;
+use quagga::processor::generate_prompt_and_output;
 use std::io::{self, BufRead, IsTerminal};
 use std::path::PathBuf;
 use std::process;
@@ -22,7 +14,7 @@ fn main() {
         Ok(_) => {}
         Err(e) => {
             eprintln!("Error: {}", e);
-            process::exit(1);
+            process::exit(e.exit_code());
         }
     }
 }
@@ -40,12 +32,12 @@ fn piped_paths() -> Option<Vec<PathBuf>> {
 
     let stdin = io::stdin();
 
-    return Some(
+    Some(
         stdin
             .lock()
             .lines()
-            .filter_map(|line| line.ok())
+            .map_while(Result::ok)
             .map(PathBuf::from)
             .collect(),
-    );
+    )
 }
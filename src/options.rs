@@ -0,0 +1,532 @@
+use crate::cli::Cli;
+use crate::file::transform::TransformSpec;
+use crate::walk::modified::TimeReference;
+use clap::ValueEnum;
+use encoding_rs::Encoding;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Default maximum number of characters in a single part of the output prompt.
+pub const DEFAULT_MAX_PART_SIZE: u64 = 100_000;
+
+/// Default maximum size of a single file in bytes. Larger files are ignored.
+pub const DEFAULT_MAX_FILESIZE: u64 = 300 * 1024;
+
+/// Default maximum total size of all files in bytes.
+pub const DEFAULT_MAX_TOTAL_SIZE: u64 = 500 * 1024;
+
+/// What to do when a single file can not be read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OnError {
+    /// Leave the file out of the prompt silently.
+    Skip,
+
+    /// Leave the file out of the prompt and report it on stderr
+    /// and with the `<skipped-files>` template tag.
+    #[default]
+    Warn,
+
+    /// Stop with an error.
+    Fail,
+}
+
+/// The order of the files in the prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SortOrder {
+    /// Sort by path.
+    Path,
+
+    /// Sort by size, largest first.
+    Size,
+
+    /// Sort by modification time, most recently modified first.
+    Mtime,
+
+    /// Sort by the time of the latest git commit, most recently committed first.
+    /// Files that were never committed come first.
+    GitRecency,
+
+    /// Sort by the number of directories in the path, top-level files first.
+    Depth,
+
+    /// Sort by file extension.
+    Type,
+
+    /// Keep the order of piped paths. Files found by walking the directory are sorted by path.
+    #[value(name = "none")]
+    Unsorted,
+}
+
+/// How the paths to files are shown in the prompt and in the file lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum PathStyle {
+    /// Relative to the root, e.g. `src/main.rs`.
+    #[default]
+    Relative,
+
+    /// Absolute, e.g. `/home/alice/project/src/main.rs`.
+    Absolute,
+
+    /// Starting with the name of the root directory, e.g. `project/src/main.rs`.
+    RootName,
+
+    /// Starting with `project` instead of the root directory name. Only the file names
+    /// of files outside the root are shown, e.g. `external/notes.txt`.
+    Anonymized,
+}
+
+/// The clipboard `--clipboard` copies the prompt to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum ClipboardBackend {
+    /// The system clipboard, or OSC 52 if there is no display server.
+    #[default]
+    Auto,
+
+    /// The clipboard of the desktop (X11, Wayland, macOS or Windows).
+    System,
+
+    /// The clipboard of the terminal, set with OSC 52 escape sequences.
+    /// Works over SSH, in tmux and in GNU screen.
+    Osc52,
+}
+
+/// Options that control how the prompt is generated and where it is sent.
+///
+/// This is the library-facing configuration of `quagga`. The command line
+/// arguments (`Cli`) are converted into these options, and library users can
+/// either fill the struct directly or use the `Quagga` builder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuaggaOptions {
+    /// The root directory to search for files.
+    pub root: PathBuf,
+
+    /// Include only file paths matching the glob patterns.
+    pub include: Vec<String>,
+
+    /// Exclude file paths that match the glob patterns.
+    pub exclude: Vec<String>,
+
+    /// Include only files that contain any of the texts.
+    pub contain: Vec<String>,
+
+    /// Include only files that contain any of the whole words.
+    pub contain_word: Vec<String>,
+
+    /// Include only files that match any of the regular expressions.
+    pub contain_regex: Vec<String>,
+
+    /// Include only files that contain all of the `contain`, `contain_word`
+    /// and `contain_regex` patterns, instead of any of them.
+    pub contain_all: bool,
+
+    /// Exclude files that contain any of the texts.
+    pub not_contain: Vec<String>,
+
+    /// Ignore case when matching `contain`, `contain_word`, `contain_regex` and `not_contain`.
+    pub ignore_case: bool,
+
+    /// Include only the lines matching the `contain`, `contain_word` and `contain_regex`
+    /// patterns, with this many lines of context around them.
+    pub excerpt: Option<usize>,
+
+    /// Include only files modified within this time before now.
+    pub modified_within: Option<Duration>,
+
+    /// Include only files modified at or after this time.
+    pub newer_than: Option<TimeReference>,
+
+    /// Include only files modified before this time.
+    pub older_than: Option<TimeReference>,
+
+    /// The order of the files in the prompt. When `None`, the files found by walking
+    /// the root directory are sorted by path and the piped paths keep their order.
+    pub sort: Option<SortOrder>,
+
+    /// Reverse the order of the files.
+    pub reverse: bool,
+
+    /// Put the files matching these glob patterns first, in the order of the patterns.
+    pub first: Vec<String>,
+
+    /// Descend only this many directories deep.
+    pub max_depth: Option<usize>,
+
+    /// Split the output into parts of this many characters.
+    pub max_part_size: u64,
+
+    /// Ignore files above this size in bytes.
+    pub max_filesize: u64,
+
+    /// Return an error if the total size of files is above this size in bytes.
+    pub max_total_size: u64,
+
+    /// Don't use .gitignore files.
+    pub no_gitignore: bool,
+
+    /// Don't use .quagga_ignore files from project and home dirs.
+    pub no_quagga_ignore: bool,
+
+    /// Include binary files.
+    pub binary: bool,
+
+    /// The encoding of the files. When `None`, the encoding of each file is detected.
+    pub encoding: Option<&'static Encoding>,
+
+    /// Include the text outputs of Jupyter notebook cells, truncated to this many characters.
+    /// When `None`, the outputs are left out.
+    pub notebook_outputs: Option<usize>,
+
+    /// Skip generated files and lock files.
+    pub skip_generated: bool,
+
+    /// Skip minified files, source maps and encoded data.
+    pub skip_minified: bool,
+
+    /// Include hidden files.
+    pub hidden: bool,
+
+    /// Follow symbolic links.
+    pub follow_links: bool,
+
+    /// Path to a custom template file.
+    pub template: Option<PathBuf>,
+
+    /// Don't use .quagga_template from project and home dirs.
+    pub no_quagga_template: bool,
+
+    /// How the paths to files are shown in the prompt.
+    pub path_style: PathStyle,
+
+    /// Output to a file instead of stdout.
+    pub output: Option<PathBuf>,
+
+    /// Copy the output to the clipboard instead of stdout.
+    pub clipboard: bool,
+
+    /// The clipboard the output is copied to with `clipboard`.
+    pub clipboard_backend: ClipboardBackend,
+
+    /// Copy this part of the output last copied to the clipboard again, without
+    /// generating the prompt. The parts are read from the session saved in the cache directory.
+    pub clipboard_part: Option<usize>,
+
+    /// Send the prompt to this OpenAI-compatible chat completions endpoint
+    /// and print the reply instead of the prompt.
+    pub send: Option<String>,
+
+    /// The model the prompt is sent to with `send`.
+    pub model: Option<String>,
+
+    /// The name of the environment variable with the API key used with `send`.
+    pub api_key_env: Option<String>,
+
+    /// The system message sent before the prompt with `send`.
+    pub system: Option<String>,
+
+    /// Run this shell command for each part of the prompt, with the part on stdin,
+    /// instead of printing the prompt.
+    pub exec: Option<String>,
+
+    /// Write a manifest describing the parts next to the `output` file.
+    pub manifest: bool,
+
+    /// Remove comments from the files.
+    pub remove_comments: bool,
+
+    /// Replace the bodies of functions with `{ ... }` in all source files,
+    /// keeping only the type definitions, signatures and doc comments.
+    pub outline: bool,
+
+    /// Outline only the source files matching these glob patterns.
+    pub outline_glob: Vec<String>,
+
+    /// Remove the license comments at the start of files, and the comment blocks
+    /// that many of the files start with.
+    pub strip_license_headers: bool,
+
+    /// Remove the whitespace at the ends of lines and replace runs of blank lines
+    /// with a single blank line, except where the whitespace is part of the syntax.
+    pub compact: bool,
+
+    /// With `compact`, replace each this many spaces at the start of lines with a tab,
+    /// except in Python, YAML and makefiles.
+    pub compact_tabs: Option<usize>,
+
+    /// The built-in transforms applied to the files after the ones enabled by other options,
+    /// in this order.
+    pub transforms: Vec<TransformSpec>,
+
+    /// Replace secrets, such as private keys and tokens, with `[REDACTED:kind]`.
+    pub redact: bool,
+
+    /// What to do when a file can not be read.
+    pub on_error: OnError,
+
+    /// Cache the results of processing files on disk to speed up repeated runs.
+    pub cache: bool,
+
+    /// The cache directory. When `None`, `$XDG_CACHE_HOME/quagga` or `~/.cache/quagga` is used.
+    pub cache_dir: Option<PathBuf>,
+}
+
+impl Default for QuaggaOptions {
+    fn default() -> Self {
+        QuaggaOptions {
+            root: PathBuf::from("."),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            contain: Vec::new(),
+            contain_word: Vec::new(),
+            contain_regex: Vec::new(),
+            contain_all: false,
+            not_contain: Vec::new(),
+            ignore_case: false,
+            excerpt: None,
+            modified_within: None,
+            newer_than: None,
+            older_than: None,
+            sort: None,
+            reverse: false,
+            first: Vec::new(),
+            max_depth: None,
+            max_part_size: DEFAULT_MAX_PART_SIZE,
+            max_filesize: DEFAULT_MAX_FILESIZE,
+            max_total_size: DEFAULT_MAX_TOTAL_SIZE,
+            no_gitignore: false,
+            no_quagga_ignore: false,
+            binary: false,
+            encoding: None,
+            notebook_outputs: None,
+            skip_generated: true,
+            skip_minified: true,
+            hidden: false,
+            follow_links: false,
+            template: None,
+            no_quagga_template: false,
+            path_style: PathStyle::default(),
+            output: None,
+            clipboard: false,
+            clipboard_backend: ClipboardBackend::default(),
+            clipboard_part: None,
+            send: None,
+            model: None,
+            api_key_env: None,
+            system: None,
+            exec: None,
+            manifest: false,
+            remove_comments: false,
+            outline: false,
+            outline_glob: Vec::new(),
+            strip_license_headers: false,
+            compact: false,
+            compact_tabs: None,
+            transforms: Vec::new(),
+            redact: true,
+            on_error: OnError::default(),
+            cache: false,
+            cache_dir: None,
+        }
+    }
+}
+
+impl From<&Cli> for QuaggaOptions {
+    fn from(cli: &Cli) -> Self {
+        QuaggaOptions {
+            root: cli.root.clone(),
+            include: cli.include.clone(),
+            exclude: cli.exclude.clone(),
+            contain: cli.contain.clone(),
+            contain_word: cli.contain_word.clone(),
+            contain_regex: cli.contain_regex.clone(),
+            contain_all: cli.contain_all,
+            not_contain: cli.not_contain.clone(),
+            ignore_case: cli.ignore_case,
+            excerpt: cli.excerpt,
+            modified_within: cli.modified_within,
+            newer_than: cli.newer_than.clone(),
+            older_than: cli.older_than.clone(),
+            sort: cli.sort,
+            reverse: cli.reverse,
+            first: cli.first.clone(),
+            max_depth: cli.max_depth,
+            max_part_size: cli.max_part_size,
+            max_filesize: cli.max_filesize,
+            max_total_size: cli.max_total_size,
+            no_gitignore: cli.no_gitignore,
+            no_quagga_ignore: cli.no_quagga_ignore,
+            binary: cli.binary,
+            encoding: cli.encoding,
+            notebook_outputs: cli.notebook_outputs,
+            skip_generated: cli.skip_generated || !cli.include_generated,
+            skip_minified: cli.skip_minified || !cli.include_generated,
+            hidden: cli.hidden,
+            follow_links: cli.follow_links,
+            template: cli.template.clone(),
+            no_quagga_template: cli.no_quagga_template,
+            path_style: cli.path_style,
+            output: cli.output.clone(),
+            clipboard: cli.clipboard,
+            clipboard_backend: cli.clipboard_backend,
+            clipboard_part: cli.clipboard_part,
+            send: cli.send.clone(),
+            model: cli.model.clone(),
+            api_key_env: cli.api_key_env.clone(),
+            system: cli.system.clone(),
+            exec: cli.exec.clone(),
+            manifest: cli.manifest,
+            remove_comments: cli.remove_comments,
+            outline: cli.outline,
+            outline_glob: cli.outline_glob.clone(),
+            strip_license_headers: cli.strip_license_headers,
+            compact: cli.compact,
+            compact_tabs: cli.compact_tabs,
+            transforms: cli.transform.clone(),
+            redact: cli.redact || !cli.no_redact,
+            on_error: cli.on_error,
+            cache: cli.cache && !cli.no_cache,
+            cache_dir: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file::transform::TransformKind;
+    use clap::Parser;
+
+    #[test]
+    fn test_default_options_match_cli_defaults() {
+        let cli = Cli::parse_from(["quagga"]);
+
+        assert_eq!(QuaggaOptions::from(&cli), QuaggaOptions::default());
+    }
+
+    #[test]
+    fn test_from_cli() {
+        let cli = Cli::parse_from([
+            "quagga",
+            "--include",
+            "*.rs",
+            "--exclude",
+            "target",
+            "--contain",
+            "todo",
+            "--not-contain",
+            "done",
+            "--ignore-case",
+            "--modified-within",
+            "1h",
+            "--sort",
+            "mtime",
+            "--max-depth",
+            "3",
+            "--max-part-size",
+            "500",
+            "--binary",
+            "--encoding",
+            "utf-16le",
+            "--notebook-outputs",
+            "200",
+            "--include-generated",
+            "--skip-minified",
+            "--remove-comments",
+            "--outline-glob",
+            "vendor/**",
+            "--strip-license-headers",
+            "--compact",
+            "--compact-tabs",
+            "2",
+            "--transform",
+            "collapse-blank-lines",
+            "tabs-to-spaces=2:*.go",
+            "--no-redact",
+            "--on-error",
+            "fail",
+            "--cache",
+            "--path-style",
+            "root-name",
+            "--clipboard-backend",
+            "osc52",
+            "--clipboard-part",
+            "2",
+            "--send",
+            "http://localhost:8080",
+            "--model",
+            "llama3",
+            "--api-key-env",
+            "LLM_API_KEY",
+            "--system",
+            "Review the code",
+            "src",
+        ]);
+
+        let options = QuaggaOptions::from(&cli);
+
+        assert_eq!(
+            options,
+            QuaggaOptions {
+                root: PathBuf::from("src"),
+                include: vec!["*.rs".to_string()],
+                exclude: vec!["target".to_string()],
+                contain: vec!["todo".to_string()],
+                not_contain: vec!["done".to_string()],
+                ignore_case: true,
+                modified_within: Some(Duration::from_secs(60 * 60)),
+                sort: Some(SortOrder::Mtime),
+                max_depth: Some(3),
+                max_part_size: 500,
+                binary: true,
+                encoding: Some(encoding_rs::UTF_16LE),
+                notebook_outputs: Some(200),
+                skip_generated: false,
+                remove_comments: true,
+                outline_glob: vec!["vendor/**".to_string()],
+                strip_license_headers: true,
+                compact: true,
+                compact_tabs: Some(2),
+                transforms: vec![
+                    TransformSpec {
+                        kind: TransformKind::CollapseBlankLines,
+                        glob: None,
+                    },
+                    TransformSpec {
+                        kind: TransformKind::TabsToSpaces(2),
+                        glob: Some("*.go".to_string()),
+                    },
+                ],
+                redact: false,
+                on_error: OnError::Fail,
+                cache: true,
+                path_style: PathStyle::RootName,
+                output: None,
+                clipboard: false,
+                clipboard_backend: ClipboardBackend::Osc52,
+                clipboard_part: Some(2),
+                send: Some("http://localhost:8080".to_string()),
+                model: Some("llama3".to_string()),
+                api_key_env: Some("LLM_API_KEY".to_string()),
+                system: Some("Review the code".to_string()),
+                exec: None,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_from_cli_manifest() {
+        let cli = Cli::parse_from(["quagga", "--output", "prompt.txt", "--manifest"]);
+        let options = QuaggaOptions::from(&cli);
+
+        assert!(options.manifest);
+        assert_eq!(options.output, Some(PathBuf::from("prompt.txt")));
+    }
+    #[test]
+    fn test_from_cli_output_destinations() {
+        let options = QuaggaOptions::from(&Cli::parse_from(["quagga", "--clipboard"]));
+        assert!(options.clipboard);
+
+        let options = QuaggaOptions::from(&Cli::parse_from(["quagga", "--exec", "wc -c"]));
+        assert_eq!(options.exec, Some("wc -c".to_string()));
+    }
+}
@@ -1,51 +1,469 @@
+use super::osc52::{split_into_chunks, Osc52Clipboard, OSC52_MAX_TEXT_BYTES};
+use super::session::ClipboardSession;
+use crate::file::size::human_readable_size;
+use crate::options::ClipboardBackend;
 use arboard::Clipboard;
+use std::env;
 use std::error::Error;
+use std::fs::File;
 use std::io::{self, Write};
+use std::path::Path;
 
-pub fn output_to_clipboard(content: Vec<String>) -> Result<(), Box<dyn Error>> {
-    let mut clipboard = Clipboard::new()?;
+/// The clipboard the prompt is copied to.
+enum ClipboardSink {
+    System(Clipboard),
+    Osc52(Osc52Clipboard<File>),
+}
+
+impl ClipboardSink {
+    /// Opens the clipboard of the backend. The `Auto` backend uses the system clipboard,
+    /// or OSC 52 if there is no display server or the system clipboard can not be opened.
+    /// OSC 52 fails if there is no terminal to write the escape sequences to.
+    fn open(backend: ClipboardBackend) -> Result<Self, Box<dyn Error>> {
+        match backend {
+            ClipboardBackend::System => Ok(ClipboardSink::System(Clipboard::new()?)),
+            ClipboardBackend::Osc52 => Ok(ClipboardSink::Osc52(Osc52Clipboard::to_terminal()?)),
+            ClipboardBackend::Auto => {
+                let system = has_display_server()
+                    .then(Clipboard::new)
+                    .and_then(Result::ok);
+
+                match system {
+                    Some(clipboard) => Ok(ClipboardSink::System(clipboard)),
+                    None => Ok(ClipboardSink::Osc52(Osc52Clipboard::to_terminal()?)),
+                }
+            }
+        }
+    }
+
+    fn set_text(&mut self, text: &str) -> Result<(), Box<dyn Error>> {
+        match self {
+            ClipboardSink::System(clipboard) => clipboard.set_text(text.to_string())?,
+            ClipboardSink::Osc52(clipboard) => clipboard.set_text(text)?,
+        }
+
+        Ok(())
+    }
+
+    /// The maximum number of bytes that can be copied at once, `None` if there is no limit.
+    fn max_text_bytes(&self) -> Option<usize> {
+        match self {
+            ClipboardSink::System(_) => None,
+            ClipboardSink::Osc52(_) => Some(OSC52_MAX_TEXT_BYTES),
+        }
+    }
+}
+
+/// A piece of the output that is copied to the clipboard at once: a part of the prompt,
+/// or a chunk of a part that is too large for the clipboard.
+#[derive(Debug, PartialEq)]
+struct ClipboardItem<'a> {
+    text: &'a str,
+    part: usize,
+    chunk: usize,
+    total_chunks: usize,
+}
+
+/// What the user asked for in the part navigator.
+#[derive(Debug, PartialEq)]
+enum NavigatorCommand {
+    Next,
+    Previous,
+    Part(usize),
+    Recopy,
+    Quit,
+}
 
-    if content.len() == 1 {
-        copy_single_part(&mut clipboard, &content[0])?;
+/// Copies the output to the clipboard. When there is more than one part, or a part
+/// is too large to be copied at once, the user navigates between them: Enter or `n` copies
+/// the next one, `p` the previous one, a number copies that part, `r` copies the current
+/// one again and `q` quits.
+///
+/// # Arguments
+///
+/// * `content` - An output prompt text, splitted into parts.
+/// * `backend` - The clipboard to use.
+/// * `session_dir` - The directory the parts are saved to, so that they can be copied
+///   again with `--clipboard-part`. `None` if the parts are not saved.
+pub fn output_to_clipboard(
+    content: Vec<String>,
+    backend: ClipboardBackend,
+    session_dir: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let session = ClipboardSession { parts: content };
+
+    if let Some(dir) = session_dir {
+        if let Err(error) = session.save(dir) {
+            eprintln!(
+                "Warning: failed to save the parts for --clipboard-part: {}",
+                error
+            );
+        }
+    }
+
+    let mut clipboard = ClipboardSink::open(backend)?;
+    let items = clipboard_items(&session.parts, clipboard.max_text_bytes());
+
+    if items.len() == 1 {
+        copy_single_part(&mut clipboard, items[0].text)?;
     } else {
-        copy_multiple_parts(&mut clipboard, &content)?;
+        copy_multiple_parts(&mut clipboard, &items, session.parts.len())?;
     }
 
     Ok(())
 }
 
-fn copy_single_part(clipboard: &mut Clipboard, content: &str) -> Result<(), Box<dyn Error>> {
-    clipboard.set_text(content.trim().to_string())?;
-    println!("Output copied to clipboard.");
+/// Copies a part of the output that was last copied to the clipboard,
+/// without generating the prompt again.
+///
+/// # Arguments
+///
+/// * `number` - The number of the part, starting from 1.
+/// * `backend` - The clipboard to use.
+/// * `session_dir` - The directory the parts were saved to by `output_to_clipboard`.
+pub fn output_saved_part_to_clipboard(
+    number: usize,
+    backend: ClipboardBackend,
+    session_dir: Option<&Path>,
+) -> Result<(), Box<dyn Error>> {
+    let dir = session_dir.ok_or("cache directory could not be determined")?;
+
+    let session = ClipboardSession::load(dir)
+        .map_err(|_| "no saved parts, the parts are saved only with --cache: copy the output with --clipboard --cache first")?;
+
+    let total_parts = session.parts.len();
+
+    if number == 0 || number > total_parts {
+        return Err(format!(
+            "there is no part {}, the saved output has {} part(s)",
+            number, total_parts
+        )
+        .into());
+    }
+
+    let mut clipboard = ClipboardSink::open(backend)?;
+
+    let items: Vec<ClipboardItem> = clipboard_items(&session.parts, clipboard.max_text_bytes())
+        .into_iter()
+        .filter(|item| item.part == number)
+        .collect();
+
+    if items.len() > 1 {
+        return copy_multiple_parts(&mut clipboard, &items, total_parts);
+    }
+
+    clipboard.set_text(items[0].text)?;
+    println!("{} copied to clipboard.", item_name(&items[0], total_parts));
     Ok(())
 }
 
-fn copy_multiple_parts(
-    clipboard: &mut Clipboard,
-    content: &[String],
-) -> Result<(), Box<dyn Error>> {
+/// Checks if a display server is available for the system clipboard. The clipboards
+/// of macOS and Windows do not need one.
+fn has_display_server() -> bool {
+    if cfg!(any(target_os = "macos", windows)) {
+        return true;
+    }
+
+    env::var_os("DISPLAY").is_some() || env::var_os("WAYLAND_DISPLAY").is_some()
+}
+
+/// Splits the trimmed parts into the pieces copied to the clipboard at once.
+fn clipboard_items(content: &[String], max_bytes: Option<usize>) -> Vec<ClipboardItem<'_>> {
+    let mut items = Vec::new();
+
     for (index, part) in content.iter().enumerate() {
-        clipboard.set_text(part.trim().to_string())?;
+        let part = part.trim();
 
-        println!(
-            "Part {} of {} copied to clipboard.",
-            index + 1,
-            content.len()
-        );
+        let chunks = match max_bytes {
+            Some(max_bytes) => split_into_chunks(part, max_bytes),
+            None => vec![part],
+        };
+
+        let total_chunks = chunks.len();
 
-        if index < content.len() - 1 {
-            println!("Press Enter to copy the next part...");
-            wait_for_enter()?;
+        for (chunk_index, text) in chunks.into_iter().enumerate() {
+            items.push(ClipboardItem {
+                text,
+                part: index + 1,
+                chunk: chunk_index + 1,
+                total_chunks,
+            });
         }
     }
 
-    println!("We are done.");
+    items
+}
+
+/// Describes the item for the messages, e.g. `Part 1 of 2` or `Part 1 of 2, chunk 2 of 3`.
+fn item_name(item: &ClipboardItem, total_parts: usize) -> String {
+    let part = format!("Part {} of {}", item.part, total_parts);
+
+    if item.total_chunks == 1 {
+        return part;
+    }
+
+    let chunk = format!("chunk {} of {}", item.chunk, item.total_chunks);
+
+    if total_parts == 1 {
+        return format!("Output {}", chunk);
+    }
+
+    format!("{}, {}", part, chunk)
+}
+
+fn copy_single_part(clipboard: &mut ClipboardSink, content: &str) -> Result<(), Box<dyn Error>> {
+    clipboard.set_text(content)?;
+    println!("Output copied to clipboard.");
     Ok(())
 }
 
-fn wait_for_enter() -> io::Result<()> {
-    io::stdout().flush()?;
-    let mut input = String::new();
-    io::stdin().read_line(&mut input)?;
+fn copy_multiple_parts(
+    clipboard: &mut ClipboardSink,
+    items: &[ClipboardItem],
+    total_parts: usize,
+) -> Result<(), Box<dyn Error>> {
+    println!("{}", format_part_sizes(items));
+    let mut current = Some(0);
+
+    while let Some(index) = current {
+        let item = &items[index];
+        clipboard.set_text(item.text)?;
+        println!("{} copied to clipboard.", item_name(item, total_parts));
+        current = ask_next_item(items, index, total_parts)?;
+    }
+
+    println!("We are done.");
     Ok(())
 }
+
+/// Lists the sizes of the parts, e.g. `  1. 1.95 KB (2 chunks)`.
+fn format_part_sizes(items: &[ClipboardItem]) -> String {
+    let mut lines = vec!["Parts:".to_string()];
+
+    for item in items.iter().filter(|item| item.chunk == 1) {
+        let size: usize = items
+            .iter()
+            .filter(|other| other.part == item.part)
+            .map(|other| other.text.len())
+            .sum();
+
+        let mut line = format!("  {}. {}", item.part, human_readable_size(size as u64));
+
+        if item.total_chunks > 1 {
+            line.push_str(&format!(" ({} chunks)", item.total_chunks));
+        }
+
+        lines.push(line);
+    }
+
+    lines.join("\n")
+}
+
+/// Asks the user which item to copy after the item at `index`, until a valid command is entered.
+///
+/// # Returns
+///
+/// The index of the item to copy, or `None` when the user is done.
+fn ask_next_item(
+    items: &[ClipboardItem],
+    index: usize,
+    total_parts: usize,
+) -> Result<Option<usize>, Box<dyn Error>> {
+    println!("{}", navigator_prompt(items, index, total_parts));
+
+    loop {
+        io::stdout().flush()?;
+        let mut input = String::new();
+
+        // The input has ended
+        if io::stdin().read_line(&mut input)? == 0 {
+            return Ok(None);
+        }
+
+        let next = match parse_command(&input) {
+            Some(command) => next_item(items, index, command),
+            None => Err(format!("Unknown command '{}'.", input.trim())),
+        };
+
+        match next {
+            Ok(next) => return Ok(next),
+            Err(message) => println!("{}", message),
+        }
+    }
+}
+
+/// Tells the user what Enter does and lists the other commands.
+fn navigator_prompt(items: &[ClipboardItem], index: usize, total_parts: usize) -> String {
+    let enter = match items.get(index + 1) {
+        Some(next) if next.part == items[index].part => "copy the next chunk",
+        Some(_) => "copy the next part",
+        None => "finish",
+    };
+
+    let mut commands = vec!["p: previous".to_string()];
+
+    if total_parts > 1 {
+        commands.push(format!("1-{}: part number", total_parts));
+    }
+
+    commands.push("r: copy again".to_string());
+    commands.push("q: quit".to_string());
+
+    format!("Press Enter to {}... ({})", enter, commands.join(", "))
+}
+
+fn parse_command(input: &str) -> Option<NavigatorCommand> {
+    match input.trim() {
+        "" | "n" => Some(NavigatorCommand::Next),
+        "p" => Some(NavigatorCommand::Previous),
+        "r" => Some(NavigatorCommand::Recopy),
+        "q" => Some(NavigatorCommand::Quit),
+        text => text.parse().ok().map(NavigatorCommand::Part),
+    }
+}
+
+/// Returns the index of the item to copy after the command, `None` if the user is done,
+/// or a message if the command can not be done.
+fn next_item(
+    items: &[ClipboardItem],
+    index: usize,
+    command: NavigatorCommand,
+) -> Result<Option<usize>, String> {
+    match command {
+        NavigatorCommand::Next => Ok((index + 1 < items.len()).then_some(index + 1)),
+        NavigatorCommand::Previous if index == 0 => Err("This is the first part.".to_string()),
+        NavigatorCommand::Previous => Ok(Some(index - 1)),
+        NavigatorCommand::Part(number) => items
+            .iter()
+            .position(|item| item.part == number)
+            .map(Some)
+            .ok_or_else(|| format!("There is no part {}.", number)),
+        NavigatorCommand::Recopy => Ok(Some(index)),
+        NavigatorCommand::Quit => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+
+    fn item(text: &str, part: usize, chunk: usize, total_chunks: usize) -> ClipboardItem<'_> {
+        ClipboardItem {
+            text,
+            part,
+            chunk,
+            total_chunks,
+        }
+    }
+
+    #[test]
+    fn test_clipboard_items() {
+        let content = vec!["\nab\ncd\n".to_string(), "ef ".to_string()];
+
+        assert_eq!(
+            clipboard_items(&content, None),
+            vec![item("ab\ncd", 1, 1, 1), item("ef", 2, 1, 1)]
+        );
+
+        assert_eq!(
+            clipboard_items(&content, Some(3)),
+            vec![
+                item("ab\n", 1, 1, 2),
+                item("cd", 1, 2, 2),
+                item("ef", 2, 1, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(parse_command("\n"), Some(NavigatorCommand::Next));
+        assert_eq!(parse_command("n\n"), Some(NavigatorCommand::Next));
+        assert_eq!(parse_command("p\n"), Some(NavigatorCommand::Previous));
+        assert_eq!(parse_command(" 3 \n"), Some(NavigatorCommand::Part(3)));
+        assert_eq!(parse_command("r\n"), Some(NavigatorCommand::Recopy));
+        assert_eq!(parse_command("q\n"), Some(NavigatorCommand::Quit));
+        assert_eq!(parse_command("x\n"), None);
+    }
+
+    #[test]
+    fn test_next_item() {
+        let items = vec![item("a", 1, 1, 1), item("b", 2, 1, 2), item("c", 2, 2, 2)];
+
+        assert_eq!(next_item(&items, 0, NavigatorCommand::Next), Ok(Some(1)));
+        assert_eq!(next_item(&items, 2, NavigatorCommand::Next), Ok(None));
+        assert_eq!(
+            next_item(&items, 2, NavigatorCommand::Previous),
+            Ok(Some(1))
+        );
+        assert!(next_item(&items, 0, NavigatorCommand::Previous).is_err());
+        assert_eq!(next_item(&items, 0, NavigatorCommand::Part(2)), Ok(Some(1)));
+        assert_eq!(
+            next_item(&items, 0, NavigatorCommand::Part(3)),
+            Err("There is no part 3.".to_string())
+        );
+        assert_eq!(next_item(&items, 1, NavigatorCommand::Recopy), Ok(Some(1)));
+        assert_eq!(next_item(&items, 1, NavigatorCommand::Quit), Ok(None));
+    }
+
+    #[test]
+    fn test_navigator_prompt() {
+        let items = vec![item("a", 1, 1, 1), item("b", 2, 1, 2), item("c", 2, 2, 2)];
+
+        assert_eq!(
+            navigator_prompt(&items, 0, 2),
+            "Press Enter to copy the next part... (p: previous, 1-2: part number, r: copy again, q: quit)"
+        );
+
+        assert!(navigator_prompt(&items, 1, 2).starts_with("Press Enter to copy the next chunk..."));
+        assert!(navigator_prompt(&items, 2, 2).starts_with("Press Enter to finish..."));
+    }
+
+    #[test]
+    fn test_format_part_sizes() {
+        let items = vec![
+            item("abc", 1, 1, 1),
+            item("de", 2, 1, 2),
+            item("fgh", 2, 2, 2),
+        ];
+
+        assert_eq!(
+            format_part_sizes(&items),
+            "Parts:\n  1. 3 B\n  2. 5 B (2 chunks)"
+        );
+    }
+
+    #[test]
+    fn test_output_saved_part_to_clipboard_errors() {
+        let td = TempDir::new().unwrap();
+
+        let result = output_saved_part_to_clipboard(1, ClipboardBackend::Osc52, Some(td.path()));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "no saved parts, the parts are saved only with --cache: copy the output with --clipboard --cache first"
+        );
+
+        let session = ClipboardSession {
+            parts: vec!["One".to_string(), "Two".to_string()],
+        };
+
+        session.save(td.path()).unwrap();
+
+        let result = output_saved_part_to_clipboard(3, ClipboardBackend::Osc52, Some(td.path()));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "there is no part 3, the saved output has 2 part(s)"
+        );
+    }
+
+    #[test]
+    fn test_item_name() {
+        assert_eq!(item_name(&item("", 1, 1, 1), 2), "Part 1 of 2");
+        assert_eq!(
+            item_name(&item("", 2, 1, 3), 2),
+            "Part 2 of 2, chunk 1 of 3"
+        );
+        assert_eq!(item_name(&item("", 1, 2, 3), 1), "Output chunk 2 of 3");
+    }
+}
@@ -0,0 +1,204 @@
+use super::part_writer::{PartWriter, TrimmedWriter};
+use crate::error::QuaggaError;
+use std::io::{self, ErrorKind, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+
+/// Runs the command once for each part of the prompt, with the part on stdin.
+///
+/// # Arguments
+///
+/// * `content` - An output prompt text, splitted into parts.
+/// * `command` - The shell command, e.g. `llm -s 'Review the code'`.
+pub fn output_to_exec(content: Vec<String>, command: &str) -> Result<(), QuaggaError> {
+    let mut writer = ExecPartWriter::new(command);
+    let total = content.len();
+
+    for (index, part) in content.iter().enumerate() {
+        writer.begin_part(index + 1, total)?;
+        writer.write(part)?;
+        writer.end_part()?;
+    }
+
+    writer.finish()
+}
+
+/// Writes each part to the stdin of a new process running the shell command, as the parts
+/// are generated. The number of the part and the total number of parts are passed to
+/// the command in the `QUAGGA_PART` and `QUAGGA_TOTAL_PARTS` environment variables.
+/// Stops with an error if the command exits with a non-zero status.
+pub struct ExecPartWriter {
+    command: String,
+    current: Option<RunningPart>,
+}
+
+/// The process receiving the current part.
+struct RunningPart {
+    number: usize,
+    child: Child,
+
+    /// The stdin of the process, `None` once the process has stopped reading it.
+    stdin: Option<TrimmedWriter<ChildStdin>>,
+}
+
+impl ExecPartWriter {
+    /// Creates a writer that runs the shell `command` for each part.
+    pub fn new(command: &str) -> Self {
+        ExecPartWriter {
+            command: command.to_string(),
+            current: None,
+        }
+    }
+
+    fn error(&self, number: usize, message: String) -> QuaggaError {
+        QuaggaError::Exec {
+            command: self.command.clone(),
+            part: number,
+            message,
+        }
+    }
+}
+
+impl PartWriter for ExecPartWriter {
+    type Error = QuaggaError;
+
+    fn begin_part(&mut self, number: usize, total: usize) -> Result<(), QuaggaError> {
+        let mut child = shell_command(&self.command)
+            .env("QUAGGA_PART", number.to_string())
+            .env("QUAGGA_TOTAL_PARTS", total.to_string())
+            .stdin(Stdio::piped())
+            .spawn()
+            .map_err(|e| self.error(number, e.to_string()))?;
+
+        let stdin = child.stdin.take().map(TrimmedWriter::new);
+
+        self.current = Some(RunningPart {
+            number,
+            child,
+            stdin,
+        });
+
+        Ok(())
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), QuaggaError> {
+        let Some(part) = &mut self.current else {
+            return Ok(());
+        };
+
+        let Some(stdin) = &mut part.stdin else {
+            return Ok(());
+        };
+
+        match stdin.write(text) {
+            Ok(()) => Ok(()),
+            // The command does not read the rest of the part, its exit status is checked in `end_part`
+            Err(e) if e.kind() == ErrorKind::BrokenPipe => {
+                part.stdin = None;
+                Ok(())
+            }
+            Err(e) => {
+                let number = part.number;
+                Err(self.error(number, e.to_string()))
+            }
+        }
+    }
+
+    fn end_part(&mut self) -> Result<(), QuaggaError> {
+        let Some(mut part) = self.current.take() else {
+            return Ok(());
+        };
+
+        // Closing stdin lets the command know the part has ended
+        if let Some(stdin) = part.stdin.take() {
+            close_stdin(stdin.into_inner()).map_err(|e| self.error(part.number, e.to_string()))?;
+        }
+
+        let status = part
+            .child
+            .wait()
+            .map_err(|e| self.error(part.number, e.to_string()))?;
+
+        if !status.success() {
+            return Err(self.error(part.number, status.to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// Flushes and closes the stdin of the process, ignoring the error if the process
+/// has already stopped reading it.
+fn close_stdin(mut stdin: ChildStdin) -> io::Result<()> {
+    match stdin.flush() {
+        Err(e) if e.kind() != ErrorKind::BrokenPipe => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Creates the command that runs the `command` text in the shell.
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// Creates the command that runs the `command` text in the shell.
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+    use std::fs;
+
+    #[test]
+    fn test_output_to_exec() {
+        let td = TempDir::new().unwrap();
+        let command = format!(
+            "cat > {}/part-$QUAGGA_PART-of-$QUAGGA_TOTAL_PARTS.txt",
+            td.path().display()
+        );
+
+        let content = vec!["Part one\n\n".to_string(), "\nPart two".to_string()];
+        output_to_exec(content, &command).unwrap();
+
+        let part1 = fs::read_to_string(td.path().join("part-1-of-2.txt")).unwrap();
+        let part2 = fs::read_to_string(td.path().join("part-2-of-2.txt")).unwrap();
+
+        assert_eq!(part1, "Part one");
+        assert_eq!(part2, "Part two");
+    }
+
+    #[test]
+    fn test_output_to_exec_stops_on_failure() {
+        let td = TempDir::new().unwrap();
+        let command = format!(
+            "cat > {}/part-$QUAGGA_PART.txt; exit 3",
+            td.path().display()
+        );
+
+        let content = vec!["One".to_string(), "Two".to_string()];
+        let result = output_to_exec(content, &command);
+
+        assert!(matches!(
+            result,
+            Err(QuaggaError::Exec { part: 1, message, .. }) if message == "exit status: 3"
+        ));
+
+        assert!(td.path().join("part-1.txt").exists());
+        assert!(!td.path().join("part-2.txt").exists());
+    }
+
+    #[test]
+    fn test_output_to_exec_command_not_reading_stdin() {
+        let content = vec!["A".repeat(1_000_000)];
+
+        assert!(output_to_exec(content, "true").is_ok());
+    }
+}
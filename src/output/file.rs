@@ -1,8 +1,11 @@
+use super::part_writer::{PartWriter, TrimmedWriter};
+use crate::error::QuaggaError;
 use chrono::{DateTime, Local, Utc};
+use sha2::{Digest, Sha256};
 use std::error::Error;
 use std::fs::{self, File};
-use std::io::{self, Write};
-use std::path::PathBuf;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 /// Writes the provided content to the specified output path. If the content has multiple parts,
 /// they are written to separate files that are suffixed with `.XXX` (e.g. file.txt.001, file.txt.002).
@@ -13,7 +16,7 @@ use std::path::PathBuf;
 /// * `path` - The base output path. Can contain `{TIME}` and `{TIME_UTC}` tags that will be replaced with current timestamp in the format `YYYY-mm-DD_HH-MM-SS`.
 /// * `combine_parts` - When true, forces all parts to be combined in a single file.
 /// * `fixed_time` - An optional timestamp to use instead of current time.
-///                  Used to replace the {TIME} and {TIME_UTC} tags in path with current time.
+///   Used to replace the {TIME} and {TIME_UTC} tags in path with current time.
 ///
 /// # Returns
 ///
@@ -25,8 +28,7 @@ pub fn output_to_file(
     combine_parts: bool,
     fixed_time: Option<DateTime<Local>>,
 ) -> Result<(), io::Error> {
-    let path = replace_time_tags(&path, fixed_time)
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+    let path = replace_time_tags(&path, fixed_time).map_err(io::Error::other)?;
 
     if content.is_empty() {
         return Ok(());
@@ -50,7 +52,7 @@ pub fn output_to_file(
 ///
 /// * `Ok(())` if writing succeeds.
 /// * `Err(io::Error)` if writing fails.
-fn combine_and_write(content: Vec<String>, path: &PathBuf) -> Result<(), io::Error> {
+fn combine_and_write(content: Vec<String>, path: &Path) -> Result<(), io::Error> {
     let combined_content = content.join("\n");
     create_parent_dir(path)?;
     let mut file = File::create(path)?;
@@ -69,7 +71,7 @@ fn combine_and_write(content: Vec<String>, path: &PathBuf) -> Result<(), io::Err
 ///
 /// * `Ok(())` if all files are written successfully.
 /// * `Err(io::Error)` if any file operation fails.
-fn write_parts_separately(content: Vec<String>, base_path: &PathBuf) -> Result<(), io::Error> {
+fn write_parts_separately(content: Vec<String>, base_path: &Path) -> Result<(), io::Error> {
     create_parent_dir(base_path)?;
 
     for (index, part) in content.iter().enumerate() {
@@ -82,8 +84,156 @@ fn write_parts_separately(content: Vec<String>, base_path: &PathBuf) -> Result<(
     Ok(())
 }
 
+/// Writes the parts to files as they are generated. A single part is written to `path`,
+/// multiple parts are written to separate files suffixed with `.XXX` (e.g. file.txt.001, file.txt.002),
+/// same as `output_to_file`.
+pub struct FilePartWriter {
+    path: PathBuf,
+    current: Option<(PathBuf, TrimmedWriter<HashingWriter<BufWriter<File>>>)>,
+    written: Vec<WrittenPart>,
+}
+
+/// A part written to a file by `FilePartWriter`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrittenPart {
+    pub path: PathBuf,
+
+    /// The number of characters in the file.
+    pub chars: usize,
+
+    /// The SHA-256 hash of the file as a hex string.
+    pub sha256: String,
+}
+
+impl FilePartWriter {
+    /// Creates a writer for the base output path.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The base output path. Can contain `{TIME}` and `{TIME_UTC}` tags.
+    /// * `fixed_time` - An optional timestamp to use instead of current time.
+    pub fn new(path: &Path, fixed_time: Option<DateTime<Local>>) -> Result<Self, QuaggaError> {
+        let path = replace_time_tags(path, fixed_time).map_err(|e| QuaggaError::Output {
+            path: path.to_path_buf(),
+            source: io::Error::other(e),
+        })?;
+
+        Ok(FilePartWriter {
+            path,
+            current: None,
+            written: Vec::new(),
+        })
+    }
+
+    /// Returns the base output path, with the `{TIME}` and `{TIME_UTC}` tags replaced.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the parts written so far.
+    pub fn written_parts(&self) -> &[WrittenPart] {
+        &self.written
+    }
+}
+
+impl PartWriter for FilePartWriter {
+    type Error = QuaggaError;
+
+    fn begin_part(&mut self, number: usize, total: usize) -> Result<(), QuaggaError> {
+        let path = if total == 1 {
+            self.path.clone()
+        } else {
+            PathBuf::from(format!("{}.{:03}", self.path.display(), number))
+        };
+
+        let file = create_parent_dir(&path)
+            .and_then(|_| File::create(&path))
+            .map_err(|source| output_error(&path, source))?;
+
+        let writer = HashingWriter::new(BufWriter::new(file));
+        self.current = Some((path, TrimmedWriter::new(writer)));
+        Ok(())
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), QuaggaError> {
+        if let Some((path, writer)) = &mut self.current {
+            writer
+                .write(text)
+                .map_err(|source| output_error(path, source))?;
+        }
+
+        Ok(())
+    }
+
+    fn end_part(&mut self) -> Result<(), QuaggaError> {
+        if let Some((path, writer)) = self.current.take() {
+            let mut writer = writer.into_inner();
+            writer
+                .flush()
+                .map_err(|source| output_error(&path, source))?;
+
+            self.written.push(WrittenPart {
+                path,
+                chars: writer.chars,
+                sha256: writer.hex_digest(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Passes the bytes to the inner writer, counting the characters and hashing the bytes.
+struct HashingWriter<W: Write> {
+    inner: W,
+    hasher: Sha256,
+    chars: usize,
+}
+
+impl<W: Write> HashingWriter<W> {
+    fn new(inner: W) -> Self {
+        HashingWriter {
+            inner,
+            hasher: Sha256::new(),
+            chars: 0,
+        }
+    }
+
+    /// Returns the SHA-256 hash of the bytes written so far as a hex string.
+    fn hex_digest(self) -> String {
+        self.hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        let bytes = &buf[..written];
+        self.hasher.update(bytes);
+
+        // The text is valid UTF-8, so each character has exactly one byte that is not a continuation byte
+        self.chars += bytes.iter().filter(|&&byte| byte & 0xC0 != 0x80).count();
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn output_error(path: &Path, source: io::Error) -> QuaggaError {
+    QuaggaError::Output {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
 /// Creates the parent directories of the specified path.
-fn create_parent_dir(path: &PathBuf) -> Result<(), io::Error> {
+fn create_parent_dir(path: &Path) -> Result<(), io::Error> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
@@ -106,7 +256,7 @@ fn create_parent_dir(path: &PathBuf) -> Result<(), io::Error> {
 /// * `Ok(PathBuf)` with tags replaced.
 /// * `Err(Box<dyn Error>)` if any error occurs.
 pub fn replace_time_tags(
-    path: &PathBuf,
+    path: &Path,
     fixed_time: Option<DateTime<Local>>,
 ) -> Result<PathBuf, Box<dyn Error + Send + Sync>> {
     let path_str = path.to_string_lossy();
@@ -309,6 +459,71 @@ mod tests {
         assert_eq!(part_content, "Part 3");
     }
 
+    fn write_with_file_part_writer(base_path: &Path, parts: &[&str]) -> Vec<WrittenPart> {
+        let mut writer = FilePartWriter::new(base_path, None).unwrap();
+
+        for (index, part) in parts.iter().enumerate() {
+            writer.begin_part(index + 1, parts.len()).unwrap();
+            writer.write(part).unwrap();
+            writer.end_part().unwrap();
+        }
+
+        writer.finish().unwrap();
+        writer.written_parts().to_vec()
+    }
+
+    #[test]
+    fn test_file_part_writer_one_part() {
+        let td = TempDir::new().unwrap();
+        let base_path = td.path().join("dir/output.txt");
+
+        write_with_file_part_writer(&base_path, &["\nContent\n"]);
+
+        let part_content = fs::read_to_string(&base_path).unwrap();
+        assert_eq!(part_content, "Content");
+    }
+
+    #[test]
+    fn test_file_part_writer_multiple_parts() {
+        let td = TempDir::new().unwrap();
+        let base_path = td.path().join("dir/output.txt");
+
+        let written = write_with_file_part_writer(&base_path, &["Part 1\n", "Part 2\n"]);
+
+        assert!(!base_path.exists());
+        assert_eq!(written.len(), 2);
+        assert_eq!(
+            written[1].path,
+            PathBuf::from(format!("{}.002", base_path.display()))
+        );
+
+        let expected_path = PathBuf::from(format!("{}.001", base_path.display()));
+        let part_content = fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(part_content, "Part 1");
+
+        let expected_path = PathBuf::from(format!("{}.002", base_path.display()));
+        let part_content = fs::read_to_string(&expected_path).unwrap();
+        assert_eq!(part_content, "Part 2");
+    }
+
+    #[test]
+    fn test_file_part_writer_written_parts() {
+        let td = TempDir::new().unwrap();
+        let base_path = td.path().join("output.txt");
+
+        let written = write_with_file_part_writer(&base_path, &["\n héllo \n"]);
+
+        assert_eq!(
+            written,
+            vec![WrittenPart {
+                path: base_path.clone(),
+                chars: 5,
+                sha256: "3c48591d8d098a4538f5e013dfcf406e948eac4d3277b10bf614e295d6068179"
+                    .to_string(),
+            }]
+        );
+    }
+
     /// Helper function to create a fixed DateTime<Local> from a timestamp.
     fn create_fixed_datetime(timestamp: i64) -> DateTime<Local> {
         let fixed_utc: DateTime<Utc> = match Utc.timestamp_opt(timestamp, 0) {
@@ -0,0 +1,392 @@
+use crate::error::QuaggaError;
+use crate::options::QuaggaOptions;
+use serde_json::{json, Value};
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+
+/// The path of the chat completions endpoint of OpenAI-compatible servers.
+const CHAT_COMPLETIONS_PATH: &str = "/v1/chat/completions";
+
+/// An OpenAI-compatible chat completions endpoint the prompt is sent to with `--send`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChatEndpoint {
+    /// The URL of the chat completions endpoint.
+    pub url: String,
+
+    /// The name of the model, or `None` to let the server choose it.
+    pub model: Option<String>,
+
+    /// The key sent in the `Authorization` header, or `None` to send no key.
+    pub api_key: Option<String>,
+
+    /// The system message sent before the parts of the prompt.
+    pub system: Option<String>,
+}
+
+impl ChatEndpoint {
+    /// Creates the endpoint from the `--send`, `--model`, `--api-key-env` and `--system` options.
+    ///
+    /// # Returns
+    ///
+    /// `Ok(None)` if the prompt is not sent, or an error if the environment variable
+    /// with the API key is not set.
+    pub fn from_options(options: &QuaggaOptions) -> Result<Option<Self>, QuaggaError> {
+        let Some(url) = &options.send else {
+            return Ok(None);
+        };
+
+        let url = chat_completions_url(url);
+
+        let api_key = match &options.api_key_env {
+            Some(name) => Some(env::var(name).map_err(|_| QuaggaError::Send {
+                url: url.clone(),
+                message: format!("environment variable {} is not set", name),
+            })?),
+            None => None,
+        };
+
+        Ok(Some(ChatEndpoint {
+            url,
+            model: options.model.clone(),
+            api_key,
+            system: options.system.clone(),
+        }))
+    }
+}
+
+/// Sends the prompt to the chat completions endpoint, each part as a separate user message,
+/// and writes the reply to the `writer` as it is streamed by the server.
+///
+/// # Arguments
+///
+/// * `content` - An output prompt text, splitted into parts.
+/// * `endpoint` - The endpoint the prompt is sent to.
+/// * `writer` - Receives the reply.
+///
+/// # Returns
+///
+/// `Ok(())` if the whole reply was received, or a `QuaggaError::Send` if the request failed,
+/// the server returned an error or the reply could not be written.
+pub fn output_to_http<W: Write>(
+    content: Vec<String>,
+    endpoint: &ChatEndpoint,
+    writer: &mut W,
+) -> Result<(), QuaggaError> {
+    let error = |message: String| QuaggaError::Send {
+        url: endpoint.url.clone(),
+        message,
+    };
+
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .http_status_as_error(false)
+        .build()
+        .into();
+
+    let mut request = agent
+        .post(&endpoint.url)
+        .header("Content-Type", "application/json")
+        .header("Accept", "text/event-stream");
+
+    if let Some(api_key) = &endpoint.api_key {
+        request = request.header("Authorization", &format!("Bearer {}", api_key));
+    }
+
+    let body = request_body(&content, endpoint).to_string();
+    let response = request.send(&body).map_err(|e| error(e.to_string()))?;
+    let status = response.status();
+
+    let is_stream = response
+        .headers()
+        .get("content-type")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/event-stream"));
+
+    let mut reader = BufReader::new(response.into_body().into_reader());
+
+    if !status.is_success() {
+        let mut text = String::new();
+        let _ = reader.read_to_string(&mut text);
+        let message = serde_json::from_str(&text)
+            .ok()
+            .as_ref()
+            .and_then(error_message)
+            .unwrap_or(text);
+        return Err(error(format!("{} {}", status, message.trim())));
+    }
+
+    if is_stream {
+        stream_reply(reader, writer).map_err(error)
+    } else {
+        write_reply(reader, writer).map_err(error)
+    }
+}
+
+/// Returns the URL of the chat completions endpoint. The `url` can be the full URL of the
+/// endpoint, the URL of the server (e.g. `http://localhost:8080`), or the URL of
+/// the API (e.g. `https://api.openai.com/v1`).
+pub fn chat_completions_url(url: &str) -> String {
+    let url = url.trim_end_matches('/');
+
+    if url.ends_with("/chat/completions") {
+        url.to_string()
+    } else if url.ends_with("/v1") {
+        format!("{}/chat/completions", url)
+    } else {
+        format!("{}{}", url, CHAT_COMPLETIONS_PATH)
+    }
+}
+
+/// Creates the JSON body of the request, with the system message followed by
+/// the parts of the prompt.
+fn request_body(content: &[String], endpoint: &ChatEndpoint) -> Value {
+    let mut messages = Vec::new();
+
+    if let Some(system) = &endpoint.system {
+        messages.push(json!({ "role": "system", "content": system }));
+    }
+
+    for part in content {
+        messages.push(json!({ "role": "user", "content": part.trim() }));
+    }
+
+    let mut body = json!({ "messages": messages, "stream": true });
+
+    if let Some(model) = &endpoint.model {
+        body["model"] = json!(model);
+    }
+
+    body
+}
+
+/// Writes the text of the reply streamed as server-sent events, one `data:` line per chunk,
+/// until the `[DONE]` line or the end of the stream.
+fn stream_reply<R: BufRead, W: Write>(reader: R, writer: &mut W) -> Result<(), String> {
+    let mut ends_with_newline = true;
+
+    for line in reader.lines() {
+        let line = line.map_err(|e| e.to_string())?;
+
+        let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+            continue;
+        };
+
+        if data == "[DONE]" {
+            break;
+        }
+
+        let chunk: Value = serde_json::from_str(data).map_err(|e| e.to_string())?;
+
+        if let Some(message) = error_message(&chunk) {
+            return Err(message);
+        }
+
+        let text = chunk["choices"][0]["delta"]["content"]
+            .as_str()
+            .unwrap_or("");
+
+        if text.is_empty() {
+            continue;
+        }
+
+        writer
+            .write_all(text.as_bytes())
+            .map_err(|e| e.to_string())?;
+        writer.flush().map_err(|e| e.to_string())?;
+        ends_with_newline = text.ends_with('\n');
+    }
+
+    finish_reply(writer, ends_with_newline)
+}
+
+/// Writes the text of a reply that was not streamed, for servers that ignore `"stream": true`.
+fn write_reply<R: Read, W: Write>(mut reader: R, writer: &mut W) -> Result<(), String> {
+    let mut body = String::new();
+    reader
+        .read_to_string(&mut body)
+        .map_err(|e| e.to_string())?;
+
+    let reply: Value = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    let text = reply["choices"][0]["message"]["content"]
+        .as_str()
+        .ok_or("the reply has no message")?;
+
+    writer
+        .write_all(text.as_bytes())
+        .map_err(|e| e.to_string())?;
+    finish_reply(writer, text.ends_with('\n'))
+}
+
+/// Ends the reply with a newline, so that the shell prompt starts on its own line.
+fn finish_reply<W: Write>(writer: &mut W, ends_with_newline: bool) -> Result<(), String> {
+    if !ends_with_newline {
+        writer.write_all(b"\n").map_err(|e| e.to_string())?;
+    }
+
+    writer.flush().map_err(|e| e.to_string())
+}
+
+/// Returns the message of an error returned by the server,
+/// e.g. `{"error": {"message": "Invalid API key"}}`.
+fn error_message(value: &Value) -> Option<String> {
+    let error = value.get("error")?;
+
+    error
+        .get("message")
+        .and_then(Value::as_str)
+        .or_else(|| error.as_str())
+        .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint() -> ChatEndpoint {
+        ChatEndpoint {
+            url: "http://localhost:8080/v1/chat/completions".to_string(),
+            model: None,
+            api_key: None,
+            system: None,
+        }
+    }
+
+    #[test]
+    fn test_chat_completions_url() {
+        assert_eq!(
+            chat_completions_url("http://localhost:8080"),
+            "http://localhost:8080/v1/chat/completions"
+        );
+
+        assert_eq!(
+            chat_completions_url("http://localhost:11434/"),
+            "http://localhost:11434/v1/chat/completions"
+        );
+
+        assert_eq!(
+            chat_completions_url("https://api.openai.com/v1"),
+            "https://api.openai.com/v1/chat/completions"
+        );
+
+        assert_eq!(
+            chat_completions_url("https://example.com/api/v1/chat/completions"),
+            "https://example.com/api/v1/chat/completions"
+        );
+    }
+
+    #[test]
+    fn test_request_body() {
+        let endpoint = ChatEndpoint {
+            model: Some("llama3".to_string()),
+            system: Some("You are a code reviewer.".to_string()),
+            ..endpoint()
+        };
+
+        let content = vec!["Part 1\n".to_string(), "Part 2\n".to_string()];
+
+        assert_eq!(
+            request_body(&content, &endpoint),
+            json!({
+                "model": "llama3",
+                "stream": true,
+                "messages": [
+                    { "role": "system", "content": "You are a code reviewer." },
+                    { "role": "user", "content": "Part 1" },
+                    { "role": "user", "content": "Part 2" }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_request_body_without_model_and_system() {
+        let body = request_body(&["Hello".to_string()], &endpoint());
+
+        assert_eq!(
+            body,
+            json!({
+                "stream": true,
+                "messages": [{ "role": "user", "content": "Hello" }]
+            })
+        );
+    }
+
+    #[test]
+    fn test_stream_reply() {
+        let stream = "\
+data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}
+
+: keep-alive
+data: {\"choices\":[{\"delta\":{\"content\":\"Looks \"}}]}
+
+data: {\"choices\":[{\"delta\":{\"content\":\"good.\"}}]}
+
+data: [DONE]
+";
+
+        let mut output = Vec::new();
+        stream_reply(stream.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "Looks good.\n");
+    }
+
+    #[test]
+    fn test_stream_reply_error() {
+        let stream = "data: {\"error\":{\"message\":\"model not found\"}}\n";
+
+        let result = stream_reply(stream.as_bytes(), &mut Vec::new());
+
+        assert_eq!(result, Err("model not found".to_string()));
+    }
+
+    #[test]
+    fn test_write_reply() {
+        let body = r#"{"choices":[{"message":{"role":"assistant","content":"Hi!\n"}}]}"#;
+
+        let mut output = Vec::new();
+        write_reply(body.as_bytes(), &mut output).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "Hi!\n");
+    }
+
+    #[test]
+    fn test_from_options() {
+        let options = QuaggaOptions {
+            send: Some("http://localhost:8080".to_string()),
+            model: Some("llama3".to_string()),
+            system: Some("Be brief.".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            ChatEndpoint::from_options(&options).unwrap(),
+            Some(ChatEndpoint {
+                url: "http://localhost:8080/v1/chat/completions".to_string(),
+                model: Some("llama3".to_string()),
+                api_key: None,
+                system: Some("Be brief.".to_string()),
+            })
+        );
+
+        assert_eq!(
+            ChatEndpoint::from_options(&QuaggaOptions::default()).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_from_options_missing_api_key() {
+        let options = QuaggaOptions {
+            send: Some("http://localhost:8080".to_string()),
+            api_key_env: Some("QUAGGA_TEST_MISSING_API_KEY".to_string()),
+            ..Default::default()
+        };
+
+        let result = ChatEndpoint::from_options(&options);
+
+        assert!(matches!(
+            result,
+            Err(QuaggaError::Send { message, .. })
+                if message == "environment variable QUAGGA_TEST_MISSING_API_KEY is not set"
+        ));
+    }
+}
@@ -0,0 +1,289 @@
+use super::file::WrittenPart;
+use crate::error::QuaggaError;
+use crate::file::path_style::PathFormatter;
+use crate::options::QuaggaOptions;
+use crate::template::stream::PartFile;
+use crate::template::template::Template;
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The average number of characters in a token, used to estimate the number of tokens.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Describes the prompt written to files with `--output`, so that it can be reproduced
+/// or audited: the parts with their sizes and hashes, the files in each part,
+/// the template and the command line.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Manifest {
+    pub quagga_version: String,
+
+    /// The command line arguments quagga was run with.
+    pub command: Vec<String>,
+
+    pub template: ManifestTemplate,
+    pub parts: Vec<ManifestPart>,
+}
+
+/// The template used to generate the prompt.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ManifestTemplate {
+    /// The path to the custom template, `None` if the built-in template is used.
+    pub path: Option<PathBuf>,
+
+    #[serde(flatten)]
+    pub sections: Template,
+}
+
+/// A part of the prompt written to a file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ManifestPart {
+    /// The number of the part, starting from 1.
+    pub number: usize,
+
+    /// The name of the file the part was written to.
+    pub file: String,
+
+    pub chars: usize,
+
+    /// A rough estimate of the number of tokens, assuming four characters per token.
+    pub estimated_tokens: usize,
+
+    /// The SHA-256 hash of the part as a hex string.
+    pub sha256: String,
+
+    pub files: Vec<ManifestFile>,
+}
+
+/// A file, or a range of lines of a file, in a part.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ManifestFile {
+    /// The path to the file, shown in the same style as in the prompt.
+    pub path: String,
+
+    /// The first and the last line of the file in the part, if the file is split between parts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<[usize; 2]>,
+}
+
+impl Manifest {
+    /// Creates the manifest of the written prompt.
+    ///
+    /// # Arguments
+    ///
+    /// * `written` - The parts written to the files.
+    /// * `part_files` - The files in each part.
+    /// * `template` - The template used to generate the prompt.
+    /// * `template_path` - The path to the custom template, `None` for the built-in template.
+    /// * `options` - Options used to generate the prompt.
+    /// * `command` - The command line arguments.
+    pub fn new(
+        written: &[WrittenPart],
+        part_files: &[Vec<PartFile>],
+        template: &Template,
+        template_path: Option<PathBuf>,
+        options: &QuaggaOptions,
+        command: Vec<String>,
+    ) -> Self {
+        let formatter = PathFormatter::from_options(options);
+
+        let parts = written
+            .iter()
+            .zip(part_files)
+            .enumerate()
+            .map(|(index, (part, files))| ManifestPart {
+                number: index + 1,
+                file: part
+                    .path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default(),
+                chars: part.chars,
+                estimated_tokens: part.chars.div_ceil(CHARS_PER_TOKEN),
+                sha256: part.sha256.clone(),
+                files: files
+                    .iter()
+                    .map(|file| ManifestFile {
+                        path: formatter.display(&file.path),
+                        lines: file.lines.map(|lines| [lines.start, lines.end]),
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Manifest {
+            quagga_version: env!("CARGO_PKG_VERSION").to_string(),
+            command,
+            template: ManifestTemplate {
+                path: template_path,
+                sections: template.clone(),
+            },
+            parts,
+        }
+    }
+
+    /// Writes the manifest as JSON to `<output_path>.manifest.json`.
+    ///
+    /// # Arguments
+    ///
+    /// * `output_path` - The base output path the parts were written to.
+    ///
+    /// # Returns
+    ///
+    /// The path to the manifest, or `QuaggaError::Output` if it can not be written.
+    pub fn write(&self, output_path: &Path) -> Result<PathBuf, QuaggaError> {
+        let path = manifest_path(output_path);
+
+        serde_json::to_string_pretty(self)
+            .map_err(std::io::Error::other)
+            .and_then(|json| fs::write(&path, json + "\n"))
+            .map_err(|source| QuaggaError::Output {
+                path: path.clone(),
+                source,
+            })?;
+
+        Ok(path)
+    }
+}
+
+/// Returns the path to the manifest of the prompt written to `output_path`.
+pub fn manifest_path(output_path: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.manifest.json", output_path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::stream::LineRange;
+    use crate::template::template::PromptTemplate;
+    use crate::test_utils::temp_dir::TempDir;
+    use serde_json::{json, Value};
+
+    fn written_part(path: &Path, chars: usize) -> WrittenPart {
+        WrittenPart {
+            path: path.to_path_buf(),
+            chars,
+            sha256: "abc".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_write_manifest() {
+        let td = TempDir::new().unwrap();
+        let output_path = td.path().join("prompt.txt");
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
+
+        let template = Template {
+            prompt: PromptTemplate {
+                header: "Header".to_string(),
+                file: "<file-content>".to_string(),
+                footer: "Footer".to_string(),
+            },
+            part: Default::default(),
+        };
+
+        let written = vec![
+            written_part(&td.path().join("prompt.txt.001"), 10),
+            written_part(&td.path().join("prompt.txt.002"), 3),
+        ];
+
+        let part_files = vec![
+            vec![
+                PartFile {
+                    path: td.path().join("a.txt"),
+                    lines: None,
+                },
+                PartFile {
+                    path: td.path().join("src/b.txt"),
+                    lines: Some(LineRange { start: 1, end: 20 }),
+                },
+            ],
+            vec![PartFile {
+                path: td.path().join("src/b.txt"),
+                lines: Some(LineRange { start: 21, end: 30 }),
+            }],
+        ];
+
+        let manifest = Manifest::new(
+            &written,
+            &part_files,
+            &template,
+            None,
+            &options,
+            vec!["quagga".to_string(), "--manifest".to_string()],
+        );
+
+        let path = manifest.write(&output_path).unwrap();
+        assert_eq!(path, td.path().join("prompt.txt.manifest.json"));
+
+        let json: Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+
+        assert_eq!(
+            json,
+            json!({
+                "quagga_version": env!("CARGO_PKG_VERSION"),
+                "command": ["quagga", "--manifest"],
+                "template": {
+                    "path": null,
+                    "prompt": {
+                        "header": "Header",
+                        "file": "<file-content>",
+                        "footer": "Footer"
+                    },
+                    "part": {
+                        "header": "",
+                        "footer": "",
+                        "pending": ""
+                    }
+                },
+                "parts": [
+                    {
+                        "number": 1,
+                        "file": "prompt.txt.001",
+                        "chars": 10,
+                        "estimated_tokens": 3,
+                        "sha256": "abc",
+                        "files": [
+                            { "path": "a.txt" },
+                            { "path": "src/b.txt", "lines": [1, 20] }
+                        ]
+                    },
+                    {
+                        "number": 2,
+                        "file": "prompt.txt.002",
+                        "chars": 3,
+                        "estimated_tokens": 1,
+                        "sha256": "abc",
+                        "files": [
+                            { "path": "src/b.txt", "lines": [21, 30] }
+                        ]
+                    }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_write_manifest_error() {
+        let td = TempDir::new().unwrap();
+        let output_path = td.path().join("missing/prompt.txt");
+
+        let manifest = Manifest::new(
+            &[],
+            &[],
+            &Template::default(),
+            None,
+            &QuaggaOptions::default(),
+            Vec::new(),
+        );
+
+        assert!(matches!(
+            manifest.write(&output_path),
+            Err(QuaggaError::Output { .. })
+        ));
+    }
+}
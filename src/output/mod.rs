@@ -1,4 +1,11 @@
 pub mod clipboard;
+pub mod exec;
 pub mod file;
+pub mod http;
+pub mod manifest;
+pub mod osc52;
+#[allow(clippy::module_inception)]
 pub mod output;
+pub mod part_writer;
+pub mod session;
 pub mod stdout;
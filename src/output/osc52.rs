@@ -0,0 +1,213 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+/// The maximum number of bytes of text copied with a single OSC 52 sequence.
+/// The base64-encoded sequence is then under 100,000 bytes, which is the limit
+/// of some terminals, e.g. hterm.
+pub const OSC52_MAX_TEXT_BYTES: usize = 74_994;
+
+/// The controlling terminal of the process.
+const TERMINAL_PATH: &str = "/dev/tty";
+
+/// The maximum length of a DCS string accepted by GNU screen.
+const SCREEN_MAX_CHUNK: usize = 76;
+
+/// The terminal multiplexer the sequence is passed through to the outer terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    None,
+    Tmux,
+    Screen,
+}
+
+impl Multiplexer {
+    /// Detects the multiplexer from the `TMUX` and `TERM` environment variables.
+    pub fn detect() -> Self {
+        if env::var_os("TMUX").is_some() {
+            return Multiplexer::Tmux;
+        }
+
+        match env::var("TERM") {
+            Ok(term) if term.starts_with("screen") => Multiplexer::Screen,
+            _ => Multiplexer::None,
+        }
+    }
+}
+
+/// Copies text to the clipboard of the terminal by writing OSC 52 escape sequences.
+/// This works in terminals connected over SSH and on hosts without a display server.
+pub struct Osc52Clipboard<W: Write> {
+    out: W,
+    multiplexer: Multiplexer,
+}
+
+impl<W: Write> Osc52Clipboard<W> {
+    pub fn new(out: W, multiplexer: Multiplexer) -> Self {
+        Osc52Clipboard { out, multiplexer }
+    }
+
+    /// Replaces the contents of the clipboard with the text.
+    pub fn set_text(&mut self, text: &str) -> io::Result<()> {
+        self.out
+            .write_all(osc52_sequence(text, self.multiplexer).as_bytes())?;
+        self.out.flush()
+    }
+}
+
+impl Osc52Clipboard<File> {
+    /// Creates the clipboard writing to the controlling terminal of the process.
+    ///
+    /// # Returns
+    ///
+    /// The clipboard, or an error if there is no terminal, e.g. when quagga runs from cron.
+    /// The sequences are not written to stdout instead, since it can be piped to another program.
+    pub fn to_terminal() -> io::Result<Self> {
+        Self::open(Path::new(TERMINAL_PATH))
+    }
+
+    /// Creates the clipboard writing to the terminal device at the `path`.
+    fn open(path: &Path) -> io::Result<Self> {
+        let tty = OpenOptions::new().write(true).open(path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("can not open the terminal to copy with OSC 52: {}", e),
+            )
+        })?;
+
+        Ok(Osc52Clipboard::new(tty, Multiplexer::detect()))
+    }
+}
+
+/// Creates the escape sequence that copies the text to the clipboard, wrapped for
+/// the multiplexer so that it reaches the outer terminal.
+///
+/// # Arguments
+///
+/// * `text` - The text to copy.
+/// * `multiplexer` - The terminal multiplexer quagga runs in.
+pub fn osc52_sequence(text: &str, multiplexer: Multiplexer) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+
+    match multiplexer {
+        Multiplexer::None => sequence,
+        Multiplexer::Tmux => format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b")),
+        Multiplexer::Screen => sequence
+            .as_bytes()
+            .chunks(SCREEN_MAX_CHUNK)
+            .map(|chunk| format!("\x1bP{}\x1b\\", String::from_utf8_lossy(chunk)))
+            .collect(),
+    }
+}
+
+/// Splits the text into chunks of at most `max_bytes` bytes, each of which can be copied
+/// with a single sequence. The chunks end at line breaks when possible.
+///
+/// # Arguments
+///
+/// * `text` - The text to split.
+/// * `max_bytes` - The maximum size of a chunk in bytes.
+pub fn split_into_chunks(text: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = text;
+
+    while rest.len() > max_bytes {
+        let mut end = max_bytes;
+
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        if let Some(newline) = rest[..end].rfind('\n') {
+            end = newline + 1;
+        }
+
+        if end == 0 {
+            // A character longer than the limit
+            end = rest.chars().next().map_or(rest.len(), char::len_utf8);
+        }
+
+        chunks.push(&rest[..end]);
+        rest = &rest[end..];
+    }
+
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest);
+    }
+
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequence("Hello", Multiplexer::None),
+            "\x1b]52;c;SGVsbG8=\x07"
+        );
+    }
+
+    #[test]
+    fn test_osc52_sequence_tmux() {
+        assert_eq!(
+            osc52_sequence("Hello", Multiplexer::Tmux),
+            "\x1bPtmux;\x1b\x1b]52;c;SGVsbG8=\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_osc52_sequence_screen() {
+        let text = "A".repeat(100);
+        let result = osc52_sequence(&text, Multiplexer::Screen);
+        let sequence = osc52_sequence(&text, Multiplexer::None);
+
+        assert_eq!(
+            result,
+            format!(
+                "\x1bP{}\x1b\\\x1bP{}\x1b\\",
+                &sequence[..SCREEN_MAX_CHUNK],
+                &sequence[SCREEN_MAX_CHUNK..]
+            )
+        );
+    }
+
+    #[test]
+    fn test_osc52_clipboard_set_text() {
+        let mut clipboard = Osc52Clipboard::new(Vec::new(), Multiplexer::None);
+        clipboard.set_text("Hi").unwrap();
+
+        assert_eq!(clipboard.out, b"\x1b]52;c;SGk=\x07");
+    }
+
+    #[test]
+    fn test_osc52_clipboard_without_terminal() {
+        let result = Osc52Clipboard::open(Path::new("/nonexistent/tty"));
+
+        assert!(matches!(
+            result,
+            Err(e) if e.to_string().starts_with("can not open the terminal to copy with OSC 52")
+        ));
+    }
+
+    #[test]
+    fn test_split_into_chunks() {
+        assert_eq!(split_into_chunks("Hello", 10), vec!["Hello"]);
+        assert_eq!(split_into_chunks("", 10), vec![""]);
+
+        // Split at line breaks
+        assert_eq!(
+            split_into_chunks("one\ntwo\nthree\n", 9),
+            vec!["one\ntwo\n", "three\n"]
+        );
+
+        // Long lines are split at character boundaries
+        assert_eq!(split_into_chunks("abcdefg", 3), vec!["abc", "def", "g"]);
+        assert_eq!(split_into_chunks("ééé", 3), vec!["é", "é", "é"]);
+    }
+}
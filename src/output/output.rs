@@ -1,14 +1,37 @@
 use super::clipboard::output_to_clipboard;
+use super::exec::output_to_exec;
 use super::file::output_to_file;
+use super::http::{output_to_http, ChatEndpoint};
+use super::session::save_session_dir;
 use super::stdout::output_to_stdout;
-use crate::cli::Cli;
-use std::error::Error;
-
-pub fn process_output(content: Vec<String>, cli: &Cli) -> Result<(), Box<dyn Error>> {
-    if cli.clipboard {
-        output_to_clipboard(content)?;
-    } else if let Some(output_path) = &cli.output {
-        output_to_file(content, output_path.clone(), false, None)?;
+use crate::error::QuaggaError;
+use crate::options::QuaggaOptions;
+use std::io;
+
+/// Sends the output prompt to the clipboard, a chat completions endpoint, a command,
+/// a file or stdout, depending on the options.
+///
+/// # Arguments
+///
+/// * `content` - An output prompt text, splitted into parts.
+/// * `options` - Options for generating the prompt.
+pub fn process_output(content: Vec<String>, options: &QuaggaOptions) -> Result<(), QuaggaError> {
+    if options.clipboard {
+        let session_dir = save_session_dir(options);
+
+        output_to_clipboard(content, options.clipboard_backend, session_dir.as_deref())
+            .map_err(|e| QuaggaError::Clipboard(e.to_string()))?;
+    } else if let Some(endpoint) = ChatEndpoint::from_options(options)? {
+        output_to_http(content, &endpoint, &mut io::stdout())?;
+    } else if let Some(command) = &options.exec {
+        output_to_exec(content, command)?;
+    } else if let Some(output_path) = &options.output {
+        output_to_file(content, output_path.clone(), false, None).map_err(|source| {
+            QuaggaError::Output {
+                path: output_path.clone(),
+                source,
+            }
+        })?;
     } else {
         output_to_stdout(content);
     }
@@ -19,9 +42,7 @@ pub fn process_output(content: Vec<String>, cli: &Cli) -> Result<(), Box<dyn Err
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cli::Cli;
     use crate::test_utils::temp_dir::TempDir;
-    use clap::Parser;
     use std::fs;
 
     #[test]
@@ -29,11 +50,13 @@ mod tests {
         let td = TempDir::new().unwrap();
         let output_path = td.path().join("test.txt");
 
-        let mut cli = Cli::parse_from(&["quagga"]);
-        cli.output = Some(output_path.clone());
+        let options = QuaggaOptions {
+            output: Some(output_path.clone()),
+            ..Default::default()
+        };
 
         let content = vec!["Hello, world!".to_string()];
-        let result = process_output(content.clone(), &cli);
+        let result = process_output(content.clone(), &options);
 
         assert!(result.is_ok());
         assert!(output_path.exists());
@@ -0,0 +1,162 @@
+use std::convert::Infallible;
+use std::io::{self, Write};
+
+/// Receives the output prompt piece by piece as the files are read, one part at a time.
+///
+/// The calls come in the order: `begin_part`, any number of `write`, `end_part`
+/// for each part, followed by `finish` once all parts are written.
+pub trait PartWriter {
+    type Error;
+
+    /// Starts a new part.
+    ///
+    /// # Arguments
+    ///
+    /// * `number` - The number of the part, starting from 1.
+    /// * `total` - The total number of parts.
+    fn begin_part(&mut self, number: usize, total: usize) -> Result<(), Self::Error>;
+
+    /// Writes a piece of the current part.
+    fn write(&mut self, text: &str) -> Result<(), Self::Error>;
+
+    /// Finishes the current part.
+    fn end_part(&mut self) -> Result<(), Self::Error>;
+
+    /// Called after all parts have been written.
+    fn finish(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Collects the parts in memory.
+#[derive(Debug, Default)]
+pub struct MemoryPartWriter {
+    parts: Vec<String>,
+}
+
+impl MemoryPartWriter {
+    /// Returns the collected parts.
+    pub fn into_parts(self) -> Vec<String> {
+        self.parts
+    }
+}
+
+impl PartWriter for MemoryPartWriter {
+    type Error = Infallible;
+
+    fn begin_part(&mut self, _number: usize, _total: usize) -> Result<(), Infallible> {
+        self.parts.push(String::new());
+        Ok(())
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), Infallible> {
+        if let Some(part) = self.parts.last_mut() {
+            part.push_str(text);
+        }
+
+        Ok(())
+    }
+
+    fn end_part(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
+
+/// Writes text to the inner writer with the leading and trailing whitespace removed,
+/// same as `str::trim` would do for the whole text, without keeping the text in memory.
+/// Only the trailing whitespace written so far is held back, since it is not known
+/// whether more text will follow.
+#[derive(Debug)]
+pub struct TrimmedWriter<W: Write> {
+    inner: W,
+    started: bool,
+    pending_whitespace: String,
+}
+
+impl<W: Write> TrimmedWriter<W> {
+    pub fn new(inner: W) -> Self {
+        TrimmedWriter {
+            inner,
+            started: false,
+            pending_whitespace: String::new(),
+        }
+    }
+
+    /// Writes the text, trimming the whitespace at the start and at the end of the whole text.
+    pub fn write(&mut self, text: &str) -> io::Result<()> {
+        let text = if self.started {
+            text
+        } else {
+            text.trim_start()
+        };
+
+        if text.is_empty() {
+            return Ok(());
+        }
+
+        self.started = true;
+        let trimmed = text.trim_end();
+
+        if trimmed.is_empty() {
+            self.pending_whitespace.push_str(text);
+            return Ok(());
+        }
+
+        self.inner.write_all(self.pending_whitespace.as_bytes())?;
+        self.inner.write_all(trimmed.as_bytes())?;
+        self.pending_whitespace.clear();
+        self.pending_whitespace.push_str(&text[trimmed.len()..]);
+        Ok(())
+    }
+
+    /// Flushes the text written so far, except for the trailing whitespace.
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    /// Drops the trailing whitespace and returns the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_trimmed(pieces: &[&str]) -> String {
+        let mut writer = TrimmedWriter::new(Vec::new());
+
+        for piece in pieces {
+            writer.write(piece).unwrap();
+        }
+
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn test_trimmed_writer_matches_trim() {
+        let pieces = ["\n  ", "", " Hello\n", "\n", " ", "World ", "\n\n", "  "];
+
+        assert_eq!(write_trimmed(&pieces), pieces.concat().trim());
+    }
+
+    #[test]
+    fn test_trimmed_writer_whitespace_only() {
+        assert_eq!(write_trimmed(&["  ", "\n"]), "");
+    }
+
+    #[test]
+    fn test_memory_part_writer() {
+        let mut writer = MemoryPartWriter::default();
+        writer.begin_part(1, 2).unwrap();
+        writer.write("Hello").unwrap();
+        writer.write(" world").unwrap();
+        writer.end_part().unwrap();
+        writer.begin_part(2, 2).unwrap();
+        writer.write("Part 2").unwrap();
+        writer.end_part().unwrap();
+
+        assert_eq!(writer.into_parts(), vec!["Hello world", "Part 2"]);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::file::cache::{default_cache_dir, write_private_file};
+use crate::options::QuaggaOptions;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The name of the file in the cache directory the session is saved to.
+const SESSION_FILE: &str = "clipboard-session.json";
+
+/// The parts of the last prompt copied to the clipboard. The session is saved so that
+/// the parts can be copied again later with `--clipboard-part` without generating the prompt.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardSession {
+    pub parts: Vec<String>,
+}
+
+impl ClipboardSession {
+    /// Saves the session to the directory, replacing the previous session.
+    /// The file is readable only by the owner, since it contains the prompt.
+    pub fn save(&self, dir: &Path) -> io::Result<()> {
+        fs::create_dir_all(dir)?;
+        let json = serde_json::to_string(self).map_err(io::Error::other)?;
+        write_private_file(&session_path(dir), json.as_bytes())
+    }
+
+    /// Loads the session saved to the directory.
+    ///
+    /// # Returns
+    ///
+    /// The session, or an error if no session was saved or it can not be read.
+    pub fn load(dir: &Path) -> io::Result<ClipboardSession> {
+        let json = fs::read_to_string(session_path(dir))?;
+        serde_json::from_str(&json).map_err(io::Error::other)
+    }
+}
+
+/// Returns the directory the session is saved to: the cache directory from the options,
+/// or the default cache directory.
+pub fn session_dir(options: &QuaggaOptions) -> Option<PathBuf> {
+    options.cache_dir.clone().or_else(default_cache_dir)
+}
+
+/// Returns the directory to save the session to after copying the prompt.
+///
+/// # Returns
+///
+/// `None` if the cache is disabled, the prompt is then not written to disk.
+pub fn save_session_dir(options: &QuaggaOptions) -> Option<PathBuf> {
+    if options.cache {
+        session_dir(options)
+    } else {
+        None
+    }
+}
+
+fn session_path(dir: &Path) -> PathBuf {
+    dir.join(SESSION_FILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+
+    #[test]
+    fn test_save_and_load_session() {
+        let td = TempDir::new().unwrap();
+        let dir = td.path().join("cache");
+
+        let session = ClipboardSession {
+            parts: vec!["Part 1".to_string(), "Part 2".to_string()],
+        };
+
+        session.save(&dir).unwrap();
+        assert_eq!(ClipboardSession::load(&dir).unwrap(), session);
+
+        // A new session replaces the previous one
+        let session = ClipboardSession {
+            parts: vec!["Only part".to_string()],
+        };
+
+        session.save(&dir).unwrap();
+        assert_eq!(ClipboardSession::load(&dir).unwrap(), session);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_session_readable_only_by_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let td = TempDir::new().unwrap();
+        let path = td.mkfile_with_contents(SESSION_FILE, "{}");
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let session = ClipboardSession {
+            parts: vec!["Secret".to_string()],
+        };
+
+        session.save(td.path()).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_load_missing_session() {
+        let td = TempDir::new().unwrap();
+
+        assert!(ClipboardSession::load(td.path()).is_err());
+    }
+
+    #[test]
+    fn test_session_dir() {
+        let options = QuaggaOptions {
+            cache_dir: Some(PathBuf::from("/tmp/quagga-cache")),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            session_dir(&options),
+            Some(PathBuf::from("/tmp/quagga-cache"))
+        );
+    }
+
+    #[test]
+    fn test_save_session_dir() {
+        let options = QuaggaOptions {
+            cache_dir: Some(PathBuf::from("/tmp/quagga-cache")),
+            ..Default::default()
+        };
+
+        assert_eq!(save_session_dir(&options), None);
+
+        let options = QuaggaOptions {
+            cache: true,
+            ..options
+        };
+
+        assert_eq!(
+            save_session_dir(&options),
+            Some(PathBuf::from("/tmp/quagga-cache"))
+        );
+    }
+}
@@ -1,3 +1,76 @@
+use super::part_writer::{PartWriter, TrimmedWriter};
+use crate::error::QuaggaError;
+use std::io::{self, BufWriter, StdoutLock, Write};
+use std::path::PathBuf;
+
 pub fn output_to_stdout(content: Vec<String>) {
     println!("{}", content.join("\n").trim());
 }
+
+/// Writes the parts to stdout as they are generated, separated by newlines.
+/// The output is the same as with `output_to_stdout`.
+pub struct StdoutPartWriter {
+    writer: Option<TrimmedWriter<BufWriter<StdoutLock<'static>>>>,
+}
+
+impl StdoutPartWriter {
+    pub fn new() -> Self {
+        StdoutPartWriter {
+            writer: Some(TrimmedWriter::new(BufWriter::new(io::stdout().lock()))),
+        }
+    }
+
+    fn write_text(&mut self, text: &str) -> Result<(), QuaggaError> {
+        match &mut self.writer {
+            Some(writer) => writer.write(text).map_err(stdout_error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for StdoutPartWriter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartWriter for StdoutPartWriter {
+    type Error = QuaggaError;
+
+    fn begin_part(&mut self, number: usize, _total: usize) -> Result<(), QuaggaError> {
+        if number > 1 {
+            self.write_text("\n")?;
+        }
+
+        Ok(())
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), QuaggaError> {
+        self.write_text(text)
+    }
+
+    fn end_part(&mut self) -> Result<(), QuaggaError> {
+        match &mut self.writer {
+            Some(writer) => writer.flush().map_err(stdout_error),
+            None => Ok(()),
+        }
+    }
+
+    fn finish(&mut self) -> Result<(), QuaggaError> {
+        // The whole output is written, so the trailing whitespace is dropped
+        if let Some(writer) = self.writer.take() {
+            let mut stdout = writer.into_inner();
+            stdout.write_all(b"\n").map_err(stdout_error)?;
+            stdout.flush().map_err(stdout_error)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn stdout_error(source: io::Error) -> QuaggaError {
+    QuaggaError::Output {
+        path: PathBuf::from("stdout"),
+        source,
+    }
+}
@@ -1,12 +1,21 @@
 use crate::cli::Cli;
-use crate::file::file_reader::read_and_concatenate_files;
-use crate::info::info::info_output;
+use crate::error::QuaggaError;
+use crate::file::path_style::PathFormatter;
+use crate::file::redact::Redaction;
+use crate::file::skipped_file::SkippedFile;
+use crate::file::transform::TransformSavings;
+use crate::info::info::{info_output, is_info_output};
+use crate::options::{OnError, QuaggaOptions};
+use crate::output::clipboard::output_saved_part_to_clipboard;
+use crate::output::exec::ExecPartWriter;
+use crate::output::file::FilePartWriter;
+use crate::output::manifest::Manifest;
 use crate::output::output::process_output;
-use crate::template::read::{path_to_custom_template, read_and_parse_template};
-use crate::template::template::Template;
-use crate::walk::file_walker::get_all_files;
-use std::error::Error;
-use std::path::PathBuf;
+use crate::output::session::session_dir;
+use crate::output::stdout::StdoutPartWriter;
+use crate::quagga::{Quagga, WriteSummary};
+use crate::template::read::path_to_custom_template;
+use std::path::{Path, PathBuf};
 
 /// Processes command line arguments, generates prompt and sends it to the output.
 /// This is the main function called by `main.rs`.
@@ -15,8 +24,8 @@ use std::path::PathBuf;
 ///
 /// * `cli` - Command line arguments.
 /// * `piped_paths` - An optional `Vec<PathBuf>` representing a list of file paths the user
-///                  has piped in via stdin. When present, the program will process the
-///                  files in the list instead of walking the root directory.
+///   has piped in via stdin. When present, the program will process the
+///   files in the list instead of walking the root directory.
 ///
 /// # Returns
 ///
@@ -24,20 +33,72 @@ use std::path::PathBuf;
 pub fn generate_prompt_and_output(
     cli: &Cli,
     piped_paths: Option<Vec<PathBuf>>,
-) -> Result<(), Box<dyn Error>> {
-    let output = generate_prompt(cli, piped_paths)?;
-    process_output(output, &cli)?;
+) -> Result<(), QuaggaError> {
+    if let Some(number) = cli.clipboard_part {
+        // The part is copied from the saved session, without reading the files
+        let options = QuaggaOptions::from(cli);
+        let session_dir = session_dir(&options);
+
+        return output_saved_part_to_clipboard(
+            number,
+            options.clipboard_backend,
+            session_dir.as_deref(),
+        )
+        .map_err(|e| QuaggaError::Clipboard(e.to_string()));
+    }
+
+    if cli.clipboard || cli.send.is_some() || is_info_output(cli) {
+        // The clipboard and the chat endpoint need all parts at once
+        let output = generate_prompt(cli, piped_paths)?;
+        process_output(output, &QuaggaOptions::from(cli))?;
+        return Ok(());
+    }
+
+    let quagga = Quagga::with_options(QuaggaOptions::from(cli))
+        .paths(piped_paths)
+        .build()?;
+
+    let summary = match (&cli.exec, &cli.output) {
+        (Some(command), _) => quagga.write_parts(&mut ExecPartWriter::new(command))?,
+        (None, Some(path)) => write_to_file(&quagga, path, cli)?,
+        (None, None) => quagga.write_parts(&mut StdoutPartWriter::new())?,
+    };
+
+    warn_about_skipped_files(cli, &summary.skipped);
+    report_redactions(cli, &summary.redactions);
+    report_savings(cli, &summary.savings);
     Ok(())
 }
 
+/// Writes the prompt to files at the `path`, followed by the manifest if `--manifest` is used.
+fn write_to_file(quagga: &Quagga, path: &Path, cli: &Cli) -> Result<WriteSummary, QuaggaError> {
+    let mut writer = FilePartWriter::new(path, None)?;
+    let summary = quagga.write_parts(&mut writer)?;
+
+    if cli.manifest {
+        let manifest = Manifest::new(
+            writer.written_parts(),
+            &summary.parts,
+            quagga.template(),
+            path_to_custom_template(quagga.options()),
+            quagga.options(),
+            std::env::args().collect(),
+        );
+
+        manifest.write(writer.path())?;
+    }
+
+    Ok(summary)
+}
+
 /// Generates the output prompt content based on the given command line arguments.
 ///
 /// # Arguments
 ///
 /// * `cli` - Command line arguments.
 /// * `piped_paths` - An optional `Vec<PathBuf>` representing a list of file paths the user
-///                  has piped in via stdin. When present, the program will process the
-///                  files in the list instead of walking the root directory.
+///   has piped in via stdin. When present, the program will process the
+///   files in the list instead of walking the root directory.
 ///
 /// # Returns
 ///
@@ -46,53 +107,79 @@ pub fn generate_prompt_and_output(
 pub fn generate_prompt(
     cli: &Cli,
     piped_paths: Option<Vec<PathBuf>>,
-) -> Result<Vec<String>, Box<dyn Error>> {
-    let output = info_output(cli, piped_paths.clone())?;
+) -> Result<Vec<String>, QuaggaError> {
+    let mut skipped = Vec::new();
+    let output = info_output(cli, piped_paths.clone(), &mut skipped)?;
 
     if let Some(output) = output {
+        warn_about_skipped_files(cli, &skipped);
         return Ok(Vec::from([output]));
     }
 
-    let template_path = path_to_custom_template(cli);
-    let template = read_and_parse_template(template_path)?;
+    let prompt = Quagga::with_options(QuaggaOptions::from(cli))
+        .paths(piped_paths)
+        .build()?
+        .render()?;
+
+    warn_about_skipped_files(cli, &prompt.skipped);
+    report_redactions(cli, &prompt.redactions);
+    report_savings(cli, &prompt.savings);
+    Ok(prompt.parts)
+}
 
-    if let Some(path_list) = piped_paths {
-        return read_and_concatenate_files(path_list, template, cli)
-            .map_err(|e| Box::new(e) as Box<dyn Error>);
-    } else {
-        return process_files(cli, template);
+/// Prints the files that could not be read to stderr when `--on-error warn` is used.
+fn warn_about_skipped_files(cli: &Cli, skipped: &[SkippedFile]) {
+    if cli.on_error != OnError::Warn {
+        return;
+    }
+
+    for file in skipped {
+        eprintln!("Warning: skipped {}: {}", file.path.display(), file.reason);
     }
 }
 
-/// Processes files starting from the given root path:
-/// - Retrieves file paths by walking the root directory.
-/// - Reads and concatenates their contents.
-///
-/// # Arguments
-///
-/// * `cli` - Command line arguments.
-///
-/// # Returns
-///
-/// A `Result` containing the output prompt, splitted into parts, if successful,
-/// or an `io::Error` if an error occurs.
-///
-/// # Errors
-///
-/// This function will return an error if:
-/// - Retrieving the list of files fails.
-/// - Reading any of the files fails.
-pub fn process_files(cli: &Cli, template: Template) -> Result<Vec<String>, Box<dyn Error>> {
-    let mut files = get_all_files(cli)?;
-    files.sort();
-
-    read_and_concatenate_files(files, template, cli).map_err(|e| Box::new(e) as Box<dyn Error>)
+/// Prints the file and line of each redacted secret to stderr when `--redact-report` is used.
+/// The paths are shown in the `--path-style`, same as in the prompt.
+fn report_redactions(cli: &Cli, redactions: &[Redaction]) {
+    if !cli.redact_report {
+        return;
+    }
+
+    let formatter = PathFormatter::from_options(&QuaggaOptions::from(cli));
+
+    for redaction in redactions {
+        eprintln!(
+            "Redacted {} at {}:{}",
+            redaction.kind,
+            formatter.display(&redaction.path),
+            redaction.line
+        );
+    }
+
+    eprintln!("Redacted {} secret(s)", redactions.len());
+}
+
+/// Prints the number of characters saved by each transform to stderr
+/// when `--transform-report` is used.
+fn report_savings(cli: &Cli, savings: &[TransformSavings]) {
+    if !cli.transform_report {
+        return;
+    }
+
+    for transform in savings {
+        eprintln!(
+            "{} saved {} character(s) in {} file(s)",
+            transform.name, transform.chars, transform.files
+        );
+    }
+
+    let total: usize = savings.iter().map(|transform| transform.chars).sum();
+    eprintln!("Transforms saved {} character(s)", total);
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::template::template::PromptTemplate;
     use crate::test_utils::temp_dir::TempDir;
     use clap::Parser;
 
@@ -102,7 +189,7 @@ mod tests {
         let path1 = td.mkfile("file1.txt");
         let path2 = td.mkfile("file2.txt");
 
-        let mut cli = Cli::parse_from(&["test", "--paths"]);
+        let mut cli = Cli::parse_from(["test", "--paths", "--path-style", "absolute"]);
         cli.root = td.path_buf();
 
         let result = generate_prompt(&cli, None);
@@ -122,7 +209,7 @@ mod tests {
         td.mkdir("subdir");
         td.mkfile("subdir/file3.txt");
 
-        let mut cli = Cli::parse_from(&["test", "--tree"]);
+        let mut cli = Cli::parse_from(["test", "--tree"]);
         cli.root = td.path_buf();
 
         let result = generate_prompt(&cli, None);
@@ -131,68 +218,80 @@ mod tests {
         let content = result.unwrap();
         assert_eq!(content.len(), 1);
 
-        let expected = format!(
-            r#"{}
+        let expected = r#".
 ├── subdir
 │   └── file3.txt
 ├── file1.txt
-└── file2.txt"#,
-            td.path().display()
-        );
+└── file2.txt"#;
 
         assert_eq!(content[0], expected);
     }
 
     #[test]
-    fn test_process_files_success() {
+    fn test_generate_prompt_combines_files() {
         let td = TempDir::new().unwrap();
-        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
-        let file2_path = td.mkfile_with_contents("file3.txt", "World!");
+        add_template(&td);
+        td.mkfile_with_contents("file1.txt", "Hello");
+        td.mkfile_with_contents("file3.txt", "World!");
 
-        let mut cli = Cli::parse_from(&["test"]);
+        let mut cli = Cli::parse_from(["test"]);
         cli.root = td.path_buf();
 
-        let template = Template {
-            prompt: PromptTemplate {
-                header: "Header".to_string(),
-                file: "File: <file-path>\nContent:\n<file-content>\n---".to_string(),
-                footer: "Footer".to_string(),
-            },
-            part: Default::default(),
-        };
-
-        let result = process_files(&cli, template);
+        let result = generate_prompt(&cli, None);
 
         assert!(result.is_ok());
         let content = result.unwrap();
-        assert_eq!(content.len(), 1);
+        assert_eq!(content, vec!["Hello\nWorld!\n".to_string()]);
+    }
 
-        let expected = format!(
-            "\
-Header
-File: {}
-Content:
-Hello
----
-File: {}
-Content:
-World!
----
-Footer",
-            file1_path.display(),
-            file2_path.display()
-        );
+    #[test]
+    fn test_generate_prompt_with_piped_paths() {
+        let td = TempDir::new().unwrap();
+        add_template(&td);
+        let file2_path = td.mkfile_with_contents("file2.txt", "World!");
+        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
+        td.mkfile_with_contents("file3.txt", "Not included");
 
-        assert_eq!(content[0], expected);
+        let mut cli = Cli::parse_from(["test"]);
+        cli.root = td.path_buf();
+
+        let result = generate_prompt(&cli, Some(vec![file2_path, file1_path]));
+
+        assert!(result.is_ok());
+        let content = result.unwrap();
+
+        // Piped paths keep their order
+        assert_eq!(content, vec!["World!\nHello\n".to_string()]);
     }
 
     #[test]
-    fn test_process_files_with_nonexistent_directory() {
-        let mut cli = Cli::parse_from(&["test"]);
+    fn test_generate_prompt_with_nonexistent_directory() {
+        let mut cli = Cli::parse_from(["test", "--no-quagga-template"]);
         cli.root = PathBuf::from("/path/to/nonexistent/directory");
 
-        let result = process_files(&cli, Template::default());
+        let result = generate_prompt(&cli, None);
 
         assert!(result.is_err());
     }
+
+    /// Adds a template that only outputs the file contents.
+    fn add_template(td: &TempDir) {
+        td.mkfile_with_contents(
+            ".quagga_template",
+            r#"
+<template>
+  <prompt>
+    <header></header>
+    <file><file-content></file>
+    <footer></footer>
+  </prompt>
+  <part>
+    <header></header>
+    <footer></footer>
+    <pending></pending>
+  </part>
+</template>
+"#,
+        );
+    }
 }
@@ -0,0 +1,932 @@
+use crate::error::QuaggaError;
+use crate::file::archive::ArchiveCache;
+use crate::file::file_content::FileContent;
+use crate::file::file_reader::{concatenate_file_contents, read_file_list};
+use crate::file::redact::Redaction;
+use crate::file::size::{check_total_size, keep_regular_files};
+use crate::file::skipped_file::SkippedFile;
+use crate::file::sort::sort_files;
+use crate::file::transform::{
+    ContentTransform, TransformPipeline, TransformReport, TransformSavings, TransformSpec,
+};
+use crate::options::{OnError, PathStyle, QuaggaOptions, SortOrder};
+use crate::output::part_writer::PartWriter;
+use crate::template::read::{path_to_custom_template, read_and_parse_template};
+use crate::template::stream::{stream_files, PartFile};
+use crate::template::template::Template;
+use crate::walk::archive_walker::expand_archives;
+use crate::walk::file_walker::{get_all_file_contents, get_all_files};
+use crate::walk::modified::TimeReference;
+use encoding_rs::Encoding;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Generates prompts from text files. This is the entry point for using `quagga` as a library.
+///
+/// ```no_run
+/// use quagga::Quagga;
+///
+/// let prompt = Quagga::new("src")
+///     .include(["*.rs"])
+///     .exclude(["target"])
+///     .max_part_size(50_000)
+///     .build()?
+///     .render()?;
+///
+/// for part in prompt.parts {
+///     println!("{}", part);
+/// }
+/// # Ok::<(), quagga::QuaggaError>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Quagga {
+    options: QuaggaOptions,
+    template: Template,
+    paths: Option<Vec<PathBuf>>,
+    content_transforms: Vec<Arc<dyn ContentTransform>>,
+
+    /// The archives read while generating the prompts, so that each archive is read once.
+    archives: ArchiveCache,
+}
+
+/// The generated prompt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prompt {
+    /// Paths to the files included in the prompt, in the order they appear in it.
+    pub files: Vec<PathBuf>,
+
+    /// The prompt text, split into parts that fit within the `max_part_size` limit.
+    pub parts: Vec<String>,
+
+    /// Files left out of the prompt because they could not be read.
+    pub skipped: Vec<SkippedFile>,
+
+    /// Secrets replaced with `[REDACTED:kind]` in the prompt.
+    pub redactions: Vec<Redaction>,
+
+    /// The characters saved by each transform.
+    pub savings: Vec<TransformSavings>,
+}
+
+/// The result of writing the prompt with `Quagga::write_parts`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteSummary {
+    /// The files in each part. Files split between parts are listed with the lines in each part.
+    pub parts: Vec<Vec<PartFile>>,
+
+    /// Files left out of the prompt because they could not be read.
+    pub skipped: Vec<SkippedFile>,
+
+    /// Secrets replaced with `[REDACTED:kind]` in the prompt.
+    pub redactions: Vec<Redaction>,
+
+    /// The characters saved by each transform.
+    pub savings: Vec<TransformSavings>,
+}
+
+impl Quagga {
+    /// Starts building a `Quagga` that will look for files in the `root` directory.
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new<P: Into<PathBuf>>(root: P) -> QuaggaBuilder {
+        QuaggaBuilder::new(QuaggaOptions {
+            root: root.into(),
+            ..Default::default()
+        })
+    }
+
+    /// Starts building a `Quagga` from existing options.
+    pub fn with_options(options: QuaggaOptions) -> QuaggaBuilder {
+        QuaggaBuilder::new(options)
+    }
+
+    /// Returns the options used to generate the prompt.
+    pub fn options(&self) -> &QuaggaOptions {
+        &self.options
+    }
+
+    /// Returns the template used to generate the prompt.
+    pub fn template(&self) -> &Template {
+        &self.template
+    }
+
+    /// Returns the paths to the files that will be included in the prompt.
+    /// These are either the paths given with `QuaggaBuilder::paths`, or the paths
+    /// found by walking the root directory, in the order of the `sort`, `reverse`
+    /// and `first` options.
+    pub fn files(&self) -> Result<Vec<PathBuf>, QuaggaError> {
+        if let Some(paths) = self.sorted_paths(&mut Vec::new())? {
+            return Ok(paths);
+        }
+
+        get_all_files(&self.options, &self.archives, &mut Vec::new())
+    }
+
+    /// Reads the files and combines them into a prompt using the template.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if:
+    /// - Retrieving the list of files fails (`QuaggaError::Walk`).
+    /// - Reading any of the files fails (`QuaggaError::FileRead`) and `on_error` is `OnError::Fail`.
+    ///   Otherwise the files are listed in `Prompt::skipped`.
+    /// - There are no files (`QuaggaError::NoFiles`) or their total size exceeds the limit
+    ///   (`QuaggaError::TotalSizeExceeded`).
+    pub fn render(&self) -> Result<Prompt, QuaggaError> {
+        let mut skipped = Vec::new();
+        let mut report = TransformReport::default();
+        let file_contents = self.read_files(&mut skipped)?;
+        let pipeline = self.pipeline(TransformPipeline::for_contents(
+            &self.options,
+            &file_contents,
+        )?);
+        let files = file_contents.iter().map(|file| file.path.clone()).collect();
+
+        let parts = concatenate_file_contents(
+            file_contents,
+            self.template.clone(),
+            &self.options,
+            &self.archives,
+            &pipeline,
+            &skipped,
+            &mut report,
+        )?;
+
+        Ok(Prompt {
+            files,
+            parts,
+            skipped,
+            redactions: report.redactions,
+            savings: report.savings,
+        })
+    }
+
+    /// Reads the files and writes the prompt to the `writer` part by part, instead of
+    /// returning all parts at once like `render`. The rendered files are kept in memory
+    /// only up to a limit, the files after it are read again when the parts are written.
+    ///
+    /// # Returns
+    ///
+    /// The files in each part, the files left out of the prompt because they could not be read,
+    /// the secrets that were redacted and the characters saved by the transforms.
+    ///
+    /// # Errors
+    ///
+    /// Same as `render`, and also the errors returned by the `writer`.
+    pub fn write_parts<W>(&self, writer: &mut W) -> Result<WriteSummary, QuaggaError>
+    where
+        W: PartWriter<Error = QuaggaError>,
+    {
+        let mut skipped = Vec::new();
+        let mut report = TransformReport::default();
+
+        let files = match self.sorted_paths(&mut skipped)? {
+            Some(paths) => {
+                keep_regular_files(paths, self.options.on_error, &self.archives, &mut skipped)?
+            }
+            None => get_all_files(&self.options, &self.archives, &mut skipped)?,
+        };
+
+        check_total_size(files.clone(), &self.options, &self.archives)?;
+        let pipeline = self.pipeline(TransformPipeline::for_files(
+            &self.options,
+            &files,
+            &self.archives,
+        )?);
+        let parts = stream_files(
+            files,
+            &self.template,
+            &self.options,
+            &self.archives,
+            &pipeline,
+            &mut skipped,
+            &mut report,
+            writer,
+        )?;
+
+        Ok(WriteSummary {
+            parts,
+            skipped,
+            redactions: report.redactions,
+            savings: report.savings,
+        })
+    }
+
+    /// Adds the custom transforms to the end of the pipeline with the transforms
+    /// enabled by the options.
+    fn pipeline(&self, mut pipeline: TransformPipeline) -> TransformPipeline {
+        for transform in &self.content_transforms {
+            pipeline.push_shared(transform.clone());
+        }
+
+        pipeline
+    }
+
+    /// Reads the files given with `QuaggaBuilder::paths`, or the files found by walking
+    /// the root directory. The files that could not be read are added to `skipped`.
+    fn read_files(&self, skipped: &mut Vec<SkippedFile>) -> Result<Vec<FileContent>, QuaggaError> {
+        match self.sorted_paths(skipped)? {
+            Some(paths) => read_file_list(paths, &self.options, &self.archives, skipped),
+            None => get_all_file_contents(&self.options, &self.archives, skipped),
+        }
+    }
+
+    /// Returns the paths given with `QuaggaBuilder::paths` in the order of the `sort`,
+    /// `reverse` and `first` options, or `None` if the files are found by walking.
+    /// The paths to archives are replaced with the paths to the files inside them.
+    fn sorted_paths(
+        &self,
+        skipped: &mut Vec<SkippedFile>,
+    ) -> Result<Option<Vec<PathBuf>>, QuaggaError> {
+        let Some(paths) = &self.paths else {
+            return Ok(None);
+        };
+
+        let mut paths = expand_archives(paths.clone(), &self.options, &self.archives, skipped)?;
+        sort_files(&mut paths, &self.options, &self.archives)?;
+        Ok(Some(paths))
+    }
+}
+
+/// Builder for `Quagga`, created with `Quagga::new` or `Quagga::with_options`.
+#[derive(Debug, Clone)]
+pub struct QuaggaBuilder {
+    options: QuaggaOptions,
+    template: Option<Template>,
+    paths: Option<Vec<PathBuf>>,
+    content_transforms: Vec<Arc<dyn ContentTransform>>,
+}
+
+impl QuaggaBuilder {
+    fn new(options: QuaggaOptions) -> Self {
+        QuaggaBuilder {
+            options,
+            template: None,
+            paths: None,
+            content_transforms: Vec::new(),
+        }
+    }
+
+    /// Include only file paths matching the glob patterns (e.g., src/*.js).
+    pub fn include<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.include = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Exclude file paths that match the glob patterns (e.g., node_modules).
+    pub fn exclude<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.exclude = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Include only files that contain any of the texts.
+    pub fn contain<I, S>(mut self, texts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.contain = texts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Include only files that contain any of the whole words.
+    pub fn contain_word<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.contain_word = words.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Include only files that match any of the regular expressions.
+    pub fn contain_regex<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.contain_regex = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Exclude files that contain any of the texts.
+    pub fn not_contain<I, S>(mut self, texts: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.not_contain = texts.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Include only files that contain all of the `contain`, `contain_word`
+    /// and `contain_regex` patterns, instead of any of them.
+    pub fn contain_all(mut self, enabled: bool) -> Self {
+        self.options.contain_all = enabled;
+        self
+    }
+
+    /// Ignore case when matching the content of the files.
+    pub fn ignore_case(mut self, enabled: bool) -> Self {
+        self.options.ignore_case = enabled;
+        self
+    }
+
+    /// Include only files modified within `age` before now.
+    pub fn modified_within(mut self, age: Duration) -> Self {
+        self.options.modified_within = Some(age);
+        self
+    }
+
+    /// Include only files modified at or after the `time`.
+    pub fn newer_than(mut self, time: TimeReference) -> Self {
+        self.options.newer_than = Some(time);
+        self
+    }
+
+    /// Include only files modified before the `time`.
+    pub fn older_than(mut self, time: TimeReference) -> Self {
+        self.options.older_than = Some(time);
+        self
+    }
+
+    /// Sets the order of the files in the prompt.
+    pub fn sort(mut self, order: SortOrder) -> Self {
+        self.options.sort = Some(order);
+        self
+    }
+
+    /// Reverse the order of the files.
+    pub fn reverse(mut self, enabled: bool) -> Self {
+        self.options.reverse = enabled;
+        self
+    }
+
+    /// Put the files matching the glob patterns first, in the order of the patterns.
+    pub fn first<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.first = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Include only the lines matching the `contain`, `contain_word` and `contain_regex`
+    /// patterns, with `context` lines around them.
+    pub fn excerpt(mut self, context: usize) -> Self {
+        self.options.excerpt = Some(context);
+        self
+    }
+
+    /// Descend only `depth` directories deep.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.options.max_depth = Some(depth);
+        self
+    }
+
+    /// Split the prompt into parts of this many characters.
+    pub fn max_part_size(mut self, chars: u64) -> Self {
+        self.options.max_part_size = chars;
+        self
+    }
+
+    /// Ignore files above this size in bytes.
+    pub fn max_filesize(mut self, bytes: u64) -> Self {
+        self.options.max_filesize = bytes;
+        self
+    }
+
+    /// Return an error if the total size of files is above this size in bytes.
+    pub fn max_total_size(mut self, bytes: u64) -> Self {
+        self.options.max_total_size = bytes;
+        self
+    }
+
+    /// Whether to use .gitignore files (used by default).
+    pub fn gitignore(mut self, enabled: bool) -> Self {
+        self.options.no_gitignore = !enabled;
+        self
+    }
+
+    /// Whether to use .quagga_ignore files from project and home dirs (used by default).
+    pub fn quagga_ignore(mut self, enabled: bool) -> Self {
+        self.options.no_quagga_ignore = !enabled;
+        self
+    }
+
+    /// Whether to include binary files (ignored by default).
+    pub fn binary(mut self, enabled: bool) -> Self {
+        self.options.binary = enabled;
+        self
+    }
+
+    /// Sets the encoding of the files instead of detecting it.
+    pub fn encoding(mut self, encoding: &'static Encoding) -> Self {
+        self.options.encoding = Some(encoding);
+        self
+    }
+
+    /// Include the text outputs of Jupyter notebook cells, truncated to `chars` characters.
+    pub fn notebook_outputs(mut self, chars: usize) -> Self {
+        self.options.notebook_outputs = Some(chars);
+        self
+    }
+
+    /// Whether to skip generated files and lock files (skipped by default).
+    pub fn skip_generated(mut self, enabled: bool) -> Self {
+        self.options.skip_generated = enabled;
+        self
+    }
+
+    /// Whether to skip minified files, source maps and encoded data (skipped by default).
+    pub fn skip_minified(mut self, enabled: bool) -> Self {
+        self.options.skip_minified = enabled;
+        self
+    }
+
+    /// Whether to include hidden files (ignored by default).
+    pub fn hidden(mut self, enabled: bool) -> Self {
+        self.options.hidden = enabled;
+        self
+    }
+
+    /// Whether to follow symbolic links (not followed by default).
+    pub fn follow_links(mut self, enabled: bool) -> Self {
+        self.options.follow_links = enabled;
+        self
+    }
+
+    /// Whether to remove comments from the files.
+    pub fn remove_comments(mut self, enabled: bool) -> Self {
+        self.options.remove_comments = enabled;
+        self
+    }
+
+    /// Whether to replace the bodies of functions with `{ ... }` in all source files.
+    pub fn outline(mut self, enabled: bool) -> Self {
+        self.options.outline = enabled;
+        self
+    }
+
+    /// Outline only the source files matching the glob patterns (e.g., `vendor/**`).
+    pub fn outline_glob<I, S>(mut self, patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.options.outline_glob = patterns.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Remove the license comments at the start of files, and the comment blocks
+    /// that many of the files start with.
+    pub fn strip_license_headers(mut self, enabled: bool) -> Self {
+        self.options.strip_license_headers = enabled;
+        self
+    }
+
+    /// Remove the whitespace at the ends of lines and replace runs of blank lines
+    /// with a single blank line, except where the whitespace is part of the syntax.
+    pub fn compact(mut self, enabled: bool) -> Self {
+        self.options.compact = enabled;
+        self
+    }
+
+    /// With `compact`, replace each `width` spaces at the start of lines with a tab,
+    /// except in Python, YAML and makefiles.
+    pub fn compact_tabs(mut self, width: usize) -> Self {
+        self.options.compact_tabs = Some(width);
+        self
+    }
+
+    /// Apply the built-in transforms to the files after the ones enabled by other options,
+    /// in this order.
+    pub fn transforms<I>(mut self, transforms: I) -> Self
+    where
+        I: IntoIterator<Item = TransformSpec>,
+    {
+        self.options.transforms = transforms.into_iter().collect();
+        self
+    }
+
+    /// Apply a custom transform to all files, after the built-in transforms.
+    pub fn content_transform<T: ContentTransform + 'static>(mut self, transform: T) -> Self {
+        self.content_transforms.push(Arc::new(transform));
+        self
+    }
+
+    /// Whether to replace secrets, such as private keys and tokens, with `[REDACTED:kind]`
+    /// (replaced by default).
+    pub fn redact(mut self, enabled: bool) -> Self {
+        self.options.redact = enabled;
+        self
+    }
+
+    /// What to do when a file can not be read (`OnError::Warn` by default).
+    pub fn on_error(mut self, on_error: OnError) -> Self {
+        self.options.on_error = on_error;
+        self
+    }
+
+    /// Cache the results of processing files on disk (disabled by default).
+    pub fn cache(mut self, enabled: bool) -> Self {
+        self.options.cache = enabled;
+        self
+    }
+
+    /// The cache directory, `$XDG_CACHE_HOME/quagga` or `~/.cache/quagga` by default.
+    pub fn cache_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.options.cache_dir = Some(dir.into());
+        self
+    }
+
+    /// Path to a custom template file.
+    pub fn template<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.options.template = Some(path.into());
+        self
+    }
+
+    /// Whether to use .quagga_template from project and home dirs (used by default).
+    pub fn quagga_template(mut self, enabled: bool) -> Self {
+        self.options.no_quagga_template = !enabled;
+        self
+    }
+
+    /// How the paths to files are shown in the prompt (`PathStyle::Relative` by default).
+    pub fn path_style(mut self, style: PathStyle) -> Self {
+        self.options.path_style = style;
+        self
+    }
+
+    /// Use an already parsed template instead of reading one from disk.
+    pub fn parsed_template(mut self, template: Template) -> Self {
+        self.template = Some(template);
+        self
+    }
+
+    /// Use the given file paths instead of walking the root directory.
+    /// Passing `None` restores walking the root directory.
+    pub fn paths(mut self, paths: Option<Vec<PathBuf>>) -> Self {
+        self.paths = paths;
+        self
+    }
+
+    /// Reads and parses the template and returns `Quagga` ready to render the prompt.
+    ///
+    /// # Errors
+    ///
+    /// Returns `QuaggaError::TemplateRead` or `QuaggaError::TemplateParse` if the template
+    /// can not be read or parsed.
+    pub fn build(self) -> Result<Quagga, QuaggaError> {
+        let template = match self.template {
+            Some(template) => template,
+            None => read_and_parse_template(path_to_custom_template(&self.options))?,
+        };
+
+        Ok(Quagga {
+            options: self.options,
+            template,
+            paths: self.paths,
+            content_transforms: self.content_transforms,
+            archives: ArchiveCache::default(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::template::template::PromptTemplate;
+    use crate::test_utils::temp_dir::TempDir;
+
+    fn simple_template() -> Template {
+        Template {
+            prompt: PromptTemplate {
+                header: "Header".to_string(),
+                file: "File: <file-path>\nContent:\n<file-content>\n---".to_string(),
+                footer: "Footer".to_string(),
+            },
+            part: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let td = TempDir::new().unwrap();
+        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
+        let file2_path = td.mkfile_with_contents("file3.txt", "World!");
+
+        let prompt = Quagga::new(td.path())
+            .parsed_template(simple_template())
+            .build()
+            .unwrap()
+            .render()
+            .unwrap();
+
+        assert_eq!(prompt.files, vec![file1_path.clone(), file2_path.clone()]);
+        assert_eq!(prompt.parts.len(), 1);
+
+        let expected = "\
+Header
+File: file1.txt
+Content:
+Hello
+---
+File: file3.txt
+Content:
+World!
+---
+Footer";
+
+        assert_eq!(prompt.parts[0], expected);
+    }
+
+    #[test]
+    fn test_render_with_builder_options() {
+        let td = TempDir::new().unwrap();
+        td.mkfile_with_contents("file1.rs", "let x = 1; // comment");
+        td.mkfile_with_contents("file2.md", "Skipped");
+
+        let prompt = Quagga::new(td.path())
+            .include(["*.rs"])
+            .remove_comments(true)
+            .parsed_template(Template {
+                prompt: PromptTemplate {
+                    file: "<file-content>".to_string(),
+                    ..Default::default()
+                },
+                part: Default::default(),
+            })
+            .build()
+            .unwrap()
+            .render()
+            .unwrap();
+
+        assert_eq!(prompt.files.len(), 1);
+        assert_eq!(prompt.parts, vec!["let x = 1;\n".to_string()]);
+    }
+
+    #[test]
+    fn test_render_with_content_transform() {
+        struct Uppercase;
+
+        impl ContentTransform for Uppercase {
+            fn name(&self) -> &str {
+                "uppercase"
+            }
+
+            fn transform(&self, file: FileContent, _report: &mut TransformReport) -> FileContent {
+                FileContent {
+                    content: file.content.to_uppercase(),
+                    path: file.path,
+                }
+            }
+        }
+
+        let td = TempDir::new().unwrap();
+        td.mkfile_with_contents("file1.txt", "hello\n\n\nworld");
+
+        let prompt = Quagga::new(td.path())
+            .transforms(["collapse-blank-lines".parse().unwrap()])
+            .content_transform(Uppercase)
+            .parsed_template(Template {
+                prompt: PromptTemplate {
+                    file: "<file-content>".to_string(),
+                    ..Default::default()
+                },
+                part: Default::default(),
+            })
+            .build()
+            .unwrap()
+            .render()
+            .unwrap();
+
+        assert_eq!(prompt.parts, vec!["HELLO\n\nWORLD\n".to_string()]);
+
+        assert_eq!(
+            prompt.savings,
+            vec![TransformSavings {
+                name: "collapse-blank-lines".to_string(),
+                files: 1,
+                chars: 1,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_render_with_paths() {
+        let td = TempDir::new().unwrap();
+        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
+        td.mkfile_with_contents("file2.txt", "Not included");
+
+        let quagga = Quagga::new(td.path())
+            .paths(Some(vec![file1_path.clone()]))
+            .parsed_template(simple_template())
+            .build()
+            .unwrap();
+
+        let prompt = quagga.render().unwrap();
+
+        assert_eq!(prompt.files, vec![file1_path]);
+        assert!(prompt.parts[0].contains("Hello"));
+        assert!(!prompt.parts[0].contains("Not included"));
+    }
+
+    #[test]
+    fn test_render_with_skipped_files() {
+        let td = TempDir::new().unwrap();
+        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
+        let file2_path = td.path().join("nonexistent.txt");
+
+        let prompt = Quagga::new(td.path())
+            .paths(Some(vec![file1_path.clone(), file2_path.clone()]))
+            .parsed_template(simple_template())
+            .build()
+            .unwrap()
+            .render()
+            .unwrap();
+
+        assert_eq!(prompt.files, vec![file1_path]);
+        assert_eq!(prompt.skipped.len(), 1);
+        assert_eq!(prompt.skipped[0].path, file2_path);
+    }
+
+    #[test]
+    fn test_render_with_skipped_files_on_error_fail() {
+        let td = TempDir::new().unwrap();
+        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
+        let file2_path = td.path().join("nonexistent.txt");
+
+        let result = Quagga::new(td.path())
+            .paths(Some(vec![file1_path, file2_path]))
+            .on_error(OnError::Fail)
+            .parsed_template(simple_template())
+            .build()
+            .unwrap()
+            .render();
+
+        assert!(matches!(result, Err(QuaggaError::FileMetadata { .. })));
+    }
+
+    #[test]
+    fn test_build_uses_template_from_options() {
+        let td = TempDir::new().unwrap();
+        td.mkfile_with_contents("file.txt", "Hello");
+
+        let template_path = td.mkfile_with_contents(
+            "template.md",
+            r#"
+<template>
+  <prompt>
+    <header>Custom Header</header>
+    <file><file-content></file>
+    <footer></footer>
+  </prompt>
+  <part>
+    <header></header>
+    <footer></footer>
+    <pending></pending>
+  </part>
+</template>
+"#,
+        );
+
+        let prompt = Quagga::new(td.path())
+            .include(["*.txt"])
+            .template(template_path)
+            .build()
+            .unwrap()
+            .render()
+            .unwrap();
+
+        assert_eq!(prompt.parts, vec!["Custom Header\nHello\n".to_string()]);
+    }
+
+    #[test]
+    fn test_build_with_invalid_template() {
+        let td = TempDir::new().unwrap();
+        let template_path = td.mkfile_with_contents("template.md", "No tags");
+
+        let result = Quagga::new(td.path()).template(template_path).build();
+
+        assert!(matches!(result, Err(QuaggaError::TemplateParse { .. })));
+    }
+
+    #[test]
+    fn test_render_lists_skipped_files_in_warn_mode() {
+        let td = TempDir::new().unwrap();
+        let file1_path = td.mkfile_with_contents("file1.txt", "Hello");
+        let file2_path = td.mkfile_with_bytes("file2.txt", &[0x00, 0xC0, 0xC1]); // Not text
+        let file3_path = td.path().join("nonexistent.txt");
+
+        let template = Template {
+            prompt: PromptTemplate {
+                header: "".to_string(),
+                file: "<file-content>".to_string(),
+                footer: "Files:\n<skipped-files>".to_string(),
+            },
+            part: Default::default(),
+        };
+
+        let render = |on_error| {
+            Quagga::new(td.path())
+                .paths(Some(vec![
+                    file1_path.clone(),
+                    file2_path.clone(),
+                    file3_path.clone(),
+                ]))
+                .on_error(on_error)
+                .parsed_template(template.clone())
+                .build()
+                .unwrap()
+                .render()
+                .unwrap()
+        };
+
+        let prompt = render(OnError::Warn);
+
+        assert_eq!(
+            prompt.parts,
+            vec![format!(
+                "Hello\nFiles:\nSkipped nonexistent.txt: {}\nSkipped file2.txt: stream did not contain text in a known encoding",
+                prompt.skipped[0].reason
+            )]
+        );
+
+        // The skipped files are not listed in the skip mode
+        let prompt = render(OnError::Skip);
+
+        assert_eq!(prompt.parts, vec!["Hello\nFiles:\n".to_string()]);
+        assert_eq!(prompt.skipped.len(), 2);
+    }
+
+    #[test]
+    fn test_render_total_size_exceeds_limit() {
+        let td = TempDir::new().unwrap();
+        td.mkfile_with_contents("file1.txt", "1234567890a"); // 11 bytes
+
+        let result = Quagga::new(td.path())
+            .max_total_size(10)
+            .parsed_template(simple_template())
+            .build()
+            .unwrap()
+            .render();
+
+        assert!(matches!(
+            result,
+            Err(QuaggaError::TotalSizeExceeded {
+                total: 11,
+                limit: 10
+            })
+        ));
+    }
+
+    #[test]
+    fn test_render_with_nonexistent_directory() {
+        let quagga = Quagga::new("/path/to/nonexistent/directory")
+            .parsed_template(Template::default())
+            .build()
+            .unwrap();
+
+        assert!(matches!(quagga.render(), Err(QuaggaError::Walk(_))));
+    }
+
+    #[test]
+    fn test_render_reads_archive_once_per_quagga() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip("project.zip", &[("a.txt", "A")]);
+        let modified = std::fs::metadata(&archive).unwrap().modified().unwrap();
+
+        let build = || {
+            Quagga::new(td.path())
+                .parsed_template(simple_template())
+                .paths(Some(vec![archive.clone()]))
+                .build()
+                .unwrap()
+        };
+
+        let quagga = build();
+        assert!(quagga.render().unwrap().parts[0].contains("Content:\nA\n"));
+
+        // Replace the archive with one of the same size and modification time
+        td.mkzip("project.zip", &[("a.txt", "B")]);
+        std::fs::File::options()
+            .write(true)
+            .open(&archive)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+
+        assert!(quagga.render().unwrap().parts[0].contains("Content:\nA\n"));
+        assert!(build().render().unwrap().parts[0].contains("Content:\nB\n"));
+    }
+}
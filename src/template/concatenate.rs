@@ -1,6 +1,9 @@
 use super::split::split_into_parts;
-use crate::cli::Cli;
+use crate::file::archive::ArchiveCache;
 use crate::file::file_content::FileContent;
+use crate::file::path_style::PathFormatter;
+use crate::file::skipped_file::SkippedFile;
+use crate::options::QuaggaOptions;
 use crate::template::tags::header_footer::process_header_footer;
 use crate::template::template::Template;
 use std::path::PathBuf;
@@ -11,22 +14,44 @@ use std::path::PathBuf;
 ///
 /// * `template` - A `Template` struct containing template structure.
 /// * `files` - A vector of `FileContent` structs.
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `skipped` - Files that were left out of the prompt because they could not be read.
 ///
 /// # Returns
 ///
 /// A `String` vector containing the output prompt content splitted into parts
-pub fn concatenate_files(template: Template, files: Vec<FileContent>, cli: &Cli) -> Vec<String> {
+pub fn concatenate_files(
+    template: Template,
+    files: Vec<FileContent>,
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    skipped: &[SkippedFile],
+) -> Vec<String> {
     let file_paths: Vec<PathBuf> = files.iter().map(|f| f.path.clone()).collect();
-    let header = process_header_footer(&template.prompt.header, &file_paths, &cli.root);
-    let files = apply_file_template(&template.prompt.file, &files);
-    let footer = process_header_footer(&template.prompt.footer, &file_paths, &cli.root);
+    let paths = PathFormatter::from_options(options);
+    let header = process_header_footer(
+        &template.prompt.header,
+        &file_paths,
+        &paths,
+        skipped,
+        archives,
+    );
+    let files = apply_file_template(&template.prompt.file, &files, &paths);
+    let footer = process_header_footer(
+        &template.prompt.footer,
+        &file_paths,
+        &paths,
+        skipped,
+        archives,
+    );
 
     split_into_parts(
         header,
         files,
         footer,
         template.part,
-        cli.max_part_size as usize,
+        options.max_part_size as usize,
     )
 }
 
@@ -36,26 +61,39 @@ pub fn concatenate_files(template: Template, files: Vec<FileContent>, cli: &Cli)
 ///
 /// * `item_template` - A `String` representing the item template.
 /// * `files` - A vector of `FileContent` structs.
+/// * `paths` - Formats the file paths in the `--path-style`.
 ///
 /// # Returns
 ///
 /// A `Vec<String>` containing the content of each file with the template applied.
-pub fn apply_file_template(item_template: &str, files: &Vec<FileContent>) -> Vec<String> {
+pub fn apply_file_template(
+    item_template: &str,
+    files: &[FileContent],
+    paths: &PathFormatter,
+) -> Vec<String> {
     files
         .iter()
-        .map(|file| {
-            item_template
-                .replace("<file-path>", &file.path.display().to_string())
-                .replace("<file-content>", &file.content)
-        })
+        .map(|file| render_file(item_template, file, paths))
         .collect()
 }
 
+/// Applies the file template to a single file.
+pub fn render_file(item_template: &str, file: &FileContent, paths: &PathFormatter) -> String {
+    item_template
+        .replace("<file-path>", &paths.display(&file.path))
+        .replace("<file-content>", &file.content)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::PathStyle;
+    use std::path::Path;
+
+    fn relative() -> PathFormatter {
+        PathFormatter::new(Path::new("."), PathStyle::Relative)
+    }
     use crate::template::template::{PromptTemplate, Template};
-    use clap::Parser;
     use std::path::PathBuf;
 
     #[test]
@@ -80,9 +118,9 @@ mod tests {
         };
 
         let files = vec![file1, file2];
-        let cli = Cli::parse_from(&["test"]);
+        let options = QuaggaOptions::default();
 
-        let result = concatenate_files(template, files, &cli);
+        let result = concatenate_files(template, files, &options, &ArchiveCache::default(), &[]);
 
         assert_eq!(result.len(), 1);
 
@@ -123,9 +161,9 @@ Footer";
             },
         ];
 
-        let cli = Cli::parse_from(&["test"]);
+        let options = QuaggaOptions::default();
 
-        let result = concatenate_files(template, files, &cli);
+        let result = concatenate_files(template, files, &options, &ArchiveCache::default(), &[]);
 
         assert_eq!(result.len(), 1);
 
@@ -155,7 +193,7 @@ file2.txt"#;
 
         let files = vec![file1, file2];
 
-        let result = apply_file_template(item_template, &files);
+        let result = apply_file_template(item_template, &files, &relative());
 
         assert_eq!(result.len(), 2);
 
@@ -1,6 +1,6 @@
-use std::error::Error;
+use crate::error::QuaggaError;
 use std::fs;
-use std::path::PathBuf;
+use std::path::Path;
 
 /// Copies the embedded default template to `.quagga_template` in the specified `root` directory.
 /// Returns a success message if copied, or an error if the file already exists.
@@ -13,15 +13,21 @@ use std::path::PathBuf;
 ///
 /// * `Ok(String)` with the success message.
 /// * `Err` if an error occurred or the file already exists.
-pub fn copy_template(root: &PathBuf) -> Result<String, Box<dyn Error>> {
+pub fn copy_template(root: &Path) -> Result<String, QuaggaError> {
     let template_content = include_str!("../../templates/default.md");
     let destination = root.join(".quagga_template");
 
     if destination.exists() {
-        return Err(format!("Template file '{}' already exists.", destination.display()).into());
+        return Err(QuaggaError::TemplateExists { path: destination });
+    }
+
+    if let Err(source) = fs::write(&destination, template_content) {
+        return Err(QuaggaError::Output {
+            path: destination,
+            source,
+        });
     }
 
-    fs::write(&destination, template_content)?;
     Ok(format!(
         "Template was copied to '{}'.",
         destination.display()
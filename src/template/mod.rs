@@ -4,5 +4,7 @@ pub mod parse;
 pub mod quagga_template;
 pub mod read;
 pub mod split;
+pub mod stream;
 pub mod tags;
+#[allow(clippy::module_inception)]
 pub mod template;
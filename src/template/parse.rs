@@ -1,5 +1,12 @@
+use crate::error::{QuaggaError, TemplateParseErrorKind};
 use crate::template::template::{PartTemplate, PromptTemplate, Template};
 
+/// Text enclosed in a tag, along with the template line number where the text starts.
+struct Section {
+    content: String,
+    line: usize,
+}
+
 /// Parses the entire template string into a `Template` struct.
 ///
 /// # Arguments
@@ -9,22 +16,24 @@ use crate::template::template::{PartTemplate, PromptTemplate, Template};
 /// # Returns
 ///
 /// * `Ok(Template)` containing the parsed template sections.
-/// * `Err(String)` with an error message if parsing fails.
-pub fn parse_template(text: &str) -> Result<Template, String> {
-    let template_content = text_inside_tag(text, "template")?;
-    let prompt_content = text_inside_tag(&template_content, "prompt")?;
-    let part_content = text_inside_tag(&template_content, "part")?;
-    let prompt = parse_prompt_section(&prompt_content)?;
-    let part = parse_part_section(&part_content)?;
+/// * `Err(QuaggaError::TemplateParse)` with the tag and line number if parsing fails.
+pub fn parse_template(text: &str) -> Result<Template, QuaggaError> {
+    let template_section = section_inside_tag(text, "template", 1)?;
+    let prompt_section =
+        section_inside_tag(&template_section.content, "prompt", template_section.line)?;
+    let part_section =
+        section_inside_tag(&template_section.content, "part", template_section.line)?;
+    let prompt = parse_prompt_section(&prompt_section)?;
+    let part = parse_part_section(&part_section)?;
     let template = Template { prompt, part };
 
     Ok(template)
 }
 
-fn parse_part_section(part_content: &str) -> Result<PartTemplate, String> {
-    let header = text_inside_tag(part_content, "header")?;
-    let footer = text_inside_tag(part_content, "footer")?;
-    let pending = text_inside_tag(part_content, "pending")?;
+fn parse_part_section(section: &Section) -> Result<PartTemplate, QuaggaError> {
+    let header = section_inside_tag(&section.content, "header", section.line)?.content;
+    let footer = section_inside_tag(&section.content, "footer", section.line)?.content;
+    let pending = section_inside_tag(&section.content, "pending", section.line)?.content;
 
     Ok(PartTemplate {
         header,
@@ -33,10 +42,10 @@ fn parse_part_section(part_content: &str) -> Result<PartTemplate, String> {
     })
 }
 
-fn parse_prompt_section(prompt_content: &str) -> Result<PromptTemplate, String> {
-    let header = text_inside_tag(prompt_content, "header")?;
-    let file = text_inside_tag(prompt_content, "file")?;
-    let footer = text_inside_tag(prompt_content, "footer")?;
+fn parse_prompt_section(section: &Section) -> Result<PromptTemplate, QuaggaError> {
+    let header = section_inside_tag(&section.content, "header", section.line)?.content;
+    let file = section_inside_tag(&section.content, "file", section.line)?.content;
+    let footer = section_inside_tag(&section.content, "footer", section.line)?.content;
 
     Ok(PromptTemplate {
         header,
@@ -56,29 +65,60 @@ fn parse_prompt_section(prompt_content: &str) -> Result<PromptTemplate, String>
 /// # Returns
 ///
 /// * `Ok(String)` containing the extracted section.
-/// * `Err(String)` with an error message if parsing fails.
-pub fn text_inside_tag(text: &str, tag: &str) -> Result<String, String> {
+/// * `Err(QuaggaError::TemplateParse)` if parsing fails.
+pub fn text_inside_tag(text: &str, tag: &str) -> Result<String, QuaggaError> {
+    Ok(section_inside_tag(text, tag, 1)?.content)
+}
+
+/// Extracts the content enclosed in a tag, see `text_inside_tag`.
+///
+/// # Arguments
+///
+/// * `text` - The text to parse.
+/// * `tag` - The specific tag name (e.g., "template" or "prompt").
+/// * `first_line` - The template line number on which the `text` starts, used in errors.
+///
+/// # Returns
+///
+/// * `Ok(Section)` containing the extracted text and the line number where it starts.
+/// * `Err(QuaggaError::TemplateParse)` if parsing fails.
+fn section_inside_tag(text: &str, tag: &str, first_line: usize) -> Result<Section, QuaggaError> {
     let opening_tag = format!("<{}>", tag);
     let closing_tag = format!("</{}>", tag);
 
+    let parse_error = |offset: usize, kind: TemplateParseErrorKind| QuaggaError::TemplateParse {
+        tag: tag.to_string(),
+        line: line_at(text, offset, first_line),
+        kind,
+    };
+
     let start = text
         .find(&opening_tag)
-        .ok_or_else(|| format!("Opening tag <{}> not found in the provided text.", tag))?;
+        .ok_or_else(|| parse_error(0, TemplateParseErrorKind::OpeningTagNotFound))?;
 
     let end = text
         .rfind(&closing_tag)
-        .ok_or_else(|| format!("Closing tag </{}> not found in the provided text.", tag))?;
+        .ok_or_else(|| parse_error(start, TemplateParseErrorKind::ClosingTagNotFound))?;
 
     if end < start {
-        return Err(format!(
-            "Closing tag </{}> found before opening tag <{}>.",
-            tag, tag
+        return Err(parse_error(
+            end,
+            TemplateParseErrorKind::ClosingTagBeforeOpening,
         ));
     }
 
     let content = &text[start + opening_tag.len()..end];
-    let trimmed_content = trim_indentation(content);
-    Ok(trimmed_content)
+
+    Ok(Section {
+        // Trimming indentation keeps the number of lines, so line numbers stay valid
+        content: trim_indentation(content),
+        line: line_at(text, start, first_line),
+    })
+}
+
+/// Returns the line number of the byte `offset` in the `text` that starts on `first_line`.
+fn line_at(text: &str, offset: usize, first_line: usize) -> usize {
+    first_line + text[..offset].matches('\n').count()
 }
 
 /// Removes the common leading whitespace from each line in the given content.
@@ -168,11 +208,40 @@ mod tests {
 
         assert!(result.is_err());
         assert_eq!(
-            result.unwrap_err(),
-            "Closing tag </prompt> not found in the provided text."
+            result.unwrap_err().to_string(),
+            "Template error on line 3: Closing tag </prompt> not found in the provided text."
         );
     }
 
+    #[test]
+    fn test_parse_template_error_line_in_nested_section() {
+        let text = r#"
+<template>
+  <prompt>
+    <header>Header</header>
+    <file>File</file>
+    <footer>Footer</footer>
+  </prompt>
+
+  <part>
+    <header>Part start</header>
+    <footer>Part end</footer>
+  </part>
+</template>
+"#;
+
+        let result = parse_template(text);
+
+        match result {
+            Err(QuaggaError::TemplateParse { tag, line, kind }) => {
+                assert_eq!(tag, "pending");
+                assert_eq!(line, 9);
+                assert_eq!(kind, TemplateParseErrorKind::OpeningTagNotFound);
+            }
+            _ => panic!("Expected template parse error"),
+        }
+    }
+
     #[test]
     fn test_text_inside_tag_success() {
         let text = "before
@@ -234,8 +303,8 @@ Line three
         let result = text_inside_tag(text, "template");
         assert!(result.is_err());
         assert_eq!(
-            result.unwrap_err(),
-            "Opening tag <template> not found in the provided text."
+            result.unwrap_err().to_string(),
+            "Template error on line 1: Opening tag <template> not found in the provided text."
         );
     }
 
@@ -245,8 +314,8 @@ Line three
         let result = text_inside_tag(text, "template");
         assert!(result.is_err());
         assert_eq!(
-            result.unwrap_err(),
-            "Closing tag </template> not found in the provided text."
+            result.unwrap_err().to_string(),
+            "Template error on line 1: Closing tag </template> not found in the provided text."
         );
     }
 
@@ -256,8 +325,8 @@ Line three
         let result = text_inside_tag(text, "template");
         assert!(result.is_err());
         assert_eq!(
-            result.unwrap_err(),
-            "Closing tag </template> found before opening tag <template>."
+            result.unwrap_err().to_string(),
+            "Template error on line 1: Closing tag </template> found before opening tag <template>."
         );
     }
 
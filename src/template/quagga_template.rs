@@ -23,13 +23,7 @@ pub fn quagga_template_path(
     }
 
     // Check home directory
-    let home_directory = if let Some(dir) = home_dir_override {
-        Some(dir)
-    } else if let Some(dir) = home_dir() {
-        Some(dir)
-    } else {
-        None
-    };
+    let home_directory = home_dir_override.or_else(home_dir);
 
     if let Some(home) = home_directory {
         let home_template = home.join(".quagga_template");
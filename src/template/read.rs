@@ -1,8 +1,9 @@
 use super::parse::parse_template;
 use super::quagga_template::quagga_template_path;
 use super::template::Template;
-use crate::cli::Cli;
-use std::error::Error;
+use crate::error::QuaggaError;
+use crate::file::archive::is_archive;
+use crate::options::QuaggaOptions;
 use std::fs;
 use std::path::PathBuf;
 
@@ -18,13 +19,13 @@ const DEFAULT_TEMPLATE: &str = include_str!("../../templates/default.md");
 /// # Arguments
 ///
 /// * `template_path` - An `Option<PathBuf>` specifying the path to the template file.
-///                     If `None`, the default embedded template is used.
+///   If `None`, the default embedded template is used.
 ///
 /// # Returns
 ///
 /// * `Ok(Template)` containing the parsed template components.
-/// * `Err<Box<dyn Error>>` if an error occurs during reading, validation, or parsing.
-pub fn read_and_parse_template(template_path: Option<PathBuf>) -> Result<Template, Box<dyn Error>> {
+/// * `Err<QuaggaError>` if an error occurs during reading, validation, or parsing.
+pub fn read_and_parse_template(template_path: Option<PathBuf>) -> Result<Template, QuaggaError> {
     let template_content = read_template(template_path)?;
     let template_content = template_content.replace("\r\n", "\n"); // Normalize line endings
     let template = parse_template(&template_content)?;
@@ -32,25 +33,35 @@ pub fn read_and_parse_template(template_path: Option<PathBuf>) -> Result<Templat
 }
 
 /// Retrieves the path to the curstom template:
-/// - If a custom template path is provided via the CLI, it is used.
+/// - If a custom template path is provided in the options, it is used.
 /// - Use `.quagga_template` file from the current or home directory,
-///   unless the `--no-quagga-template` command line option is used.
+///   unless the `no_quagga_template` option is used.
 ///
 /// # Arguments
 ///
-/// * `cli` - Command line arguments.
+/// * `options` - Options for generating the prompt.
 ///
 /// # Returns
 ///
 /// * An `Option<PathBuf>` containing the path to the custom template file if used.
-pub fn path_to_custom_template(cli: &Cli) -> Option<PathBuf> {
-    if let Some(path) = cli.template.clone() {
+pub fn path_to_custom_template(options: &QuaggaOptions) -> Option<PathBuf> {
+    if let Some(path) = options.template.clone() {
         Some(path) // Use the provided template from --template option
-    } else if cli.no_quagga_template {
+    } else if options.no_quagga_template {
         None
     } else {
-        // Use the .quagga_template file from the current or home directory
-        quagga_template_path(cli.root.clone(), None)
+        // Use the .quagga_template file from the current or home directory.
+        // When the root is an archive, look next to the archive.
+        let project_root = if is_archive(&options.root) {
+            match options.root.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+                _ => PathBuf::from("."),
+            }
+        } else {
+            options.root.clone()
+        };
+
+        quagga_template_path(project_root, None)
     }
 }
 
@@ -63,15 +74,12 @@ pub fn path_to_custom_template(cli: &Cli) -> Option<PathBuf> {
 /// # Returns
 ///
 /// * `Ok<String>` containing the template content.
-/// * `Err<Box<dyn Error>>` if an I/O error occurs while reading the template, including the path in the error message.
-pub fn read_template(template_path: Option<PathBuf>) -> Result<String, Box<dyn Error>> {
+/// * `Err<QuaggaError::TemplateRead>` if an I/O error occurs while reading the template.
+pub fn read_template(template_path: Option<PathBuf>) -> Result<String, QuaggaError> {
     match template_path {
-        Some(path) => fs::read_to_string(&path).map_err(|e| {
-            Box::new(std::io::Error::new(
-                e.kind(),
-                format!("Failed to read template from '{}': {}", path.display(), e),
-            )) as Box<dyn Error>
-        }),
+        Some(path) => {
+            fs::read_to_string(&path).map_err(|source| QuaggaError::TemplateRead { path, source })
+        }
         None => Ok(DEFAULT_TEMPLATE.to_string()),
     }
 }
@@ -80,7 +88,6 @@ pub fn read_template(template_path: Option<PathBuf>) -> Result<String, Box<dyn E
 mod tests {
     use super::*;
     use crate::test_utils::temp_dir::TempDir;
-    use clap::Parser;
 
     #[test]
     fn test_read_template_with_none() {
@@ -175,7 +182,7 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err().to_string(),
-            "Opening tag <template> not found in the provided text."
+            "Template error on line 1: Opening tag <template> not found in the provided text."
         );
     }
 
@@ -184,13 +191,12 @@ mod tests {
         let td = TempDir::new().unwrap();
         let custom_template_path = td.mkfile("custom_template.txt");
 
-        let cli = Cli::parse_from(&[
-            "quagga",
-            "--template",
-            custom_template_path.to_str().unwrap(),
-        ]);
+        let options = QuaggaOptions {
+            template: Some(custom_template_path.clone()),
+            ..Default::default()
+        };
 
-        let result = path_to_custom_template(&cli);
+        let result = path_to_custom_template(&options);
 
         assert_eq!(result.unwrap(), custom_template_path);
     }
@@ -200,10 +206,13 @@ mod tests {
         let td = TempDir::new().unwrap();
         td.mkfile(".quagga_template");
 
-        let mut cli = Cli::parse_from(&["quagga", "--no-quagga-template"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            no_quagga_template: true,
+            ..Default::default()
+        };
 
-        let result = path_to_custom_template(&cli);
+        let result = path_to_custom_template(&options);
 
         assert!(result.is_none());
     }
@@ -213,10 +222,12 @@ mod tests {
         let project_dir = TempDir::new().unwrap();
         let project_template_path = project_dir.mkfile(".quagga_template");
 
-        let mut cli = Cli::parse_from(&["quagga"]);
-        cli.root = project_dir.path_buf();
+        let options = QuaggaOptions {
+            root: project_dir.path_buf(),
+            ..Default::default()
+        };
 
-        let result = path_to_custom_template(&cli);
+        let result = path_to_custom_template(&options);
 
         assert_eq!(result.unwrap(), project_template_path);
     }
@@ -225,10 +236,12 @@ mod tests {
     fn test_path_to_custom_template_no_template_found() {
         let project_dir = TempDir::new().unwrap();
 
-        let mut cli = Cli::parse_from(&["quagga"]);
-        cli.root = project_dir.path_buf();
+        let options = QuaggaOptions {
+            root: project_dir.path_buf(),
+            ..Default::default()
+        };
 
-        let result = path_to_custom_template(&cli);
+        let result = path_to_custom_template(&options);
 
         assert!(result.is_none());
     }
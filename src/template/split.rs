@@ -1,4 +1,6 @@
+use crate::output::part_writer::{MemoryPartWriter, PartWriter};
 use crate::template::template::PartTemplate;
+use std::convert::Infallible;
 
 /// Splits the concatenated content into multiple parts based on the maximum allowed characters.
 ///
@@ -20,288 +22,385 @@ pub fn split_into_parts(
     part_template: PartTemplate,
     max_part_chars: usize,
 ) -> Vec<String> {
-    // Determine if all content fits in a single part
-    if fits_in_single_part(&header, &files, &footer, max_part_chars) {
-        // No need to split into parts
-        return assemble_single_part(&header, &files, &footer);
+    let planner = SplitPlanner::new(&header, &footer, &part_template, max_part_chars);
+    let sizes: Vec<FileSize> = files.iter().map(|file| planner.measure(file)).collect();
+    let plan = planner.plan(&sizes);
+    let mut writer = MemoryPartWriter::default();
+
+    let result: Result<(), Infallible> =
+        planner.write_parts(&plan, |index| Ok(files[index].clone()), &mut writer);
+
+    match result {
+        Ok(()) => writer.into_parts(),
+        Err(never) => match never {},
     }
+}
 
-    // Content does not fit into one part - split into multiple parts
-    let parts = create_split_plan(&header, &files, &footer, &part_template, max_part_chars);
-    assemble_multiple_parts(parts, &part_template, &header, &footer)
+/// The size of a single file in the prompt, after the file template is applied.
+/// The split plan is created from these sizes, so that the file contents
+/// do not need to be kept in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSize {
+    /// The number of characters in the file.
+    pub chars: usize,
+
+    /// The number of characters in each chunk, if the file is too large to fit
+    /// in a single part and is split at line boundaries. Empty otherwise.
+    pub chunks: Vec<usize>,
+
+    /// The number of lines in each chunk. Empty if the file is not split.
+    pub chunk_lines: Vec<usize>,
 }
 
-/// Represents the content of a single part.
-/// Each part can contain content of multiple files
-#[derive(Clone)]
-struct PartContent {
-    /// The file content that make up the part.
-    /// Each chunk is a string representing content form one file,
-    /// or a section of a file if the file is too large to fit in a single part.
-    file_chunks: Vec<String>,
+/// Describes how the files are divided into parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SplitPlan {
+    /// All files fit in a single part, which has no part header and footer.
+    Single { files: usize },
+
+    /// The files are split into parts with part headers and footers.
+    Multiple(Vec<PartPlan>),
 }
 
-/// Checks if the combined header, files, and footer fit within the max_part_chars.
-///
-/// # Arguments
-///
-/// * `header` - The global header string.
-/// * `files` - A reference to a vector of file contents.
-/// * `footer` - The global footer string.
-/// * `max_part_chars` - The maximum number of characters allowed per part.
-///
-/// # Returns
-///
-/// `true` if all content fits in a single part, `false` otherwise.
-fn fits_in_single_part(
-    header: &str,
-    files: &[String],
-    footer: &str,
-    max_part_chars: usize,
-) -> bool {
-    let mut total_length = files.iter().map(|f| f.chars().count() + 1).sum::<usize>();
+impl SplitPlan {
+    /// Returns the number of parts in the output.
+    pub fn total_parts(&self) -> usize {
+        match self {
+            SplitPlan::Single { .. } => 1,
+            SplitPlan::Multiple(parts) => parts.len(),
+        }
+    }
 
-    if !header.is_empty() {
-        total_length += header.chars().count() + 1;
+    /// Returns the files, or chunks of files, in each part.
+    pub fn part_items(&self) -> Vec<Vec<PartItem>> {
+        match self {
+            SplitPlan::Single { files } => vec![(0..*files).map(PartItem::File).collect()],
+            SplitPlan::Multiple(parts) => parts.iter().map(|part| part.items.clone()).collect(),
+        }
     }
+}
 
-    total_length += footer.chars().count();
+/// The content of a single part.
+/// Each part can contain multiple files, or chunks of files that are too large
+/// to fit in a single part.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PartPlan {
+    pub items: Vec<PartItem>,
+}
 
-    total_length <= max_part_chars
+/// A file, or a chunk of a file, in a part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PartItem {
+    /// The entire file with the given index.
+    File(usize),
+
+    /// A chunk of the file with the given index.
+    Chunk { file: usize, chunk: usize },
 }
 
-/// Assembles all content into a single part without part headers/footers.
-///
-/// # Arguments
-///
-/// * `header` - The global header string.
-/// * `files` - A reference to a vector of file contents.
-/// * `footer` - The global footer string.
-///
-/// # Returns
+/// Plans how the files are split into parts and writes the parts.
 ///
-/// A single part as a string.
-fn assemble_single_part(header: &str, files: &[String], footer: &str) -> Vec<String> {
-    let mut part = String::new();
+/// First, each file is measured with `measure` and the plan is created from the sizes
+/// with `plan`. Then the parts are written with `write_parts`, which requests
+/// the contents of the files one at a time.
+pub struct SplitPlanner<'a> {
+    header: &'a str,
+    footer: &'a str,
+    part_template: &'a PartTemplate,
+    max_part_chars: usize,
+    part_overhead: usize,
+}
 
-    if !header.is_empty() {
-        part.push_str(header);
-        part.push('\n');
+impl<'a> SplitPlanner<'a> {
+    /// Creates a planner.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - The global header string.
+    /// * `footer` - The global footer string.
+    /// * `part_template` - The part template containing part header, footer, and pending text.
+    /// * `max_part_chars` - The maximum number of characters allowed per part.
+    pub fn new(
+        header: &'a str,
+        footer: &'a str,
+        part_template: &'a PartTemplate,
+        max_part_chars: usize,
+    ) -> Self {
+        SplitPlanner {
+            header,
+            footer,
+            part_template,
+            max_part_chars,
+            part_overhead: calculate_part_overhead(part_template),
+        }
     }
 
-    for file in files {
-        part.push_str(file);
-        part.push('\n');
+    /// Measures the content of a file with the file template applied.
+    pub fn measure(&self, file: &str) -> FileSize {
+        let chars = file.chars().count();
+
+        let split = if self.is_large_file(chars) {
+            self.split_large_file(file)
+        } else {
+            Vec::new()
+        };
+
+        FileSize {
+            chars,
+            chunks: split.iter().map(|chunk| chunk.chars().count()).collect(),
+            chunk_lines: split
+                .iter()
+                .map(|chunk| chunk.split('\n').count())
+                .collect(),
+        }
     }
 
-    part.push_str(footer);
+    /// Creates a split plan determining how to divide files into parts.
+    ///
+    /// # Arguments
+    ///
+    /// * `files` - The sizes of the files, measured with `measure`.
+    ///
+    /// # Returns
+    ///
+    /// The `SplitPlan` with the files or chunks of files for each part.
+    pub fn plan(&self, files: &[FileSize]) -> SplitPlan {
+        if self.fits_in_single_part(files) {
+            return SplitPlan::Single { files: files.len() };
+        }
 
-    vec![part]
-}
+        let mut parts = Vec::new();
+        let mut current_part = PartPlan::default();
+        let mut current_part_size = 0;
+
+        for (index, file) in files.iter().enumerate() {
+            let file_length = self.calculate_file_length(files.len(), index, file.chars);
+
+            if current_part_size + file_length + self.part_overhead <= self.max_part_chars {
+                // File fits in the current part
+                current_part.items.push(PartItem::File(index));
+                current_part_size += file_length;
+            } else if self.is_large_file(file.chars) {
+                // File does not fit in any part, split it into chunks at line boundaries
+                for (chunk, chunk_chars) in file.chunks.iter().enumerate() {
+                    start_new_part_if_needed(&mut parts, &mut current_part, &mut current_part_size);
+                    current_part
+                        .items
+                        .push(PartItem::Chunk { file: index, chunk });
+                    current_part_size += chunk_chars + 1;
+                }
+            } else {
+                // File does not fit in the current part, move it to the next one
+                start_new_part_if_needed(&mut parts, &mut current_part, &mut current_part_size);
+                current_part.items.push(PartItem::File(index));
+                current_part_size += file.chars + 1;
+            }
+        }
 
-/// Creates a split plan determining how to divide files into parts.
-///
-/// # Arguments
-///
-/// * `header` - The global header string.
-/// * `files` - A reference to a vector of file contents.
-/// * `footer` - The global footer string.
-/// * `part_template` - The part template.
-/// * `max_part_chars` - Maximum characters per part.
-///
-/// # Returns
-///
-/// A vector of `PartContent` structs containing the file content for each part.
-fn create_split_plan(
-    header: &str,
-    files: &[String],
-    footer: &str,
-    part_template: &PartTemplate,
-    max_part_chars: usize,
-) -> Vec<PartContent> {
-    let part_overhead = calculate_part_overhead(part_template);
-    let mut parts = Vec::new();
-    let mut current_part_size = 0;
-
-    let mut current_part = PartContent {
-        file_chunks: Vec::new(),
-    };
-
-    for (i, file) in files.iter().enumerate() {
-        let file_length = calculate_file_length(header, footer, files, i, file);
-
-        if current_part_size + file_length + part_overhead > max_part_chars {
-            // File does not fit in the current part
-            handle_exceeding_size(
-                &mut parts,
-                &mut current_part,
-                &mut current_part_size,
-                file,
-                part_overhead,
-                footer.chars().count(),
-                header.chars().count(),
-                max_part_chars,
-            );
-        } else {
-            // File fits in the current part
-            add_file_to_part(&mut current_part, file, &mut current_part_size, file_length);
+        // Add the remaining part
+        if !current_part.items.is_empty() {
+            parts.push(current_part);
         }
+
+        SplitPlan::Multiple(parts)
     }
 
-    // Add the remaining part
-    if !current_part.file_chunks.is_empty() {
-        parts.push(current_part);
+    /// Writes the output text for all parts by concatenating the headers, file contents and footers.
+    ///
+    /// # Arguments
+    ///
+    /// * `plan` - The split plan created with `plan`.
+    /// * `file` - Returns the content of the file with the given index, with the file template applied.
+    ///   The files are requested in order, each file once.
+    /// * `writer` - Receives the parts.
+    ///
+    /// # Returns
+    ///
+    /// An error returned by `file` or `writer`.
+    pub fn write_parts<E, F, W>(
+        &self,
+        plan: &SplitPlan,
+        mut file: F,
+        writer: &mut W,
+    ) -> Result<(), E>
+    where
+        F: FnMut(usize) -> Result<String, E>,
+        W: PartWriter<Error = E>,
+    {
+        match plan {
+            SplitPlan::Single { files } => self.write_single_part(*files, &mut file, writer)?,
+            SplitPlan::Multiple(parts) => self.write_multiple_parts(parts, &mut file, writer)?,
+        }
+
+        writer.finish()
     }
 
-    parts
-}
+    /// Writes all content into a single part without part headers/footers.
+    fn write_single_part<E, F, W>(
+        &self,
+        files: usize,
+        file: &mut F,
+        writer: &mut W,
+    ) -> Result<(), E>
+    where
+        F: FnMut(usize) -> Result<String, E>,
+        W: PartWriter<Error = E>,
+    {
+        writer.begin_part(1, 1)?;
+
+        if !self.header.is_empty() {
+            writer.write(self.header)?;
+            writer.write("\n")?;
+        }
 
-/// Calculates the length of a file content including header and footer.
-///
-/// # Arguments
-///
-/// * `header` - The global header string.
-/// * `footer` - The global footer string.
-/// * `files` - The list of files.
-/// * `index` - Current file index.
-/// * `file` - Current file content.
-///
-/// # Returns
-///
-/// The total length of the file with header and footer.
-fn calculate_file_length(
-    header: &str,
-    footer: &str,
-    files: &[String],
-    index: usize,
-    file: &str,
-) -> usize {
-    let is_first = index == 0 && !header.is_empty();
-    let is_last = index == files.len() - 1 && !footer.is_empty();
-
-    let header_len = if is_first {
-        header.chars().count() + 1
-    } else {
-        0
-    };
-
-    let footer_len = if is_last {
-        footer.chars().count() + 1
-    } else {
-        0
-    };
-
-    header_len + file.chars().count() + 1 + footer_len // +1 for newline
-}
+        for index in 0..files {
+            writer.write(&file(index)?)?;
+            writer.write("\n")?;
+        }
 
-/// Handles the scenario where adding a file exceeds the maximum part size.
-/// It either splits the file or starts a new part.
-///
-/// # Arguments
-///
-/// * `parts` - The vector of parts.
-/// * `current_part` - The current part being assembled.
-/// * `current_size` - The current size of the part.
-/// * `file` - The file content.
-/// * `part_overhead` - Overhead coming from part header, footer, and pending text.
-/// * `footer_len` - Length of the footer.
-/// * `header_len` - Length of the header.
-/// * `max_size` - Maximum allowed size.
-fn handle_exceeding_size(
-    parts: &mut Vec<PartContent>,
-    current_part: &mut PartContent,
-    current_part_size: &mut usize,
-    file: &str,
-    part_overhead: usize,
-    footer_len: usize,
-    header_len: usize,
-    max_size: usize,
-) {
-    if file.chars().count() + part_overhead > max_size {
-        handle_large_file(
-            parts,
-            current_part,
-            current_part_size,
-            file,
-            part_overhead,
-            footer_len,
-            header_len,
-            max_size,
-        );
-    } else {
-        start_new_part_if_needed(parts, current_part, current_part_size);
-        add_file_to_part(
-            current_part,
-            file,
-            current_part_size,
-            file.chars().count() + 1,
-        );
+        writer.write(self.footer)?;
+        writer.end_part()
     }
-}
 
-// Splits file that is too large to fit in a single part into chunks at line boundaries.
-// and creates parts for each chunk.
-fn handle_large_file(
-    parts: &mut Vec<PartContent>,
-    current_part: &mut PartContent,
-    current_part_size: &mut usize,
-    file: &str,
-    part_overhead: usize,
-    footer_len: usize,
-    header_len: usize,
-    max_size: usize,
-) {
-    let max_chunk_size = max_size.saturating_sub(part_overhead + footer_len + header_len);
-    let chunks = split_file_by_lines(file, max_chunk_size);
+    /// Writes the parts with part headers, footers and pending texts.
+    fn write_multiple_parts<E, F, W>(
+        &self,
+        parts: &[PartPlan],
+        file: &mut F,
+        writer: &mut W,
+    ) -> Result<(), E>
+    where
+        F: FnMut(usize) -> Result<String, E>,
+        W: PartWriter<Error = E>,
+    {
+        let total_parts = parts.len();
+
+        // The chunks of the large file that is being written, which can span multiple parts
+        let mut chunks: Option<(usize, Vec<String>)> = None;
+
+        for (i, part) in parts.iter().enumerate() {
+            writer.begin_part(i + 1, total_parts)?;
+
+            // Add global header only in the first part
+            if i == 0 && !self.header.is_empty() {
+                writer.write(self.header)?;
+                writer.write("\n")?;
+            }
+
+            // Add part header
+            let part_header = replace_placeholders(&self.part_template.header, i + 1, total_parts);
+            writer.write(&part_header)?;
+            writer.write("\n")?;
+
+            // Add file chunks
+            for item in &part.items {
+                match *item {
+                    PartItem::File(index) => writer.write(&file(index)?)?,
+                    PartItem::Chunk { file: index, chunk } => {
+                        if !matches!(chunks, Some((chunks_file, _)) if chunks_file == index) {
+                            chunks = Some((index, self.split_large_file(&file(index)?)));
+                        }
+
+                        if let Some(text) = chunks.as_ref().and_then(|(_, c)| c.get(chunk)) {
+                            writer.write(text)?;
+                        }
+                    }
+                }
+
+                writer.write("\n")?;
+            }
+
+            // Add part footer
+            let part_footer = replace_placeholders(&self.part_template.footer, i + 1, total_parts);
+            writer.write(&part_footer)?;
+            writer.write("\n")?;
+
+            // Add pending text if not the last part
+            if i < total_parts - 1 && !self.part_template.pending.is_empty() {
+                let pending_text =
+                    replace_placeholders(&self.part_template.pending, i + 1, total_parts);
 
-    for chunk in chunks {
-        start_new_part_if_needed(parts, current_part, current_part_size);
-        add_chunk_to_part(current_part, &chunk, current_part_size);
+                writer.write(&pending_text)?;
+                writer.write("\n")?;
+            }
+
+            // Add global footer only in the last part
+            if i == total_parts - 1 && !self.footer.is_empty() {
+                writer.write(self.footer)?;
+                writer.write("\n")?;
+            }
+
+            writer.end_part()?;
+        }
+
+        Ok(())
     }
-}
 
-fn start_new_part_if_needed(
-    parts: &mut Vec<PartContent>,
-    current_part: &mut PartContent,
-    current_size: &mut usize,
-) {
-    if !current_part.file_chunks.is_empty() {
-        parts.push(current_part.clone());
-        *current_part = PartContent {
-            file_chunks: Vec::new(),
+    /// Checks if the combined header, files, and footer fit within the max_part_chars.
+    fn fits_in_single_part(&self, files: &[FileSize]) -> bool {
+        let mut total_length = files.iter().map(|f| f.chars + 1).sum::<usize>();
+
+        if !self.header.is_empty() {
+            total_length += self.header.chars().count() + 1;
+        }
+
+        total_length += self.footer.chars().count();
+
+        total_length <= self.max_part_chars
+    }
+
+    /// Calculates the length of a file content including the global header and footer
+    /// when the file is the first or the last one.
+    ///
+    /// # Arguments
+    ///
+    /// * `total_files` - The number of files.
+    /// * `index` - Current file index.
+    /// * `file_chars` - The number of characters in the current file.
+    fn calculate_file_length(&self, total_files: usize, index: usize, file_chars: usize) -> usize {
+        let is_first = index == 0 && !self.header.is_empty();
+        let is_last = index == total_files - 1 && !self.footer.is_empty();
+
+        let header_len = if is_first {
+            self.header.chars().count() + 1
+        } else {
+            0
         };
-        *current_size = 0;
+
+        let footer_len = if is_last {
+            self.footer.chars().count() + 1
+        } else {
+            0
+        };
+
+        header_len + file_chars + 1 + footer_len // +1 for newline
+    }
+
+    /// Checks if the file is too large to fit in a single part and needs to be split into chunks.
+    pub fn is_large_file(&self, file_chars: usize) -> bool {
+        file_chars + self.part_overhead > self.max_part_chars
+    }
+
+    /// Splits a file that is too large to fit in a single part into chunks at line boundaries.
+    fn split_large_file(&self, file: &str) -> Vec<String> {
+        let max_chunk_size = self.max_part_chars.saturating_sub(
+            self.part_overhead + self.footer.chars().count() + self.header.chars().count(),
+        );
+
+        split_file_by_lines(file, max_chunk_size)
     }
 }
 
-/// Adds a file to the current part.
-///
-/// # Arguments
-///
-/// * `current_part` - The current part being assembled.
-/// * `file` - The file content.
-/// * `current_size` - The current size of the part.
-/// * `file_length` - Length of the file to add.
-fn add_file_to_part(
-    current_part: &mut PartContent,
-    file: &str,
+/// Adds the current part to the list of parts and starts a new one, unless the current part is empty.
+fn start_new_part_if_needed(
+    parts: &mut Vec<PartPlan>,
+    current_part: &mut PartPlan,
     current_size: &mut usize,
-    file_length: usize,
 ) {
-    current_part.file_chunks.push(format!("{}\n", file));
-    *current_size += file_length;
-}
-
-/// Adds a chunk to the current part.
-///
-/// # Arguments
-///
-/// * `current_part` - The current part being assembled.
-/// * `chunk` - The file chunk.
-/// * `current_size` - The current size of the part.
-fn add_chunk_to_part(current_part: &mut PartContent, chunk: &str, current_size: &mut usize) {
-    current_part.file_chunks.push(format!("{}\n", chunk));
-    *current_size += chunk.chars().count() + 1;
+    if !current_part.items.is_empty() {
+        parts.push(std::mem::take(current_part));
+        *current_size = 0;
+    }
 }
 
 /// Splits a content of a large file that does not fit into a single part
@@ -324,12 +423,12 @@ fn split_file_by_lines(file_content: &str, max_chunk_size: usize) -> Vec<String>
         let line_chars = line.chars().count();
         let line_with_newline_chars = line_chars + 1;
 
-        if current_chunk_chars + line_with_newline_chars > max_chunk_size {
-            if !current_chunk.is_empty() {
-                chunks.push(current_chunk[..current_chunk.len() - 1].to_string());
-                current_chunk.clear();
-                current_chunk_chars = 0;
-            }
+        if current_chunk_chars + line_with_newline_chars > max_chunk_size
+            && !current_chunk.is_empty()
+        {
+            chunks.push(current_chunk[..current_chunk.len() - 1].to_string());
+            current_chunk.clear();
+            current_chunk_chars = 0;
         }
 
         current_chunk.push_str(line);
@@ -385,71 +484,6 @@ fn calculate_part_overhead(part_template: &PartTemplate) -> usize {
     overhead
 }
 
-/// Generates the output text for all parts by concatenating the headers, file contents and footers.
-///
-/// # Arguments
-///
-/// * `split_plan` - The SplitPlan struct.
-/// * `part_template` - The part_template
-/// * `header` - The global header string.
-/// * `footer` - The global footer string.
-///
-/// # Returns
-///
-/// A vector of assembled parts as strings.
-fn assemble_multiple_parts(
-    parts: Vec<PartContent>,
-    part_template: &PartTemplate,
-    header: &str,
-    footer: &str,
-) -> Vec<String> {
-    let mut assembled_parts: Vec<String> = Vec::new();
-    let total_parts = parts.len();
-
-    for (i, part) in parts.iter().enumerate() {
-        let mut part_content = String::new();
-
-        // Add global header only in the first part
-        if i == 0 && !header.is_empty() {
-            part_content.push_str(header);
-            part_content.push('\n');
-        }
-
-        // Add part header
-        let part_header = replace_placeholders(&part_template.header, i + 1, total_parts);
-        part_content.push_str(&part_header);
-        part_content.push('\n');
-
-        // Add file chunks
-        for chunk in &part.file_chunks {
-            part_content.push_str(chunk);
-        }
-
-        // Add part footer
-        let part_footer = replace_placeholders(&part_template.footer, i + 1, total_parts);
-        part_content.push_str(&part_footer);
-        part_content.push('\n');
-
-        // Add pending text if not the last part
-        if i < total_parts - 1 && !part_template.pending.is_empty() {
-            let pending_text = replace_placeholders(&part_template.pending, i + 1, total_parts);
-
-            part_content.push_str(&pending_text);
-            part_content.push('\n');
-        }
-
-        // Add global footer only in the last part
-        if i == total_parts - 1 && !footer.is_empty() {
-            part_content.push_str(footer);
-            part_content.push('\n');
-        }
-
-        assembled_parts.push(part_content.to_string());
-    }
-
-    assembled_parts
-}
-
 /// Replaces `<part-number>` and `<total-parts>` placeholders in the text.
 ///
 /// # Arguments
@@ -669,6 +703,37 @@ Footer
         assert_eq!(parts[2], expected);
     }
 
+    #[test]
+    fn test_measure_fills_chunks_up_to_the_limit() {
+        let part_template = PartTemplate {
+            header: "== Part <part-number> OF <total-parts> ==".to_string(),
+            footer: "== Part END <part-number> OF <total-parts> ==".to_string(),
+            pending: "This is only a part of the code (<parts-remaining> remaining)".to_string(),
+        };
+
+        // Ten lines of 51 characters with the line breaks
+        let file: String = (0..10)
+            .map(|i| format!("{}\n", i.to_string().repeat(50)))
+            .collect();
+        let file = file.trim_end();
+
+        // A chunk can have up to the maximum part size minus the part overhead (96 characters)
+        // and the lengths of the global header and footer: 312 - 96 - 12 = 204 characters,
+        // which is exactly four lines
+        let planner = SplitPlanner::new("Header", "Footer", &part_template, 312);
+        let size = planner.measure(file);
+
+        assert_eq!(size.chunks, vec![203, 203, 101]);
+        assert_eq!(size.chunk_lines, vec![4, 4, 2]);
+
+        // One character less leaves only three lines in a chunk
+        let planner = SplitPlanner::new("Header", "Footer", &part_template, 311);
+        let size = planner.measure(file);
+
+        assert_eq!(size.chunks, vec![152, 152, 152, 50]);
+        assert_eq!(size.chunk_lines, vec![3, 3, 3, 1]);
+    }
+
     #[test]
     fn test_split_into_parts_long_file_coming_after_small_files() {
         let header = "Header".to_string();
@@ -830,7 +895,7 @@ FooterFooterFooterFooterFooterFooterFooterFooterFooterFooter
 
         // Expecting a single part with header and footer only
         assert_eq!(parts.len(), 1);
-        let expected = format!("Header\nFooter");
+        let expected = "Header\nFooter".to_string();
         assert_eq!(parts[0], expected);
     }
 
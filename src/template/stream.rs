@@ -0,0 +1,555 @@
+use crate::error::QuaggaError;
+use crate::file::archive::ArchiveCache;
+use crate::file::file_content::FileContent;
+use crate::file::file_reader::{no_files_error, read_text_file, skipped_files_to_list};
+use crate::file::notebook::convert_notebook;
+use crate::file::path_style::PathFormatter;
+use crate::file::skipped_file::{skip_or_fail, SkippedFile};
+use crate::file::transform::{TransformPipeline, TransformReport};
+use crate::options::QuaggaOptions;
+use crate::output::part_writer::PartWriter;
+use crate::template::concatenate::render_file;
+use crate::template::split::{FileSize, PartItem, SplitPlanner};
+use crate::template::tags::header_footer::process_header_footer;
+use crate::template::template::Template;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The rendered files are kept in memory after the first pass until their total size
+/// reaches this many characters. The files after the limit are read again in the second pass.
+const MAX_KEPT_CHARS: usize = 32 * 1024 * 1024;
+
+/// A file, or a range of lines of a file, in a part of the prompt.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartFile {
+    pub path: PathBuf,
+
+    /// The lines of the file in the part, if the file is split between parts.
+    /// The lines are counted in the file content after the transforms.
+    pub lines: Option<LineRange>,
+}
+
+/// A range of lines, starting from 1. Both `start` and `end` are included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Reads the files and writes the prompt to the `writer` part by part, without keeping
+/// the contents of all files in memory at once.
+///
+/// The first pass reads and measures the files, so that the split plan can be created
+/// from the sizes. The second pass writes the files to the parts. The rendered files
+/// are kept from the first pass up to `MAX_KEPT_CHARS` characters in total, the files
+/// after the limit are read and transformed again in the second pass.
+///
+/// # Arguments
+///
+/// * `files` - The paths to the files, their total size should already be checked.
+/// * `template` - A `Template` struct containing the template sections.
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `pipeline` - The transforms applied to the contents of the files.
+/// * `skipped` - Files that were already skipped. The files that can not be read are added to it,
+///   unless `options.on_error` is `OnError::Fail`.
+/// * `report` - Collects what the transforms did to the files.
+/// * `writer` - Receives the parts of the prompt.
+///
+/// # Returns
+///
+/// The files in each part if the prompt was written, or a `QuaggaError` if there are no files to read,
+/// a file can not be read and `options.on_error` is `OnError::Fail`, a file has changed
+/// between the passes, or the output can not be written.
+#[allow(clippy::too_many_arguments)]
+pub fn stream_files<W>(
+    files: Vec<PathBuf>,
+    template: &Template,
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    pipeline: &TransformPipeline,
+    skipped: &mut Vec<SkippedFile>,
+    report: &mut TransformReport,
+    writer: &mut W,
+) -> Result<Vec<Vec<PartFile>>, QuaggaError>
+where
+    W: PartWriter<Error = QuaggaError>,
+{
+    let formatter = PathFormatter::from_options(options);
+    let renderer = FileRenderer {
+        item_template: &template.prompt.file,
+        options,
+        archives,
+        pipeline,
+        paths: &formatter,
+    };
+    let mut paths = Vec::new();
+    let mut file_chars = Vec::new();
+    let mut rendered = Vec::new();
+    let mut kept_chars = 0;
+
+    // First pass: measure the files, leaving out the ones that can not be read.
+    // The transforms are reported only in this pass, since some files can be read again.
+    for path in files {
+        match renderer.read_and_render(&path, report) {
+            Ok(text) => {
+                let chars = text.chars().count();
+                let keep = kept_chars + chars <= MAX_KEPT_CHARS;
+
+                if keep {
+                    kept_chars += chars;
+                }
+
+                rendered.push(keep.then_some(text));
+                file_chars.push(chars);
+                paths.push(path);
+            }
+            Err(error) => skip_or_fail(error, options.on_error, skipped)?,
+        }
+    }
+
+    if paths.is_empty() {
+        return Err(no_files_error(skipped));
+    }
+
+    let listed_skipped = skipped_files_to_list(options, skipped);
+    let header = process_header_footer(
+        &template.prompt.header,
+        &paths,
+        &formatter,
+        listed_skipped,
+        archives,
+    );
+
+    let footer = process_header_footer(
+        &template.prompt.footer,
+        &paths,
+        &formatter,
+        listed_skipped,
+        archives,
+    );
+
+    let planner = SplitPlanner::new(
+        &header,
+        &footer,
+        &template.part,
+        options.max_part_size as usize,
+    );
+
+    let mut sizes = Vec::new();
+
+    for (index, chars) in file_chars.into_iter().enumerate() {
+        if planner.is_large_file(chars) {
+            let size = match &rendered[index] {
+                Some(text) => planner.measure(text),
+                None => planner.measure(&renderer.read_and_render_unchanged(&paths[index], chars)?),
+            };
+
+            sizes.push(size);
+        } else {
+            sizes.push(FileSize {
+                chars,
+                chunks: Vec::new(),
+                chunk_lines: Vec::new(),
+            });
+        }
+    }
+
+    let plan = planner.plan(&sizes);
+
+    let part_files = plan
+        .part_items()
+        .into_iter()
+        .map(|items| {
+            items
+                .into_iter()
+                .map(|item| match item {
+                    PartItem::File(index) => PartFile {
+                        path: paths[index].clone(),
+                        lines: None,
+                    },
+                    PartItem::Chunk { file, chunk } => PartFile {
+                        path: paths[file].clone(),
+                        lines: Some(chunk_line_range(&template.prompt.file, &sizes[file], chunk)),
+                    },
+                })
+                .collect()
+        })
+        .collect();
+
+    // Second pass: write the files to the parts, reading again the ones that were not kept
+    planner.write_parts(
+        &plan,
+        |index| match rendered[index].take() {
+            Some(text) => Ok(text),
+            None => renderer.read_and_render_unchanged(&paths[index], sizes[index].chars),
+        },
+        writer,
+    )?;
+
+    Ok(part_files)
+}
+
+/// Returns the lines of the file content in the chunk of a file split between parts.
+/// The chunks are split from the file with the file template applied, so the lines
+/// added by the template before and after the content are left out.
+///
+/// # Arguments
+///
+/// * `item_template` - The file template, e.g. `<file-path>:\n<file-content>`.
+/// * `size` - The size of the file, with the number of lines in each chunk.
+/// * `chunk` - The index of the chunk.
+fn chunk_line_range(item_template: &str, size: &FileSize, chunk: usize) -> LineRange {
+    let (before, after) = item_template
+        .split_once("<file-content>")
+        .unwrap_or((item_template, ""));
+
+    let lines_before = before.matches('\n').count();
+    let lines_after = after.matches('\n').count();
+    let total_lines: usize = size.chunk_lines.iter().sum();
+    let content_lines = total_lines
+        .saturating_sub(lines_before + lines_after)
+        .max(1);
+
+    let start = size.chunk_lines[..chunk].iter().sum::<usize>() + 1;
+    let end = start + size.chunk_lines[chunk].saturating_sub(1);
+    let to_content_line = |line: usize| line.saturating_sub(lines_before).clamp(1, content_lines);
+
+    LineRange {
+        start: to_content_line(start),
+        end: to_content_line(end),
+    }
+}
+
+/// Reads the files and prepares them for the prompt.
+struct FileRenderer<'a> {
+    item_template: &'a str,
+    options: &'a QuaggaOptions,
+    archives: &'a ArchiveCache,
+    pipeline: &'a TransformPipeline,
+    paths: &'a PathFormatter,
+}
+
+impl FileRenderer<'_> {
+    /// Reads the file, applies the transforms and the file template.
+    /// What the transforms did is added to the `report`.
+    fn read_and_render(
+        &self,
+        path: &Path,
+        report: &mut TransformReport,
+    ) -> Result<String, QuaggaError> {
+        let options = self.options;
+        let content = read_text_file(
+            path.to_path_buf(),
+            options.binary,
+            options.encoding,
+            self.archives,
+        )?;
+
+        let file = FileContent {
+            path: path.to_path_buf(),
+            content: convert_notebook(path, content, options.notebook_outputs),
+        };
+
+        let file = self.pipeline.apply(file, report);
+        Ok(render_file(self.item_template, &file, self.paths))
+    }
+
+    /// Reads and renders the file like `read_and_render`, checking that its size
+    /// has not changed since it was measured.
+    fn read_and_render_unchanged(
+        &self,
+        path: &Path,
+        expected_chars: usize,
+    ) -> Result<String, QuaggaError> {
+        let text = self.read_and_render(path, &mut TransformReport::default())?;
+
+        if text.chars().count() != expected_chars {
+            return Err(QuaggaError::FileRead {
+                path: path.to_path_buf(),
+                source: io::Error::other("file changed while the prompt was being written"),
+            });
+        }
+
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::OnError;
+    use crate::quagga::Quagga;
+    use crate::template::template::{PartTemplate, PromptTemplate};
+    use crate::test_utils::temp_dir::TempDir;
+
+    /// Collects the streamed parts in memory.
+    #[derive(Default)]
+    struct TestWriter {
+        parts: Vec<String>,
+
+        /// Files removed before the first part is written.
+        remove_files: Vec<PathBuf>,
+    }
+
+    impl PartWriter for TestWriter {
+        type Error = QuaggaError;
+
+        fn begin_part(&mut self, _number: usize, _total: usize) -> Result<(), QuaggaError> {
+            for path in self.remove_files.drain(..) {
+                std::fs::remove_file(path).unwrap();
+            }
+
+            self.parts.push(String::new());
+            Ok(())
+        }
+
+        fn write(&mut self, text: &str) -> Result<(), QuaggaError> {
+            self.parts.last_mut().unwrap().push_str(text);
+            Ok(())
+        }
+
+        fn end_part(&mut self) -> Result<(), QuaggaError> {
+            Ok(())
+        }
+    }
+
+    fn template() -> Template {
+        Template {
+            prompt: PromptTemplate {
+                header: "Files: <all-file-paths>".to_string(),
+                file: "<file-path>:\n<file-content>".to_string(),
+                footer: "Footer".to_string(),
+            },
+            part: PartTemplate {
+                header: "== Part <part-number> of <total-parts> ==".to_string(),
+                footer: "== End of part <part-number> ==".to_string(),
+                pending: "Wait for the next part".to_string(),
+            },
+        }
+    }
+
+    fn stream(files: Vec<PathBuf>, options: &QuaggaOptions) -> Result<Vec<String>, QuaggaError> {
+        let mut writer = TestWriter::default();
+        stream_files(
+            files,
+            &template(),
+            options,
+            &ArchiveCache::default(),
+            &TransformPipeline::from_options(options)?,
+            &mut Vec::new(),
+            &mut TransformReport::default(),
+            &mut writer,
+        )?;
+        Ok(writer.parts)
+    }
+
+    #[test]
+    fn test_stream_files_returns_files_in_parts() {
+        let td = TempDir::new().unwrap();
+        let long_file: String = (1..=30).map(|i| format!("Line {}\n", i)).collect();
+
+        let files = vec![
+            td.mkfile_with_contents("file1.txt", "Hello"),
+            td.mkfile_with_contents("file2.txt", &long_file),
+        ];
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            max_part_size: 200,
+            ..Default::default()
+        };
+
+        let mut writer = TestWriter::default();
+
+        let part_files = stream_files(
+            files.clone(),
+            &template(),
+            &options,
+            &ArchiveCache::default(),
+            &TransformPipeline::default(),
+            &mut Vec::new(),
+            &mut TransformReport::default(),
+            &mut writer,
+        )
+        .unwrap();
+
+        assert_eq!(part_files.len(), writer.parts.len());
+        assert_eq!(
+            part_files[0][0],
+            PartFile {
+                path: files[0].clone(),
+                lines: None
+            }
+        );
+
+        let ranges: Vec<LineRange> = part_files
+            .iter()
+            .flatten()
+            .filter_map(|file| file.lines)
+            .collect();
+
+        assert!(ranges.len() > 1);
+        assert_eq!(ranges.first().unwrap().start, 1);
+        assert_eq!(ranges.last().unwrap().end, 30);
+
+        // The ranges are consecutive and match the lines in the parts
+        for pair in ranges.windows(2) {
+            assert_eq!(pair[1].start, pair[0].end + 1);
+        }
+
+        for (part, files) in writer.parts.iter().zip(&part_files) {
+            for range in files.iter().filter_map(|file| file.lines) {
+                assert!(part.contains(&format!("Line {}\n", range.end)));
+                assert!(part.contains(&format!("Line {}\n", range.start)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunk_line_range() {
+        let size = FileSize {
+            chars: 0,
+            chunks: vec![0, 0, 0],
+            chunk_lines: vec![4, 5, 3],
+        };
+
+        // The first line of the first chunk is the file path
+        let template = "<file-path>:\n<file-content>\n---";
+
+        assert_eq!(
+            chunk_line_range(template, &size, 0),
+            LineRange { start: 1, end: 3 }
+        );
+
+        assert_eq!(
+            chunk_line_range(template, &size, 1),
+            LineRange { start: 4, end: 8 }
+        );
+
+        // The last line of the last chunk is the `---` line
+        assert_eq!(
+            chunk_line_range(template, &size, 2),
+            LineRange { start: 9, end: 10 }
+        );
+    }
+
+    #[test]
+    fn test_stream_files_matches_in_memory_output() {
+        let td = TempDir::new().unwrap();
+        let long_file: String = (0..30).map(|i| format!("Line {}\n", i)).collect();
+
+        let files = vec![
+            td.mkfile_with_contents("file1.txt", "Hello"),
+            td.mkfile_with_contents("file2.txt", &long_file),
+            td.mkfile_with_contents("file3.txt", "World"),
+        ];
+
+        for max_part_size in [50, 200, 100_000] {
+            let options = QuaggaOptions {
+                root: td.path_buf(),
+                max_part_size,
+                ..Default::default()
+            };
+
+            let expected = Quagga::with_options(options.clone())
+                .paths(Some(files.clone()))
+                .parsed_template(template())
+                .build()
+                .unwrap()
+                .render()
+                .unwrap()
+                .parts;
+
+            assert_eq!(stream(files.clone(), &options).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_stream_files_does_not_read_files_again() {
+        let td = TempDir::new().unwrap();
+        let long_file: String = (0..30).map(|i| format!("Line {}\n", i)).collect();
+
+        let files = vec![
+            td.mkfile_with_contents("file1.txt", "Hello"),
+            td.mkfile_with_contents("file2.txt", &long_file),
+        ];
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            max_part_size: 100,
+            ..Default::default()
+        };
+
+        let expected = stream(files.clone(), &options).unwrap();
+
+        // The files are written from the first pass, so removing them does not fail
+        let mut writer = TestWriter {
+            remove_files: files.clone(),
+            ..Default::default()
+        };
+
+        stream_files(
+            files,
+            &template(),
+            &options,
+            &ArchiveCache::default(),
+            &TransformPipeline::default(),
+            &mut Vec::new(),
+            &mut TransformReport::default(),
+            &mut writer,
+        )
+        .unwrap();
+
+        assert_eq!(writer.parts, expected);
+    }
+
+    #[test]
+    fn test_stream_files_skips_unreadable_files() {
+        let td = TempDir::new().unwrap();
+        let file = td.mkfile_with_contents("file.txt", "Hello");
+        let missing = td.path().join("missing.txt");
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            on_error: OnError::Skip,
+            ..Default::default()
+        };
+
+        let mut skipped = Vec::new();
+        let mut writer = TestWriter::default();
+
+        stream_files(
+            vec![file.clone(), missing.clone()],
+            &template(),
+            &options,
+            &ArchiveCache::default(),
+            &TransformPipeline::default(),
+            &mut skipped,
+            &mut TransformReport::default(),
+            &mut writer,
+        )
+        .unwrap();
+
+        let parts = writer.parts;
+        assert_eq!(parts.len(), 1);
+        assert!(parts[0].contains("Hello"));
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, missing);
+    }
+
+    #[test]
+    fn test_stream_files_no_files() {
+        let td = TempDir::new().unwrap();
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            on_error: OnError::Skip,
+            ..Default::default()
+        };
+
+        let result = stream(vec![td.path().join("missing.txt")], &options);
+
+        assert!(matches!(result, Err(QuaggaError::NoFiles { .. })));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::file::path_style::PathFormatter;
 use crate::info::show_paths::format_file_paths;
 use std::path::PathBuf;
 
@@ -7,13 +8,18 @@ use std::path::PathBuf;
 ///
 /// * `text` - The input string which may contain the `<all-file-paths>` tag.
 /// * `file_paths` - A list of file paths to be included in the output.
+/// * `paths` - Formats the file paths in the `--path-style`.
 ///
 /// # Returns
 ///
 /// A new string where the `<all-file-paths>` tag is replaced with the formatted file paths.
-pub fn replace_all_file_paths_tag(text: &str, file_paths: Vec<PathBuf>) -> String {
+pub fn replace_all_file_paths_tag(
+    text: &str,
+    file_paths: Vec<PathBuf>,
+    paths: &PathFormatter,
+) -> String {
     if text.contains("<all-file-paths>") {
-        let formatted_paths = format_file_paths(file_paths);
+        let formatted_paths = format_file_paths(file_paths, paths);
         text.replace("<all-file-paths>", &formatted_paths)
     } else {
         text.to_string() // Return unchanged text if the tag is not present
@@ -23,6 +29,12 @@ pub fn replace_all_file_paths_tag(text: &str, file_paths: Vec<PathBuf>) -> Strin
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::PathStyle;
+    use std::path::Path;
+
+    fn relative() -> PathFormatter {
+        PathFormatter::new(Path::new("."), PathStyle::Relative)
+    }
     use std::path::PathBuf;
 
     #[test]
@@ -33,7 +45,7 @@ mod tests {
         Footer";
 
         let file_paths = vec![PathBuf::from("file1.txt"), PathBuf::from("file2.txt")];
-        let result = replace_all_file_paths_tag(template, file_paths);
+        let result = replace_all_file_paths_tag(template, file_paths, &relative());
 
         assert!(result.contains("file1.txt\nfile2.txt"));
         assert!(result.contains("Header"));
@@ -47,7 +59,7 @@ mod tests {
       Footer";
 
         let file_paths = vec![PathBuf::from("file1.txt"), PathBuf::from("file2.txt")];
-        let result = replace_all_file_paths_tag(template, file_paths);
+        let result = replace_all_file_paths_tag(template, file_paths, &relative());
 
         assert_eq!(result, template);
     }
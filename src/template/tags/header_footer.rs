@@ -1,4 +1,8 @@
+use crate::file::archive::ArchiveCache;
+use crate::file::path_style::PathFormatter;
+use crate::file::skipped_file::SkippedFile;
 use crate::template::tags::all_file_paths::replace_all_file_paths_tag;
+use crate::template::tags::skipped_files::replace_skipped_files_tag;
 use crate::template::tags::total_file_size::replace_total_file_size_tag;
 use crate::template::tags::tree::replace_tree_tag;
 use std::path::PathBuf;
@@ -9,25 +13,35 @@ use std::path::PathBuf;
 ///
 /// * `text` - The header or footer text that may contain tags.
 /// * `file_paths` - A slice of `PathBuf` representing the file paths.
-/// * `root` - The root path used for tree representation.
+/// * `paths` - Formats the file paths in the `--path-style`.
+/// * `skipped` - Files that were left out of the prompt because they could not be read.
+/// * `archives` - The cache of the archives read during the run.
 ///
 /// # Returns
 ///
 /// A `String` with all tags replaced.
-pub fn process_header_footer(text: &str, file_paths: &[PathBuf], root: &PathBuf) -> String {
+pub fn process_header_footer(
+    text: &str,
+    file_paths: &[PathBuf],
+    paths: &PathFormatter,
+    skipped: &[SkippedFile],
+    archives: &ArchiveCache,
+) -> String {
     if text.is_empty() {
         return "".to_string();
     }
 
     let mut processed_text = text.to_string();
-    processed_text = replace_all_file_paths_tag(&processed_text, file_paths.to_vec());
-    processed_text = replace_tree_tag(&processed_text, file_paths.to_vec(), root.clone());
-    replace_total_file_size_tag(&processed_text, file_paths.to_vec())
+    processed_text = replace_all_file_paths_tag(&processed_text, file_paths.to_vec(), paths);
+    processed_text = replace_tree_tag(&processed_text, file_paths.to_vec(), paths);
+    processed_text = replace_skipped_files_tag(&processed_text, skipped, paths);
+    replace_total_file_size_tag(&processed_text, file_paths.to_vec(), archives)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::PathStyle;
     use crate::test_utils::temp_dir::TempDir;
     use std::fs::File;
     use std::io::Write;
@@ -53,7 +67,13 @@ Files:<all-file-paths>
 Tree: <tree>
 Total Size: <total-file-size>"#;
 
-        let result = process_header_footer(&text, &file_paths, &root);
+        let result = process_header_footer(
+            text,
+            &file_paths,
+            &PathFormatter::new(&root, PathStyle::Absolute),
+            &[],
+            &ArchiveCache::default(),
+        );
 
         // File list
         assert!(result.contains("file1.txt"));
@@ -1,4 +1,5 @@
 pub mod all_file_paths;
 pub mod header_footer;
+pub mod skipped_files;
 pub mod total_file_size;
 pub mod tree;
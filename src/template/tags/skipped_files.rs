@@ -0,0 +1,71 @@
+use crate::file::path_style::PathFormatter;
+use crate::file::skipped_file::SkippedFile;
+
+/// Replaces the `<skipped-files>` tag in the given text with the files that were
+/// left out of the prompt because they could not be read, along with the reasons.
+///
+/// # Arguments
+///
+/// * `text` - The input string which may contain the `<skipped-files>` tag.
+/// * `skipped` - The files that were skipped.
+/// * `paths` - Formats the file paths in the `--path-style`.
+///
+/// # Returns
+///
+/// A new string where the `<skipped-files>` tag is replaced with the skipped files, one per line
+/// as `Skipped path: reason`, or with nothing if no files were skipped.
+pub fn replace_skipped_files_tag(
+    text: &str,
+    skipped: &[SkippedFile],
+    paths: &PathFormatter,
+) -> String {
+    if text.contains("<skipped-files>") {
+        let skipped: Vec<String> = skipped
+            .iter()
+            .map(|file| format!("Skipped {}: {}", paths.display(&file.path), file.reason))
+            .collect();
+
+        text.replace("<skipped-files>", &skipped.join("\n"))
+    } else {
+        text.to_string() // Return unchanged text if the tag is not present
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::PathStyle;
+    use std::path::Path;
+
+    fn relative() -> PathFormatter {
+        PathFormatter::new(Path::new("."), PathStyle::Relative)
+    }
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_replace_skipped_files_tag() {
+        let skipped = vec![SkippedFile {
+            path: PathBuf::from("secret.txt"),
+            reason: "Permission denied".to_string(),
+        }];
+
+        let result =
+            replace_skipped_files_tag("Files:\n<skipped-files>\nEnd", &skipped, &relative());
+
+        assert_eq!(result, "Files:\nSkipped secret.txt: Permission denied\nEnd");
+    }
+
+    #[test]
+    fn test_replace_skipped_files_tag_no_skipped_files() {
+        let result = replace_skipped_files_tag("Files: <skipped-files>", &[], &relative());
+
+        assert_eq!(result, "Files: ");
+    }
+
+    #[test]
+    fn test_replace_skipped_files_tag_not_present() {
+        let result = replace_skipped_files_tag("No tag here.", &[], &relative());
+
+        assert_eq!(result, "No tag here.");
+    }
+}
@@ -1,3 +1,4 @@
+use crate::file::archive::ArchiveCache;
 use crate::file::size::{calculate_total_size, human_readable_size};
 use std::path::PathBuf;
 
@@ -7,13 +8,18 @@ use std::path::PathBuf;
 ///
 /// * `text` - The input string that may contain the `<total-file-size>` tag.
 /// * `file_paths` - A list of file paths whose sizes will be summed.
+/// * `archives` - The cache of the archives read during the run.
 ///
 /// # Returns
 ///
 /// A new string where the `<total-file-size>` tag is replaced with the total file size in a human-readable format.
-pub fn replace_total_file_size_tag(text: &str, file_paths: Vec<PathBuf>) -> String {
+pub fn replace_total_file_size_tag(
+    text: &str,
+    file_paths: Vec<PathBuf>,
+    archives: &ArchiveCache,
+) -> String {
     if text.contains("<total-file-size>") {
-        match calculate_total_size(file_paths) {
+        match calculate_total_size(file_paths, archives) {
             Ok(total_size) => {
                 let readable_size = human_readable_size(total_size);
                 text.replace("<total-file-size>", &readable_size)
@@ -48,7 +54,7 @@ mod tests {
         let template = "Total size: <total-file-size>";
         let file_paths = vec![file1_path, file2_path];
 
-        let result = replace_total_file_size_tag(template, file_paths);
+        let result = replace_total_file_size_tag(template, file_paths, &ArchiveCache::default());
         assert_eq!(result, "Total size: 3 KB");
     }
 
@@ -56,7 +62,7 @@ mod tests {
     fn test_replace_total_file_size_tag_not_present() {
         let template = "No size info here.";
         let file_paths = vec![];
-        let result = replace_total_file_size_tag(template, file_paths);
+        let result = replace_total_file_size_tag(template, file_paths, &ArchiveCache::default());
         assert_eq!(result, "No size info here.");
     }
 
@@ -66,7 +72,7 @@ mod tests {
         let invalid_path = PathBuf::from("/invalid/path.txt");
         let file_paths = vec![invalid_path];
 
-        let result = replace_total_file_size_tag(template, file_paths);
+        let result = replace_total_file_size_tag(template, file_paths, &ArchiveCache::default());
         assert_eq!(result, template);
     }
 }
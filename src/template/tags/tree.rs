@@ -1,3 +1,4 @@
+use crate::file::path_style::PathFormatter;
 use crate::info::tree::file_paths_to_tree;
 use std::path::PathBuf;
 
@@ -7,13 +8,14 @@ use std::path::PathBuf;
 ///
 /// * `text` - The input string which may contain the `<tree>` tag.
 /// * `file_paths` - A list of file paths to be included in the output.
+/// * `paths` - Formats the file paths and the root in the `--path-style`.
 ///
 /// # Returns
 ///
 /// A new string where the `<tree>` tag is replaced with the formatted file paths.
-pub fn replace_tree_tag(text: &str, file_paths: Vec<PathBuf>, root: PathBuf) -> String {
+pub fn replace_tree_tag(text: &str, file_paths: Vec<PathBuf>, paths: &PathFormatter) -> String {
     if text.contains("<tree>") {
-        let tree = file_paths_to_tree(file_paths, Some(root));
+        let tree = file_paths_to_tree(file_paths, Some(paths));
         text.replace("<tree>", &tree)
     } else {
         text.to_string() // Return unchanged text if the tag is not present
@@ -23,6 +25,7 @@ pub fn replace_tree_tag(text: &str, file_paths: Vec<PathBuf>, root: PathBuf) ->
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::PathStyle;
     use std::path::PathBuf;
 
     #[test]
@@ -37,7 +40,11 @@ mod tests {
             PathBuf::from("./file2.txt"),
         ];
         let root: PathBuf = PathBuf::from(".");
-        let result = replace_tree_tag(template, file_paths, root);
+        let result = replace_tree_tag(
+            template,
+            file_paths,
+            &PathFormatter::new(&root, PathStyle::Relative),
+        );
 
         let expected = r#"Header
 .
@@ -57,7 +64,11 @@ Footer"#;
 
         let file_paths = vec![PathBuf::from("./file1.txt"), PathBuf::from("./file2.txt")];
         let root: PathBuf = PathBuf::from(".");
-        let result = replace_tree_tag(template, file_paths, root);
+        let result = replace_tree_tag(
+            template,
+            file_paths,
+            &PathFormatter::new(&root, PathStyle::Relative),
+        );
 
         assert_eq!(result, template);
     }
@@ -1,21 +1,14 @@
+use serde::Serialize;
+
 /// Represents the entire template structure.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize)]
 pub struct Template {
     pub prompt: PromptTemplate,
     pub part: PartTemplate,
 }
 
-impl Default for Template {
-    fn default() -> Self {
-        Template {
-            prompt: PromptTemplate::default(),
-            part: PartTemplate::default(),
-        }
-    }
-}
-
 /// Represents the prompt section, including header, file template, and footer.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PromptTemplate {
     pub header: String,
     pub file: String,
@@ -33,7 +26,7 @@ impl Default for PromptTemplate {
 }
 
 /// Represents the part section for multi-part outputs.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct PartTemplate {
     pub header: String,
     pub footer: String,
@@ -1,9 +1,13 @@
 // This code is based on `ignore` crate https://github.com/BurntSushi/ripgrep/tree/master/crates/ignore
+use flate2::write::GzEncoder;
+use flate2::Compression;
 use std::env;
 use std::fs;
 use std::io::Write;
 use std::io::{self, Result};
 use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::ZipWriter;
 
 /// A simple wrapper for creating a temporary directory that is
 /// automatically deleted when it's dropped.
@@ -33,15 +37,11 @@ impl TempDir {
                 continue;
             }
             fs::create_dir_all(&path).map_err(|e| {
-                io::Error::new(
-                    io::ErrorKind::Other,
-                    format!("failed to create {}: {}", path.display(), e),
-                )
+                io::Error::other(format!("failed to create {}: {}", path.display(), e))
             })?;
             return Ok(TempDir(path));
         }
-        Err(io::Error::new(
-            io::ErrorKind::Other,
+        Err(io::Error::other(
             "failed to create temp dir after 100 tries",
         ))
     }
@@ -71,7 +71,7 @@ impl TempDir {
     ///
     /// A `PathBuf` representing the full path to the created file.
     pub fn mkfile<P: AsRef<Path>>(&self, path: P) -> PathBuf {
-        return self.mkfile_with_contents(path, "contents"); // Call the new method with default content
+        self.mkfile_with_contents(path, "contents") // Call the new method with default content
     }
 
     /// Creates a file with the specified contents in the temporary directory.
@@ -108,18 +108,71 @@ impl TempDir {
         full_path
     }
 
+    /// Creates a zip archive with the specified files in the temporary directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The relative path within the temporary directory for the new archive.
+    /// * `files` - The paths of the files inside the archive and their contents.
+    ///
+    /// # Returns
+    ///
+    /// A `PathBuf` representing the full path to the created archive.
+    pub fn mkzip<P: AsRef<Path>>(&self, path: P, files: &[(&str, &str)]) -> PathBuf {
+        let full_path = self.path().join(path);
+        let mut zip = ZipWriter::new(fs::File::create(&full_path).unwrap());
+
+        for (name, contents) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+
+        zip.finish().unwrap();
+        full_path
+    }
+
+    /// Creates a gzip-compressed tar archive with the specified files in the temporary directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The relative path within the temporary directory for the new archive.
+    /// * `files` - The paths of the files inside the archive and their contents.
+    ///
+    /// # Returns
+    ///
+    /// A `PathBuf` representing the full path to the created archive.
+    pub fn mktar_gz<P: AsRef<Path>>(&self, path: P, files: &[(&str, &str)]) -> PathBuf {
+        let full_path = self.path().join(path);
+        let file = fs::File::create(&full_path).unwrap();
+        let mut tar = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+        for (name, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(1_700_000_000);
+            header.set_cksum();
+
+            tar.append_data(&mut header, format!("./{}", name), contents.as_bytes())
+                .unwrap();
+        }
+
+        tar.into_inner().unwrap().finish().unwrap();
+        full_path
+    }
+
     /// Asserts that the specified path exists in the given list of files.
-    pub fn assert_contains(&self, files: &Vec<PathBuf>, path: &str) {
+    pub fn assert_contains(&self, files: &[PathBuf], path: &str) {
         self.assert_contains_with_exist(files, path, true);
     }
 
     /// Asserts that the specified path does not exist in the given list of files.
-    pub fn assert_not_contains(&self, files: &Vec<PathBuf>, path: &str) {
+    pub fn assert_not_contains(&self, files: &[PathBuf], path: &str) {
         self.assert_contains_with_exist(files, path, false);
     }
 
     /// Helper method to check existence of a path in the list of files.
-    pub fn assert_contains_with_exist(&self, files: &Vec<PathBuf>, path: &str, should_exist: bool) {
+    pub fn assert_contains_with_exist(&self, files: &[PathBuf], path: &str, should_exist: bool) {
         let files_str: Vec<String> = files
             .iter()
             .map(|path| path.to_string_lossy().into_owned())
@@ -0,0 +1,420 @@
+use crate::error::QuaggaError;
+use crate::file::archive::{entry_path, is_archive, ArchiveCache, ArchiveEntry};
+use crate::file::file_content::FileContent;
+use crate::file::file_reader::bytes_to_text;
+use crate::file::notebook::{convert_notebook, is_notebook};
+use crate::file::skipped_file::{skip_or_fail, SkippedFile};
+use crate::options::QuaggaOptions;
+use crate::walk::binary_detector::is_valid_text_content;
+use crate::walk::classifier::classify_file;
+use crate::walk::contain::ContentMatcher;
+use crate::walk::modified::ModifiedFilter;
+use crate::walk::walk_overrides::build_overrides;
+use ignore::overrides::Override;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Lists the files inside a zip or tar archive for the output prompt, applying the same rules
+/// as when walking a directory: include and exclude patterns, hidden files, maximum depth,
+/// maximum file size, modification time, binary, generated and minified files and the `contain` options.
+/// The `.gitignore` and `.quagga_ignore` files inside the archive are not used.
+/// The sizes stored in the archive are checked against `--max-total-size` before the files are read.
+///
+/// # Arguments
+///
+/// * `archive` - The path to the archive.
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `skipped` - The list to which the files that can not be read are added.
+/// * `keep_content` - Whether to return the contents of the files.
+///
+/// # Returns
+///
+/// * `Ok(Vec<FileContent>)` containing the files with paths like `project.zip!/src/main.rs`,
+///   sorted by path. The contents are empty unless `keep_content` is true.
+/// * `Err<QuaggaError>` if the archive can not be read, the selected files are larger than
+///   `options.max_total_size`, or a file in it can not be read when `options.on_error` is `OnError::Fail`.
+pub fn walk_archive(
+    archive: &Path,
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    skipped: &mut Vec<SkippedFile>,
+    keep_content: bool,
+) -> Result<Vec<FileContent>, QuaggaError> {
+    let overrides = build_overrides(options)?;
+    let modified = ModifiedFilter::new(options, archives)?;
+    let matcher = ContentMatcher::new(options)?;
+    let archive_error = |source| QuaggaError::FileRead {
+        path: archive.to_path_buf(),
+        source,
+    };
+
+    let entries = archives.list_entries(archive).map_err(archive_error)?;
+
+    let selected: HashSet<&str> = entries
+        .iter()
+        .filter(|entry| is_entry_selected(entry, options, &overrides, &modified))
+        .map(|entry| entry.name.as_str())
+        .collect();
+
+    // Check the total of the sizes stored in the archive before reading the files
+    let total: u64 = entries
+        .iter()
+        .filter(|entry| selected.contains(entry.name.as_str()))
+        .map(|entry| entry.size)
+        .sum();
+
+    if total > options.max_total_size {
+        return Err(QuaggaError::TotalSizeExceeded {
+            total,
+            limit: options.max_total_size,
+        });
+    }
+
+    // Files larger than `--max-filesize` are not read, except notebooks that are checked after conversion
+    let max_size = |entry: &ArchiveEntry| {
+        if is_notebook(Path::new(&entry.name)) {
+            options.max_total_size
+        } else {
+            options.max_filesize
+        }
+    };
+
+    let mut contents = archives
+        .read_entries(archive, &selected, max_size)
+        .map_err(archive_error)?;
+
+    let mut names: Vec<&str> = selected.into_iter().collect();
+    names.sort();
+    let mut files = Vec::new();
+    let mut included = HashSet::new();
+
+    for name in names {
+        let Some(bytes) = contents.remove(name) else {
+            continue;
+        };
+
+        if !options.binary && !is_valid_text_content(&bytes, options.encoding) {
+            continue;
+        }
+
+        let path = entry_path(archive, name);
+
+        let content = match bytes_to_text(
+            path.clone(),
+            bytes.to_vec(),
+            options.binary,
+            options.encoding,
+        ) {
+            Ok(content) => convert_notebook(&path, content, options.notebook_outputs),
+            Err(error) => {
+                skip_or_fail(error, options.on_error, skipped)?;
+                continue;
+            }
+        };
+
+        if content.len() as u64 > options.max_filesize {
+            continue;
+        }
+
+        // Check the `--skip-generated` and `--skip-minified` options
+        if classify_file(&path, &content).is_skipped(options) {
+            continue;
+        }
+
+        // Check the `--contain`, `--contain-word`, `--contain-regex` and `--not-contain` options
+        if !matcher.is_match(&content) {
+            continue;
+        }
+
+        included.insert(name);
+        files.push(FileContent {
+            path,
+            content: if keep_content { content } else { String::new() },
+        });
+    }
+
+    // Keep the contents of the included files only, for reading them again when writing the prompt
+    archives.retain_contents(archive, |name| included.contains(name));
+
+    Ok(files)
+}
+
+/// Replaces the paths to archives with the paths to the files inside them,
+/// selected with `walk_archive`. Other paths are left unchanged.
+///
+/// # Arguments
+///
+/// * `paths` - Paths to files and archives.
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `skipped` - The list to which the files that can not be read are added.
+///
+/// # Returns
+///
+/// The paths with the archives expanded, or an error if an archive can not be read.
+pub fn expand_archives(
+    paths: Vec<PathBuf>,
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<Vec<PathBuf>, QuaggaError> {
+    let mut expanded = Vec::new();
+
+    for path in paths {
+        if is_archive(&path) {
+            let files = walk_archive(&path, options, archives, skipped, false)?;
+            expanded.extend(files.into_iter().map(|file| file.path));
+        } else {
+            expanded.push(path);
+        }
+    }
+
+    Ok(expanded)
+}
+
+/// Checks the path, size and modification time of the file in the archive
+/// before it is read.
+fn is_entry_selected(
+    entry: &ArchiveEntry,
+    options: &QuaggaOptions,
+    overrides: &Override,
+    modified: &ModifiedFilter,
+) -> bool {
+    let path = Path::new(&entry.name);
+    let depth = path.components().count();
+
+    if options.max_depth.is_some_and(|max_depth| depth > max_depth) {
+        return false;
+    }
+
+    // Notebooks are checked against `--max-filesize` after they are converted to text
+    if entry.size > options.max_filesize && !is_notebook(path) {
+        return false;
+    }
+
+    if !options.hidden && entry.name.split('/').any(|part| part.starts_with('.')) {
+        return false;
+    }
+
+    // Excluded directories exclude all files in them, as when walking a directory
+    let excluded_dir = path
+        .ancestors()
+        .skip(1)
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .any(|dir| overrides.matched(dir, true).is_ignore());
+
+    if excluded_dir || overrides.matched(path, false).is_ignore() {
+        return false;
+    }
+
+    if modified.is_empty() {
+        return true;
+    }
+
+    entry
+        .modified
+        .is_some_and(|time| modified.is_time_match(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+    use std::time::Duration;
+
+    fn names(files: &[FileContent]) -> Vec<String> {
+        files
+            .iter()
+            .map(|file| {
+                file.path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect()
+    }
+
+    fn project_files() -> Vec<(&'static str, &'static str)> {
+        vec![
+            ("src/main.rs", "fn main() {}"),
+            ("src/lib.rs", "pub mod app;"),
+            ("node_modules/lib/index.js", "module.exports = {};"),
+            (".env", "SECRET=1"),
+            ("README.md", "Read me"),
+            ("docs/guide/intro.md", "Intro"),
+        ]
+    }
+
+    #[test]
+    fn test_walk_archive() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mkzip("project.zip", &project_files());
+
+        let options = QuaggaOptions {
+            root: archive.clone(),
+            ..Default::default()
+        };
+
+        let files = walk_archive(
+            &archive,
+            &options,
+            &ArchiveCache::default(),
+            &mut Vec::new(),
+            true,
+        )
+        .unwrap();
+
+        let paths: Vec<PathBuf> = files.iter().map(|file| file.path.clone()).collect();
+
+        assert_eq!(
+            paths,
+            vec![
+                entry_path(&archive, "README.md"),
+                entry_path(&archive, "docs/guide/intro.md"),
+                entry_path(&archive, "node_modules/lib/index.js"),
+                entry_path(&archive, "src/lib.rs"),
+                entry_path(&archive, "src/main.rs"),
+            ]
+        );
+
+        assert_eq!(files[4].content, "fn main() {}");
+    }
+
+    #[test]
+    fn test_walk_archive_include_exclude_and_depth() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mktar_gz("project.tar.gz", &project_files());
+
+        let options = QuaggaOptions {
+            root: archive.clone(),
+            include: vec!["*.rs".to_string(), "*.js".to_string(), "*.md".to_string()],
+            exclude: vec!["node_modules".to_string()],
+            max_depth: Some(2),
+            ..Default::default()
+        };
+
+        let files = walk_archive(
+            &archive,
+            &options,
+            &ArchiveCache::default(),
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(names(&files), vec!["README.md", "lib.rs", "main.rs"]);
+        assert!(files.iter().all(|file| file.content.is_empty()));
+    }
+
+    #[test]
+    fn test_walk_archive_total_size_checked_before_reading() {
+        let td = TempDir::new().unwrap();
+        let archive = td.mktar_gz("project.tar.gz", &[("a.txt", "12345"), ("b.txt", "67890")]);
+
+        let options = QuaggaOptions {
+            root: archive.clone(),
+            max_total_size: 9,
+            ..Default::default()
+        };
+
+        let result = walk_archive(
+            &archive,
+            &options,
+            &ArchiveCache::default(),
+            &mut Vec::new(),
+            true,
+        );
+
+        assert!(matches!(
+            result,
+            Err(QuaggaError::TotalSizeExceeded {
+                total: 10,
+                limit: 9
+            })
+        ));
+    }
+
+    #[test]
+    fn test_walk_archive_binary_size_and_contain() {
+        let td = TempDir::new().unwrap();
+
+        let archive = td.mkzip(
+            "project.zip",
+            &[
+                ("binary.bin", "\0\0\0\0"),
+                ("large.txt", "large file with todo"),
+                ("todo.txt", "todo"),
+                ("done.txt", "done"),
+            ],
+        );
+
+        let options = QuaggaOptions {
+            root: archive.clone(),
+            max_filesize: 10,
+            contain: vec!["todo".to_string()],
+            ..Default::default()
+        };
+
+        let files = walk_archive(
+            &archive,
+            &options,
+            &ArchiveCache::default(),
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(names(&files), vec!["todo.txt"]);
+    }
+
+    #[test]
+    fn test_walk_archive_modified_filter() {
+        let td = TempDir::new().unwrap();
+
+        // Files in the test tar archives are modified in 2023
+        let archive = td.mktar_gz("project.tar.gz", &[("a.txt", "A")]);
+
+        let options = QuaggaOptions {
+            root: archive.clone(),
+            modified_within: Some(Duration::from_secs(24 * 60 * 60)),
+            ..Default::default()
+        };
+
+        let files = walk_archive(
+            &archive,
+            &options,
+            &ArchiveCache::default(),
+            &mut Vec::new(),
+            false,
+        )
+        .unwrap();
+
+        assert!(files.is_empty());
+    }
+
+    #[test]
+    fn test_expand_archives() {
+        let td = TempDir::new().unwrap();
+        let file = td.mkfile_with_contents("notes.txt", "Notes");
+        let archive = td.mkzip("project.zip", &[("b.txt", "B"), ("a.txt", "A")]);
+
+        let paths = expand_archives(
+            vec![file.clone(), archive.clone()],
+            &QuaggaOptions::default(),
+            &ArchiveCache::default(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                file,
+                entry_path(&archive, "a.txt"),
+                entry_path(&archive, "b.txt")
+            ]
+        );
+    }
+}
@@ -1,8 +1,13 @@
+use crate::file::encoding::{detect_encoding, is_text_in_encoding};
+use encoding_rs::Encoding;
 use std::fs::File;
 use std::io::{self, Read};
 use std::path::PathBuf;
 
-/// Determines if a file at the given path is likely a valid UTF-8 text code file.
+/// Number of bytes at the start of a file used to detect if it's text or binary.
+const SAMPLE_SIZE: usize = 1024;
+
+/// Determines if a file at the given path is likely a text code file.
 ///
 /// This function reads the first 1024 bytes of the file and uses `is_valid_text`
 /// to check if the content is likely text.
@@ -10,15 +15,17 @@ use std::path::PathBuf;
 /// # Arguments
 ///
 /// * `file_path` - A `PathBuf` representing the path to the file.
+/// * `encoding` - The encoding of the file, or `None` to detect it.
 ///
 /// # Returns
 ///
 /// * `Ok(true)` if the file is likely a valid text file.
 /// * `Ok(false)` if the file is likely binary.
 /// * `Err` if an error occurs while opening or reading the file.
-pub fn is_valid_text_file(file_path: PathBuf) -> io::Result<bool> {
-    const SAMPLE_SIZE: usize = 1024; // Number of bytes to read for sampling
-
+pub fn is_valid_text_file(
+    file_path: PathBuf,
+    encoding: Option<&'static Encoding>,
+) -> io::Result<bool> {
     // Open the file in read-only mode
     let mut file = File::open(file_path)?;
 
@@ -29,8 +36,30 @@ pub fn is_valid_text_file(file_path: PathBuf) -> io::Result<bool> {
         .take(SAMPLE_SIZE as u64)
         .read_to_end(&mut buffer)?;
 
-    // Use is_valid_text to determine if the buffer is likely text
-    Ok(is_valid_text(&buffer))
+    Ok(is_text_sample(&buffer, encoding))
+}
+
+/// Determines if the file content is likely text, looking only at its first `SAMPLE_SIZE` bytes,
+/// same as `is_valid_text_file`. Used when the whole file has already been read.
+///
+/// # Arguments
+///
+/// * `content` - The bytes of the entire file.
+/// * `encoding` - The encoding of the file, or `None` to detect it.
+///
+/// # Returns
+///
+/// `true` if the content is likely text, `false` otherwise.
+pub fn is_valid_text_content(content: &[u8], encoding: Option<&'static Encoding>) -> bool {
+    is_text_sample(&content[..content.len().min(SAMPLE_SIZE)], encoding)
+}
+
+/// Checks if the start of a file is text in the given encoding, or in any detected encoding.
+fn is_text_sample(buffer: &[u8], encoding: Option<&'static Encoding>) -> bool {
+    match encoding {
+        Some(encoding) => is_text_in_encoding(buffer, encoding),
+        None => is_valid_text(buffer),
+    }
 }
 
 /// Counts the number of null bytes (`0x00`) in a buffer.
@@ -66,11 +95,7 @@ pub fn is_valid_utf8(buffer: &[u8]) -> bool {
     let max_char_len = 4;
 
     // We only need to check the last few bytes
-    let start = if len > max_char_len {
-        len - max_char_len
-    } else {
-        0
-    };
+    let start = len.saturating_sub(max_char_len);
 
     // Trim off incomplete multibyte characters at the end
     for i in (start..len).rev() {
@@ -95,7 +120,8 @@ pub fn is_valid_utf8(buffer: &[u8]) -> bool {
     str::from_utf8(buffer).is_ok()
 }
 
-/// Determines if a buffer is likely a text file in UTF-8 encoding (e.g., source code).
+/// Determines if a buffer is likely a text file (e.g., source code) in UTF-8, in UTF-16 with
+/// a byte order mark, or in another encoding detected with `detect_encoding`.
 ///
 /// # Arguments
 ///
@@ -103,13 +129,9 @@ pub fn is_valid_utf8(buffer: &[u8]) -> bool {
 ///
 /// # Returns
 ///
-/// `true` if the buffer is likely a text file in UTF-8 encoding, `false` otherwise.
+/// `true` if the buffer is likely a text file, `false` otherwise.
 pub fn is_valid_text(buffer: &[u8]) -> bool {
-    if number_of_null_bytes(buffer) > 0 {
-        false // Contains null bytes; likely binary
-    } else {
-        is_valid_utf8(buffer)
-    }
+    detect_encoding(buffer).is_some_and(|encoding| is_text_in_encoding(buffer, encoding))
 }
 
 #[cfg(test)]
@@ -148,19 +170,19 @@ mod tests {
     #[test]
     fn test_is_valid_utf8_with_valid_utf8() {
         let buffer = "Hello, world!".as_bytes();
-        assert!(is_valid_utf8(&buffer));
+        assert!(is_valid_utf8(buffer));
     }
 
     #[test]
     fn test_is_valid_utf8_with_valid_utf8_multibyte() {
         let buffer = "こんにちは".as_bytes(); // "Hello" in Japanese
-        assert!(is_valid_utf8(&buffer));
+        assert!(is_valid_utf8(buffer));
     }
 
     #[test]
     fn test_is_valid_utf8_with_valid_single_multibyte_character() {
         let buffer = "こ".as_bytes();
-        assert!(is_valid_utf8(&buffer));
+        assert!(is_valid_utf8(buffer));
     }
 
     #[test]
@@ -202,22 +224,52 @@ mod tests {
 
     #[test]
     fn test_is_valid_text_with_non_utf8_text() {
-        let buffer = vec![0xC0, 0xC1]; // Invalid UTF-8 sequences
+        let buffer = vec![0x43, 0x61, 0x66, 0xE9]; // "Café" in Windows-1252
+        assert!(is_valid_text(&buffer));
+    }
+
+    #[test]
+    fn test_is_valid_text_with_control_characters() {
+        let buffer = vec![0x01, 0x02, 0xC0, 0x03]; // Not UTF-8 and not text in other encodings
         assert!(!is_valid_text(&buffer));
     }
 
+    #[test]
+    fn test_is_valid_text_with_utf16_bom() {
+        let buffer = [0xFF, 0xFE, 0x61, 0x00, 0x62, 0x00]; // "ab" in UTF-16LE
+        assert!(is_valid_text(&buffer));
+    }
+
     #[test]
     fn test_is_valid_text_with_empty_buffer() {
         let buffer: &[u8] = &[];
         assert!(is_valid_text(buffer));
     }
 
+    #[test]
+    fn test_is_valid_text_content_looks_only_at_sample() {
+        let mut content = "a".repeat(SAMPLE_SIZE).into_bytes();
+        content.extend_from_slice(&[0x00, 0xC0, 0xC1]);
+
+        assert!(is_valid_text_content(&content, None));
+        assert!(!is_valid_text_content(&[0x00, 0xC0, 0xC1], None));
+        assert!(is_valid_text_content(&[], None));
+    }
+
+    #[test]
+    fn test_is_valid_text_content_with_encoding() {
+        let content = [0x61, 0x00, 0x62, 0x00]; // "ab" in UTF-16LE without BOM
+
+        assert!(is_valid_text_content(&content, Some(encoding_rs::UTF_16LE)));
+        assert!(!is_valid_text_content(&content, None));
+    }
+
     #[test]
     fn test_is_valid_text_file_with_text_file() {
         let td = TempDir::new().unwrap();
         let file_path = td.mkfile_with_contents("test.txt", "fn main() {}");
 
-        let result = is_valid_text_file(file_path).unwrap();
+        let result = is_valid_text_file(file_path, None).unwrap();
 
         assert!(result);
     }
@@ -228,7 +280,7 @@ mod tests {
         let file_path = td.mkfile_with_bytes("test.bin", &[0x00, 0xFF, 0x00, 0xFF]); // Contains null bytes
 
         // Check if the file is valid text
-        let result = is_valid_text_file(file_path).unwrap();
+        let result = is_valid_text_file(file_path, None).unwrap();
 
         assert!(!result);
     }
@@ -239,7 +291,7 @@ mod tests {
         let file_path = td.path().join("empty.txt");
         File::create(&file_path).unwrap();
 
-        let result = is_valid_text_file(file_path).unwrap();
+        let result = is_valid_text_file(file_path, None).unwrap();
 
         assert!(result, "Empty file detected as binary");
     }
@@ -249,7 +301,7 @@ mod tests {
         let td = TempDir::new().unwrap();
         let file_path = td.path().join("nonexistent.txt");
 
-        let result = is_valid_text_file(file_path);
+        let result = is_valid_text_file(file_path, None);
 
         assert!(result.is_err());
     }
@@ -259,7 +311,7 @@ mod tests {
         let td = TempDir::new().unwrap();
         let dir_path = td.path().to_path_buf();
 
-        let result = is_valid_text_file(dir_path);
+        let result = is_valid_text_file(dir_path, None);
 
         assert!(result.is_err());
     }
@@ -0,0 +1,402 @@
+use crate::file::notebook::is_notebook;
+use crate::options::QuaggaOptions;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Number of bytes at the start of the text of a file used to classify its content.
+pub const CLASSIFY_SAMPLE_SIZE: usize = 8 * 1024;
+
+/// Files smaller than this are not checked for long lines and encoded data.
+const MIN_SIZE: usize = 1024;
+
+/// Lines longer than this many characters are considered long.
+const LONG_LINE: usize = 500;
+
+/// Files with more than this share of characters in long lines are considered minified.
+const MAX_LONG_LINE_RATIO: f64 = 0.5;
+
+/// Long lines with a smaller share of whitespace are considered minified.
+/// Prose has about one space in six characters, so long paragraphs in a single line are not minified.
+const MINIFIED_WHITESPACE_RATIO: f64 = 0.1;
+
+/// ASCII words without whitespace longer than this many characters may be encoded data.
+const LONG_WORD: usize = 64;
+
+/// Files with more than this share of characters in long words with high entropy
+/// are considered encoded data, e.g. base64.
+const MAX_ENCODED_RATIO: f64 = 0.5;
+
+/// Entropy in bits per byte above which long words are considered encoded data.
+/// Source code is usually below 5.4 bits and base64 is close to 6 bits.
+const ENCODED_ENTROPY: f64 = 5.6;
+
+/// Only the first lines are searched for the generated file markers.
+const MARKER_LINES: usize = 10;
+
+/// Comments marking generated files that should not be edited by hand.
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "DO NOT EDIT",
+    "Code generated by",
+    "<auto-generated",
+];
+
+/// Names of lock files created by package managers.
+const LOCK_FILES: &[&str] = &[
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lock",
+    "Gemfile.lock",
+    "Pipfile.lock",
+    "poetry.lock",
+    "uv.lock",
+    "composer.lock",
+    "go.sum",
+    "flake.lock",
+    "mix.lock",
+    "Podfile.lock",
+    "Package.resolved",
+    "packages.lock.json",
+];
+
+/// Extensions of prose files, which may have a paragraph in each line. Languages
+/// such as Chinese and Japanese are written without spaces between words, so their
+/// long lines are not minified.
+const PROSE_EXTENSIONS: &[&str] = &["md", "markdown", "txt", "rst", "adoc"];
+
+/// File name endings of minified files and source maps.
+const MINIFIED_SUFFIXES: &[&str] = &[".min.js", ".min.mjs", ".min.css", ".js.map", ".css.map"];
+
+/// The kind of content of a text file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FileClass {
+    /// Source code or text written by hand.
+    Source,
+
+    /// Generated code marked with `@generated` or `DO NOT EDIT`, or a lock file.
+    Generated,
+
+    /// Minified code, a source map, or encoded data such as base64.
+    Minified,
+}
+
+impl FileClass {
+    /// Checks if files of this class are left out of the prompt
+    /// with the `skip_generated` and `skip_minified` options.
+    pub fn is_skipped(self, options: &QuaggaOptions) -> bool {
+        match self {
+            FileClass::Source => false,
+            FileClass::Generated => options.skip_generated,
+            FileClass::Minified => options.skip_minified,
+        }
+    }
+}
+
+/// Classifies a text file by its name and the start of its content.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `content` - The text of the file converted to UTF-8, or its start.
+///   Only the first `CLASSIFY_SAMPLE_SIZE` bytes are used.
+///
+/// # Returns
+///
+/// The class of the file.
+pub fn classify_file(path: &Path, content: &str) -> FileClass {
+    let mut end = content.len().min(CLASSIFY_SAMPLE_SIZE);
+
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+
+    let sample = &content[..end];
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+
+    if LOCK_FILES.contains(&name.as_ref()) || has_generated_marker(sample) {
+        return FileClass::Generated;
+    }
+
+    if MINIFIED_SUFFIXES
+        .iter()
+        .any(|suffix| name.ends_with(suffix))
+    {
+        return FileClass::Minified;
+    }
+
+    // The outputs of notebooks, such as base64 images, are dropped when they are converted to text
+    if is_notebook(path) {
+        return FileClass::Source;
+    }
+
+    if sample.len() < MIN_SIZE {
+        return FileClass::Source;
+    }
+
+    if !is_prose(path) && long_line_ratio(sample) > MAX_LONG_LINE_RATIO {
+        return FileClass::Minified;
+    }
+
+    if encoded_ratio(sample) > MAX_ENCODED_RATIO {
+        return FileClass::Minified;
+    }
+
+    FileClass::Source
+}
+
+/// Checks if the file is prose, such as Markdown or plain text, by its extension.
+fn is_prose(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            PROSE_EXTENSIONS
+                .iter()
+                .any(|prose| extension.eq_ignore_ascii_case(prose))
+        })
+}
+
+/// Checks if any of the first lines contains a generated file marker.
+fn has_generated_marker(sample: &str) -> bool {
+    sample
+        .lines()
+        .take(MARKER_LINES)
+        .any(|line| GENERATED_MARKERS.iter().any(|marker| line.contains(marker)))
+}
+
+/// Returns the share of characters that are in lines longer than `LONG_LINE`
+/// with little whitespace.
+fn long_line_ratio(sample: &str) -> f64 {
+    let long_chars: usize = sample
+        .split('\n')
+        .map(|line| (line.chars().count(), line))
+        .filter(|(chars, _)| *chars > LONG_LINE)
+        .filter(|(chars, line)| {
+            let whitespace = line.chars().filter(|c| c.is_whitespace()).count();
+            (whitespace as f64) < *chars as f64 * MINIFIED_WHITESPACE_RATIO
+        })
+        .map(|(chars, _)| chars)
+        .sum();
+
+    long_chars as f64 / sample.chars().count() as f64
+}
+
+/// Returns the share of characters that are in long ASCII words without whitespace,
+/// if the words look like encoded data, or zero otherwise.
+fn encoded_ratio(sample: &str) -> f64 {
+    let long_words: Vec<&str> = sample
+        .split(char::is_whitespace)
+        .filter(|word| word.len() > LONG_WORD && word.is_ascii())
+        .collect();
+
+    let mut counts = [0usize; 256];
+    let mut total = 0;
+
+    for byte in long_words.iter().flat_map(|word| word.bytes()) {
+        counts[byte as usize] += 1;
+        total += 1;
+    }
+
+    if total == 0 || entropy(&counts, total) < ENCODED_ENTROPY {
+        return 0.0;
+    }
+
+    total as f64 / sample.chars().count() as f64
+}
+
+/// Returns the Shannon entropy in bits per byte of the byte counts.
+pub fn entropy(counts: &[usize; 256], total: usize) -> f64 {
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = count as f64 / total as f64;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns pseudo-random base64 text wrapped at 76 characters.
+    fn base64_text(length: usize) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut state: u32 = 12345;
+        let mut text = String::new();
+
+        for i in 0..length {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            text.push(ALPHABET[(state >> 16) as usize % 64] as char);
+
+            if i % 76 == 75 {
+                text.push('\n');
+            }
+        }
+
+        text
+    }
+
+    fn source_code() -> String {
+        "fn main() {\n    let answer = 42;\n    println!(\"{}\", answer);\n}\n".repeat(40)
+    }
+
+    #[test]
+    fn test_classify_source() {
+        let content = source_code();
+
+        assert_eq!(
+            classify_file(Path::new("main.rs"), &content),
+            FileClass::Source
+        );
+    }
+
+    #[test]
+    fn test_classify_small_file() {
+        assert_eq!(
+            classify_file(Path::new("main.rs"), "fn main() {}"),
+            FileClass::Source
+        );
+    }
+
+    #[test]
+    fn test_classify_lock_file() {
+        assert_eq!(
+            classify_file(Path::new("project/Cargo.lock"), "version = 3"),
+            FileClass::Generated
+        );
+
+        assert_eq!(
+            classify_file(Path::new("package-lock.json"), "{}"),
+            FileClass::Generated
+        );
+    }
+
+    #[test]
+    fn test_classify_generated_markers() {
+        let go = "// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n";
+        assert_eq!(
+            classify_file(Path::new("api.pb.go"), go),
+            FileClass::Generated
+        );
+
+        let rust = "// @generated by build.rs\npub const VERSION: &str = \"1.0\";\n";
+        assert_eq!(
+            classify_file(Path::new("version.rs"), rust),
+            FileClass::Generated
+        );
+    }
+
+    #[test]
+    fn test_classify_marker_after_first_lines() {
+        let mut content = source_code();
+        content.push_str("// DO NOT EDIT the constants below\n");
+
+        assert_eq!(
+            classify_file(Path::new("main.rs"), &content),
+            FileClass::Source
+        );
+    }
+
+    #[test]
+    fn test_classify_minified_names() {
+        assert_eq!(
+            classify_file(Path::new("dist/app.min.js"), "var a=1;"),
+            FileClass::Minified
+        );
+
+        assert_eq!(
+            classify_file(Path::new("dist/app.js.map"), "{}"),
+            FileClass::Minified
+        );
+    }
+
+    #[test]
+    fn test_classify_long_lines() {
+        let content = "function f(a,b){return a+b}var x=f(1,2);".repeat(100);
+
+        assert_eq!(
+            classify_file(Path::new("bundle.js"), &content),
+            FileClass::Minified
+        );
+    }
+
+    #[test]
+    fn test_classify_long_prose_lines() {
+        let paragraph = "Quagga combines text files into a single prompt. ".repeat(30);
+        let content = format!("# Title\n\n{}\n\n{}\n", paragraph, paragraph);
+
+        assert_eq!(
+            classify_file(Path::new("README.md"), &content),
+            FileClass::Source
+        );
+    }
+
+    #[test]
+    fn test_classify_cjk_prose() {
+        let paragraph =
+            "クアッガはテキストファイルを一つのプロンプトにまとめるためのツールです。".repeat(20);
+        let content = format!("# はじめに\n\n{}\n\n{}\n", paragraph, paragraph);
+
+        for name in ["README.md", "notes.txt"] {
+            assert_eq!(classify_file(Path::new(name), &content), FileClass::Source);
+        }
+    }
+
+    #[test]
+    fn test_classify_cjk_source() {
+        let comment = "// 設定ファイルを読み込んで、値を検証してから返す。\n".repeat(20);
+        let content = format!("{}fn main() {{}}\n", comment).repeat(4);
+
+        assert_eq!(
+            classify_file(Path::new("main.rs"), &content),
+            FileClass::Source
+        );
+    }
+
+    #[test]
+    fn test_classify_base64() {
+        let mut content = "-----BEGIN DATA-----\n".to_string();
+        content.push_str(&base64_text(4000));
+
+        assert_eq!(
+            classify_file(Path::new("data.txt"), &content),
+            FileClass::Minified
+        );
+    }
+
+    #[test]
+    fn test_classify_embedded_image() {
+        let mut content = "<svg><image href=\"data:image/png;base64,".to_string();
+        content.extend(base64_text(4000).chars().filter(|&c| c != '\n'));
+        content.push_str("\"/></svg>\n");
+
+        assert_eq!(
+            classify_file(Path::new("logo.svg"), &content),
+            FileClass::Minified
+        );
+    }
+
+    #[test]
+    fn test_file_class_is_skipped() {
+        let options = QuaggaOptions::default();
+
+        assert!(!FileClass::Source.is_skipped(&options));
+        assert!(FileClass::Generated.is_skipped(&options));
+        assert!(FileClass::Minified.is_skipped(&options));
+
+        let options = QuaggaOptions {
+            skip_generated: false,
+            ..Default::default()
+        };
+
+        assert!(!FileClass::Generated.is_skipped(&options));
+        assert!(FileClass::Minified.is_skipped(&options));
+    }
+}
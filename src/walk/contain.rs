@@ -1,114 +1,322 @@
-use crate::file::file_reader::read_text_file;
-use std::io;
-use std::path::PathBuf;
+use crate::error::QuaggaError;
+use crate::options::QuaggaOptions;
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+use std::ops::Range;
 
-/// Checks if the file at the given path contains any of the specified texts.
-///
-/// # Arguments
-///
-/// * `file_path` - The path to the file to check.
-/// * `texts` - A vector of strings to search for.
-/// * `force` - A boolean indicating whether to force reading a file when it is not valid UTF-8 text
-///             by removing removing invalid UTF-8 sequences.
+/// Checks the content of files against the `--contain`, `--contain-word`, `--contain-regex`
+/// and `--not-contain` options.
+#[derive(Debug, Clone)]
+pub struct ContentMatcher {
+    /// Patterns the file must contain: any of them, or all of them with `--contain-all`.
+    required: Option<RegexSet>,
+
+    /// The same patterns as `required`, used to find the positions of the matches.
+    required_regexes: Vec<Regex>,
+
+    /// Patterns the file must not contain.
+    excluded: Option<RegexSet>,
+
+    /// Whether the file must contain all of the required patterns.
+    all: bool,
+}
+
+impl ContentMatcher {
+    /// Creates the matcher from the options. Plain texts and words are escaped,
+    /// words are matched at word boundaries.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Options for generating the prompt.
+    ///
+    /// # Returns
+    ///
+    /// The matcher, or `QuaggaError::InvalidRegex` if a `contain_regex` pattern is invalid.
+    pub fn new(options: &QuaggaOptions) -> Result<ContentMatcher, QuaggaError> {
+        let required: Vec<String> = options
+            .contain
+            .iter()
+            .map(|text| regex::escape(text))
+            .chain(options.contain_word.iter().map(|word| word_pattern(word)))
+            .chain(options.contain_regex.iter().cloned())
+            .collect();
+
+        let excluded: Vec<String> = options
+            .not_contain
+            .iter()
+            .map(|text| regex::escape(text))
+            .collect();
+
+        Ok(ContentMatcher {
+            required: build_regex_set(&required, options)?,
+            required_regexes: build_regexes(&required, options)?,
+            excluded: build_regex_set(&excluded, options)?,
+            all: options.contain_all,
+        })
+    }
+
+    /// Returns `true` if the matcher has patterns the files must contain.
+    pub fn has_required(&self) -> bool {
+        self.required.is_some()
+    }
+
+    /// Returns `true` if the matcher has no patterns and accepts any file without reading it.
+    pub fn is_empty(&self) -> bool {
+        self.required.is_none() && self.excluded.is_none()
+    }
+
+    /// Checks if the content of a file matches the patterns.
+    pub fn is_match(&self, content: &str) -> bool {
+        let required = match &self.required {
+            Some(set) if self.all => set.matches(content).iter().count() == set.len(),
+            Some(set) => set.is_match(content),
+            None => true,
+        };
+
+        let excluded = match &self.excluded {
+            Some(set) => set.is_match(content),
+            None => false,
+        };
+
+        required && !excluded
+    }
+
+    /// Finds the lines that contain matches of the `contain`, `contain_word` and `contain_regex` patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `content` - The content of a file.
+    ///
+    /// # Returns
+    ///
+    /// Ranges of zero-based line indices, one for each match. A match can span multiple lines.
+    pub fn matching_lines(&self, content: &str) -> Vec<Range<usize>> {
+        // Byte offsets where each line starts
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+            .collect();
+
+        let line_of = |offset: usize| match line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+
+        self.required_regexes
+            .iter()
+            .flat_map(|regex| regex.find_iter(content))
+            .map(|found| {
+                let last_byte = found.end().saturating_sub(1).max(found.start());
+                line_of(found.start())..line_of(last_byte) + 1
+            })
+            .collect()
+    }
+}
+
+/// Compiles each pattern into a `Regex`.
 ///
 /// # Returns
 ///
-/// * `Ok(true)` if the file contains any of the texts.
-/// * `Ok(false)` if the file does not contain any of the texts.
-/// * `Err` if an error occurs while reading the file.
-pub fn file_contains_text(file_path: &PathBuf, texts: &[String], force: bool) -> io::Result<bool> {
-    let content = read_text_file(file_path.clone(), force)?;
+/// The regexes, or `QuaggaError::InvalidRegex` for the first invalid pattern.
+fn build_regexes(patterns: &[String], options: &QuaggaOptions) -> Result<Vec<Regex>, QuaggaError> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            RegexBuilder::new(pattern)
+                .case_insensitive(options.ignore_case)
+                .build()
+                .map_err(|error| QuaggaError::InvalidRegex {
+                    pattern: pattern.clone(),
+                    message: error.to_string(),
+                })
+        })
+        .collect()
+}
 
-    for text in texts {
-        if content.contains(text) {
-            return Ok(true);
-        }
+/// Compiles the patterns into a `RegexSet`, or returns `None` if there are no patterns.
+fn build_regex_set(
+    patterns: &[String],
+    options: &QuaggaOptions,
+) -> Result<Option<RegexSet>, QuaggaError> {
+    if patterns.is_empty() {
+        return Ok(None);
     }
 
-    return Ok(false);
+    // Compile each pattern separately first to report which one is invalid
+    build_regexes(patterns, options)?;
+
+    let set = RegexSetBuilder::new(patterns)
+        .case_insensitive(options.ignore_case)
+        .build()
+        .map_err(|error| QuaggaError::InvalidRegex {
+            pattern: patterns.join(", "),
+            message: error.to_string(),
+        })?;
+
+    Ok(Some(set))
+}
+
+/// Creates the pattern that matches the word as a whole word. The word boundaries are added
+/// only at the edges of the word that are word characters, since `\b` does not match
+/// between a space and a non-word character, e.g. before `@Override` or after `foo()`.
+fn word_pattern(word: &str) -> String {
+    let boundary = |c: Option<char>| match c {
+        Some(c) if c.is_alphanumeric() || c == '_' => r"\b",
+        _ => "",
+    };
+
+    format!(
+        "{}{}{}",
+        boundary(word.chars().next()),
+        regex::escape(word),
+        boundary(word.chars().last())
+    )
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::test_utils::temp_dir::TempDir;
 
-    #[test]
-    fn test_file_contains_text_found() {
-        let td = TempDir::new().unwrap();
-        let file_path = td.mkfile_with_contents("test.txt", "This is a test file.");
+    fn matcher(options: QuaggaOptions) -> ContentMatcher {
+        ContentMatcher::new(&options).unwrap()
+    }
 
-        let texts = vec!["test".to_string()];
-        let result = file_contains_text(&file_path, &texts, false).unwrap();
+    #[test]
+    fn test_content_matcher_empty() {
+        let matcher = matcher(QuaggaOptions::default());
 
-        assert!(result);
+        assert!(matcher.is_empty());
+        assert!(matcher.is_match("anything"));
     }
 
     #[test]
-    fn test_file_contains_text_invalid_utf8_force_false() {
-        let td = TempDir::new().unwrap();
-        let bytes = [0xC0, 0xC1, 0xFF];
-        let path = td.mkfile_with_bytes("test.txt", &bytes);
+    fn test_content_matcher_contain_any() {
+        let matcher = matcher(QuaggaOptions {
+            contain: vec!["todo".to_string(), "a.b".to_string()],
+            ..Default::default()
+        });
+
+        assert!(matcher.is_match("// todo: fix"));
+        assert!(matcher.is_match("a.b"));
+        assert!(!matcher.is_match("axb")); // Plain text is not a regex
+        assert!(!matcher.is_match("// TODO: fix"));
+    }
 
-        let texts = vec!["test".to_string()];
-        let result = file_contains_text(&path, &texts, false);
+    #[test]
+    fn test_content_matcher_contain_all() {
+        let matcher = matcher(QuaggaOptions {
+            contain: vec!["foo".to_string()],
+            contain_word: vec!["bar".to_string()],
+            contain_all: true,
+            ..Default::default()
+        });
 
-        assert!(result.is_err());
-        let err_msg = result.unwrap_err().to_string();
+        assert!(matcher.is_match("foo bar"));
+        assert!(!matcher.is_match("foo"));
+        assert!(!matcher.is_match("foo barbell"));
+    }
 
-        let expected = format!(
-            "Failed to read file {}: stream did not contain valid UTF-8",
-            path.display()
-        );
+    #[test]
+    fn test_content_matcher_contain_word() {
+        let matcher = matcher(QuaggaOptions {
+            contain_word: vec!["unsafe".to_string()],
+            ..Default::default()
+        });
 
-        assert_eq!(err_msg, expected);
+        assert!(matcher.is_match("unsafe { x }"));
+        assert!(!matcher.is_match("unsafely"));
     }
 
     #[test]
-    fn test_file_contains_text_invalid_utf8_force_true_text_found() {
-        let td = TempDir::new().unwrap();
-        let bytes = b"Valid text \xFF\xFE Invalid bytes \xC0\xC1 End.";
-        let path = td.mkfile_with_bytes("file.txt", bytes);
+    fn test_content_matcher_contain_word_starting_with_non_word_character() {
+        let matcher = matcher(QuaggaOptions {
+            contain_word: vec!["@Override".to_string()],
+            ..Default::default()
+        });
+
+        assert!(matcher.is_match("    @Override\n    public void run()"));
+        assert!(matcher.is_match("@Override"));
+        assert!(!matcher.is_match("@OverrideAll"));
+    }
 
-        let texts = vec!["bytes".to_string()];
-        let result = file_contains_text(&path, &texts, true);
+    #[test]
+    fn test_content_matcher_contain_word_ending_with_non_word_character() {
+        let matcher = matcher(QuaggaOptions {
+            contain_word: vec!["foo()".to_string()],
+            ..Default::default()
+        });
 
-        assert!(result.is_ok());
-        assert!(result.unwrap());
+        assert!(matcher.is_match("let x = foo();"));
+        assert!(matcher.is_match("foo()"));
+        assert!(!matcher.is_match("barfoo()"));
     }
 
     #[test]
-    fn test_file_contains_text_invalid_utf8_force_true_text_not_found() {
-        let td = TempDir::new().unwrap();
-        let bytes = b"Valid text \xFF\xFE Invalid bytes \xC0\xC1 End.";
-        let path = td.mkfile_with_bytes("file.txt", bytes);
+    fn test_content_matcher_contain_regex() {
+        let matcher = matcher(QuaggaOptions {
+            contain_regex: vec![r"fn \w+_test\(".to_string()],
+            ..Default::default()
+        });
+
+        assert!(matcher.is_match("fn parse_test() {}"));
+        assert!(!matcher.is_match("fn parse() {}"));
+    }
 
-        let texts = vec!["does not exist".to_string()];
-        let result = file_contains_text(&path, &texts, true);
+    #[test]
+    fn test_content_matcher_ignore_case() {
+        let matcher = matcher(QuaggaOptions {
+            contain: vec!["todo".to_string()],
+            not_contain: vec!["done".to_string()],
+            ignore_case: true,
+            ..Default::default()
+        });
 
-        assert!(result.is_ok());
-        assert!(!result.unwrap());
+        assert!(matcher.is_match("TODO: fix"));
+        assert!(!matcher.is_match("TODO: DONE"));
     }
 
     #[test]
-    fn test_file_contains_text_not_found() {
-        let td = TempDir::new().unwrap();
-        let file_path = td.mkfile_with_contents("test.txt", "This is a sample file.");
+    fn test_content_matcher_not_contain() {
+        let matcher = matcher(QuaggaOptions {
+            contain_word: vec!["unsafe".to_string()],
+            not_contain: vec!["// SAFETY:".to_string()],
+            ..Default::default()
+        });
+
+        assert!(!matcher.is_empty());
+        assert!(matcher.is_match("unsafe { x }"));
+        assert!(!matcher.is_match("// SAFETY: checked\nunsafe { x }"));
 
-        let texts = vec!["notfound".to_string()];
-        let result = file_contains_text(&file_path, &texts, false).unwrap();
+        let matcher = self::matcher(QuaggaOptions {
+            not_contain: vec!["generated".to_string()],
+            ..Default::default()
+        });
 
-        assert!(!result);
+        assert!(matcher.is_match("fn main() {}"));
+        assert!(!matcher.is_match("// generated"));
     }
 
     #[test]
-    fn test_file_contains_text_multiple_texts() {
-        let td = TempDir::new().unwrap();
-        let file_path = td.mkfile_with_contents("test.txt", "This is a test file.");
+    fn test_content_matcher_matching_lines() {
+        let matcher = matcher(QuaggaOptions {
+            contain: vec!["todo".to_string()],
+            contain_regex: vec![r"start\nend".to_string()],
+            ..Default::default()
+        });
+
+        let content = "todo\none\nstart\nend\ntwo todo";
 
-        let texts = vec!["notfound".to_string(), "test".to_string()];
-        let result = file_contains_text(&file_path, &texts, false).unwrap();
+        assert_eq!(matcher.matching_lines(content), vec![0..1, 4..5, 2..4]);
+    }
+
+    #[test]
+    fn test_content_matcher_invalid_regex() {
+        let result = ContentMatcher::new(&QuaggaOptions {
+            contain_regex: vec!["(".to_string()],
+            ..Default::default()
+        });
 
-        assert!(result);
+        assert!(matches!(
+            result,
+            Err(QuaggaError::InvalidRegex { pattern, .. }) if pattern == "("
+        ));
     }
 }
@@ -1,94 +1,476 @@
-use crate::cli::Cli;
-use crate::walk::binary_detector::is_valid_text_file;
-use crate::walk::contain::file_contains_text;
+use crate::error::QuaggaError;
+use crate::file::archive::{is_archive, ArchiveCache};
+use crate::file::cache::{CachedFile, FileCache};
+use crate::file::encoding::decode_sample;
+use crate::file::file_content::FileContent;
+use crate::file::file_reader::{bytes_to_text, read_file_bytes};
+use crate::file::notebook::{convert_notebook, is_notebook};
+use crate::file::skipped_file::{skip_or_fail, SkippedFile};
+use crate::file::sort::sort_files;
+use crate::options::QuaggaOptions;
+use crate::walk::archive_walker::walk_archive;
+use crate::walk::binary_detector::is_valid_text_content;
+use crate::walk::classifier::{classify_file, CLASSIFY_SAMPLE_SIZE};
+use crate::walk::contain::ContentMatcher;
+use crate::walk::modified::ModifiedFilter;
 use crate::walk::quagga_ignore::add_quagga_ignore_files;
 use crate::walk::walk_overrides::build_overrides;
-use ignore::WalkBuilder;
-use std::error::Error;
-use std::path::PathBuf;
+use encoding_rs::Encoding;
+use ignore::{WalkBuilder, WalkState};
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// The result of inspecting a single path found during the walk.
+enum WalkResult {
+    /// The file is included in the prompt. The content is empty unless it was requested.
+    Include(FileContent),
+
+    /// The file could not be read.
+    Error(QuaggaError),
+}
 
 /// Walks through the directory tree starting from `root` and collects all paths
 /// to text files for the output prompt.
 ///
 /// # Arguments
 ///
-/// * `cli` - Command line arguments.
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `skipped` - The list to which the files that can not be read are added.
 ///
 /// # Returns
 ///
-/// * `Ok(Vec<PathBuf>)` containing the paths to text files for the output prompt.
-/// * `Err<Box<dyn Error>>` if an error occurs during directory traversal or file reading.
-pub fn get_all_files(cli: &Cli) -> Result<Vec<PathBuf>, Box<dyn Error>> {
-    let walker_builder = configure_walk_builder(cli)?;
-    let walker = walker_builder.build();
-    let mut files = Vec::new();
+/// * `Ok(Vec<PathBuf>)` containing the paths to text files for the output prompt,
+///   sorted according to the `sort`, `reverse` and `first` options.
+/// * `Err<QuaggaError>` if an error occurs during directory traversal, or file reading
+///   when `options.on_error` is `OnError::Fail`.
+pub fn get_all_files(
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<Vec<PathBuf>, QuaggaError> {
+    let files = walk(options, archives, skipped, false)?;
+    Ok(files.into_iter().map(|file| file.path).collect())
+}
 
-    for entry in walker {
-        let entry = entry?;
-        let path = entry.path().to_path_buf();
+/// Walks through the directory tree starting from `root` and reads all text files
+/// for the output prompt. Each file is read only once: the same content is used to detect
+/// binary files, to check the `contain` texts and for the prompt.
+///
+/// # Arguments
+///
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `skipped` - The list to which the files that can not be read are added.
+///
+/// # Returns
+///
+/// * `Ok(Vec<FileContent>)` containing the text files for the output prompt,
+///   sorted according to the `sort`, `reverse` and `first` options.
+/// * `Err<QuaggaError>` if an error occurs during directory traversal, or file reading
+///   when `options.on_error` is `OnError::Fail`.
+pub fn get_all_file_contents(
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    skipped: &mut Vec<SkippedFile>,
+) -> Result<Vec<FileContent>, QuaggaError> {
+    walk(options, archives, skipped, true)
+}
 
-        if should_include_path(&path, cli)? {
-            files.push(path);
+/// Walks the directory tree using multiple threads and inspects each file.
+/// When the root is a zip or tar archive, the files inside it are listed instead.
+/// The results are sorted by path to make the output independent of the thread scheduling,
+/// and then according to the `sort`, `reverse` and `first` options.
+///
+/// # Arguments
+///
+/// * `options` - Options for generating the prompt.
+/// * `archives` - The cache of the archives read during the run.
+/// * `skipped` - The list to which the files that can not be read are added.
+/// * `keep_content` - Whether to return the contents of the files.
+fn walk(
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    skipped: &mut Vec<SkippedFile>,
+    keep_content: bool,
+) -> Result<Vec<FileContent>, QuaggaError> {
+    if is_archive(&options.root) {
+        let mut files = walk_archive(&options.root, options, archives, skipped, keep_content)?;
+        sort_files(&mut files, options, archives)?;
+        return Ok(files);
+    }
+
+    let walker = configure_walk_builder(options)?.build_parallel();
+    let cache = FileCache::from_options(options);
+    let filters = Filters::new(options, archives)?;
+    let results: Mutex<Vec<WalkResult>> = Mutex::new(Vec::new());
+    let walk_error: Mutex<Option<ignore::Error>> = Mutex::new(None);
+
+    walker.run(|| {
+        Box::new(|entry| {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    walk_error.lock().unwrap().get_or_insert(error);
+                    return WalkState::Quit;
+                }
+            };
+
+            let path = entry.path();
+
+            let result = match inspect_path(path, options, &filters, cache.as_ref(), keep_content) {
+                Ok(Some(content)) => WalkResult::Include(FileContent {
+                    path: path.to_path_buf(),
+                    content,
+                }),
+                Ok(None) => return WalkState::Continue,
+                Err(error) => WalkResult::Error(error),
+            };
+
+            results.lock().unwrap().push(result);
+            WalkState::Continue
+        })
+    });
+
+    if let Some(error) = walk_error.into_inner().unwrap() {
+        return Err(QuaggaError::Walk(error));
+    }
+
+    let mut results = results.into_inner().unwrap();
+    results.sort_by(|a, b| result_path(a).cmp(&result_path(b)));
+    let mut files = Vec::new();
+
+    for result in results {
+        match result {
+            WalkResult::Include(file) => files.push(file),
+            WalkResult::Error(error) => skip_or_fail(error, options.on_error, skipped)?,
         }
     }
 
+    sort_files(&mut files, options, archives)?;
     Ok(files)
 }
 
+/// The checks of the files found during the walk, created once for all files.
+struct Filters {
+    /// Checks the content against the `contain` and `not_contain` options.
+    matcher: ContentMatcher,
+
+    /// Checks the modification time against the `modified_within`, `newer_than`
+    /// and `older_than` options.
+    modified: ModifiedFilter,
+
+    /// The cache of the archives read during the run.
+    archives: ArchiveCache,
+}
+
+impl Filters {
+    fn new(options: &QuaggaOptions, archives: &ArchiveCache) -> Result<Self, QuaggaError> {
+        Ok(Filters {
+            matcher: ContentMatcher::new(options)?,
+            modified: ModifiedFilter::new(options, archives)?,
+            archives: archives.clone(),
+        })
+    }
+}
+
+/// Returns the path of the file the walk result is about.
+fn result_path(result: &WalkResult) -> Option<&Path> {
+    match result {
+        WalkResult::Include(file) => Some(&file.path),
+        WalkResult::Error(error) => SkippedFile::path_of_error(error),
+    }
+}
+
 /// Setup the `WalkBuilder` with the necessary configurations.
-fn configure_walk_builder(cli: &Cli) -> Result<WalkBuilder, Box<dyn Error>> {
-    let overrides = build_overrides(cli)?;
-    let mut walker_builder = WalkBuilder::new(&cli.root);
+fn configure_walk_builder(options: &QuaggaOptions) -> Result<WalkBuilder, QuaggaError> {
+    let overrides = build_overrides(options)?;
+    let mut walker_builder = WalkBuilder::new(&options.root);
     walker_builder.overrides(overrides);
-    walker_builder.git_ignore(!cli.no_gitignore);
-    walker_builder.max_depth(cli.max_depth);
-    walker_builder.max_filesize(Some(cli.max_filesize));
+    walker_builder.git_ignore(!options.no_gitignore);
+    walker_builder.max_depth(options.max_depth);
     walker_builder.require_git(false); // Apply git-related gitignore rules even if .git directory is missing
-    walker_builder.hidden(!cli.hidden);
-    walker_builder.follow_links(cli.follow_links);
+    walker_builder.hidden(!options.hidden);
+    walker_builder.follow_links(options.follow_links);
 
-    if !cli.no_quagga_ignore {
-        add_quagga_ignore_files(&mut walker_builder, cli.root.clone(), None)?;
+    if !options.no_quagga_ignore {
+        add_quagga_ignore_files(&mut walker_builder, options.root.clone(), None)?;
     }
 
     Ok(walker_builder)
 }
 
 /// Determines whether a path should be included in the output prompt.
+/// The file is read once and the content is used for all the checks.
 ///
 /// # Arguments
 ///
 /// * `path` - The path to evaluate.
-/// * `cli` - Command line arguments.
+/// * `options` - Options for generating the prompt.
+/// * `filters` - Check the content and the modification time of the file.
+/// * `cache` - The cache of binary detection and classification results, if enabled.
+/// * `keep_content` - Whether to read the entire file and return its content.
+///   When false, only the start of the file is read unless the content needs to be matched.
 ///
 /// # Returns
 ///
-/// * `Ok(true)` if the file should be included.
-/// * `Ok(false)` if the file should be skipped.
-/// * `Err<Box<dyn Error>>` if an error occurs during evaluation.
-fn should_include_path(path: &PathBuf, cli: &Cli) -> Result<bool, Box<dyn Error>> {
+/// * `Ok(Some(String))` with the file content if the file should be included.
+///   The content is empty if `keep_content` is false.
+/// * `Ok(None)` if the file should be skipped.
+/// * `Err<QuaggaError>` if an error occurs during evaluation.
+fn inspect_path(
+    path: &Path,
+    options: &QuaggaOptions,
+    filters: &Filters,
+    cache: Option<&FileCache>,
+    keep_content: bool,
+) -> Result<Option<String>, QuaggaError> {
     if !path.is_file() {
-        return Ok(false);
+        return Ok(None);
     }
 
-    if !cli.binary && !is_valid_text_file(path.clone())? {
-        return Ok(false);
+    // Notebooks are checked against `--max-filesize` after they are converted to text
+    let is_notebook = is_notebook(path);
+
+    if !is_notebook
+        && path
+            .metadata()
+            .is_ok_and(|metadata| metadata.len() > options.max_filesize)
+    {
+        return Ok(None);
+    }
+
+    // Check the `--modified-within`, `--newer-than` and `--older-than` options
+    if !filters.modified.is_match(path)? {
+        return Ok(None);
+    }
+
+    // The cache entry is loaded once, and saved only if the checks found new results
+    let cached = cache.map(|cache| cache.load(path)).unwrap_or_default();
+    let mut file = cached;
+    let result = inspect_content(path, options, filters, &mut file, keep_content);
+
+    if let Some(cache) = cache.filter(|_| file != cached) {
+        cache.store(path, file);
+    }
+
+    result
+}
+
+/// Checks the content of the file for `inspect_path`.
+///
+/// # Arguments
+///
+/// * `path` - The path to the file.
+/// * `options` - Options for generating the prompt.
+/// * `filters` - Check the content of the file.
+/// * `file` - The cached results for the file, updated with the new ones.
+/// * `keep_content` - Whether to read the entire file and return its content.
+fn inspect_content(
+    path: &Path,
+    options: &QuaggaOptions,
+    filters: &Filters,
+    file: &mut CachedFile,
+    keep_content: bool,
+) -> Result<Option<String>, QuaggaError> {
+    let matcher = &filters.matcher;
+    let is_notebook = is_notebook(path);
+
+    if !keep_content && matcher.is_empty() && !is_notebook {
+        // Only the start of the file is needed to detect binary, generated and minified files
+        let mut start = FileStart::new(path, options.encoding);
+        let is_included = is_text_file(&mut start, options, file)?
+            && !is_skipped_class(&mut start, options, file)?;
+
+        return Ok(is_included.then(String::new));
+    }
+
+    if !options.binary && file.is_text == Some(false) {
+        return Ok(None);
     }
 
-    // If `--contain` option is used, check if file contains the specified texts
-    if !cli.contain.is_empty() && !file_contains_text(path, &cli.contain, cli.binary)? {
+    let Some(content) = read_text_content(path, options, &filters.archives, file)? else {
+        return Ok(None);
+    };
+
+    let content = convert_notebook(path, content, options.notebook_outputs);
+
+    if is_notebook && content.len() as u64 > options.max_filesize {
+        return Ok(None);
+    }
+
+    // Check the `--skip-generated` and `--skip-minified` options
+    if is_skipped_class(&mut FileStart::from_content(path, &content), options, file)? {
+        return Ok(None);
+    }
+
+    // Check the `--contain`, `--contain-word`, `--contain-regex` and `--not-contain` options
+    if !matcher.is_match(&content) {
+        return Ok(None);
+    }
+
+    if keep_content {
+        Ok(Some(content))
+    } else {
+        Ok(Some(String::new()))
+    }
+}
+
+/// Reads the file and converts it to text, noting in `file` whether it is a text file.
+///
+/// # Returns
+///
+/// * `Ok(Some(String))` with the file content.
+/// * `Ok(None)` if the file is binary and binary files are not included.
+/// * `Err<QuaggaError>` if the file can not be read.
+fn read_text_content(
+    path: &Path,
+    options: &QuaggaOptions,
+    archives: &ArchiveCache,
+    file: &mut CachedFile,
+) -> Result<Option<String>, QuaggaError> {
+    let bytes = read_file_bytes(path, archives)?;
+
+    if !options.binary {
+        let is_text = is_valid_text_content(&bytes, options.encoding);
+        file.is_text = Some(is_text);
+
+        if !is_text {
+            return Ok(None);
+        }
+    }
+
+    let content = bytes_to_text(path.to_path_buf(), bytes, options.binary, options.encoding)?;
+    Ok(Some(content))
+}
+
+/// The start of a file, read when it is first needed, so that the binary detection
+/// and the classification share a single read. The file is classified by its text,
+/// so that files in any encoding are classified the same way whether or not
+/// their whole content is read.
+struct FileStart<'a> {
+    path: &'a Path,
+    encoding: Option<&'static Encoding>,
+    bytes: Option<Vec<u8>>,
+    text: Option<Cow<'a, str>>,
+}
+
+impl<'a> FileStart<'a> {
+    /// Creates the start of the file at `path`, which is read on the first call to `bytes` or `text`.
+    fn new(path: &'a Path, encoding: Option<&'static Encoding>) -> FileStart<'a> {
+        FileStart {
+            path,
+            encoding,
+            bytes: None,
+            text: None,
+        }
+    }
+
+    /// Creates the start of the file from its content that has already been read.
+    fn from_content(path: &'a Path, content: &'a str) -> FileStart<'a> {
+        FileStart {
+            path,
+            encoding: None,
+            bytes: None,
+            text: Some(Cow::Borrowed(content)),
+        }
+    }
+
+    /// Returns the start of the file, reading it if it has not been read yet.
+    fn bytes(&mut self) -> Result<&[u8], QuaggaError> {
+        if self.bytes.is_none() {
+            self.bytes = Some(read_file_start(self.path)?);
+        }
+
+        Ok(self.bytes.as_deref().unwrap_or_default())
+    }
+
+    /// Returns the start of the file converted to UTF-8, reading it if it has not been read yet.
+    fn text(&mut self) -> Result<&str, QuaggaError> {
+        if self.text.is_none() {
+            let encoding = self.encoding;
+            let text = decode_sample(self.bytes()?, encoding);
+            self.text = Some(Cow::Owned(text));
+        }
+
+        Ok(self.text.as_deref().unwrap_or_default())
+    }
+}
+
+/// Checks if the file is a text file by its start, or always returns true
+/// when binary files are included.
+fn is_text_file(
+    start: &mut FileStart,
+    options: &QuaggaOptions,
+    file: &mut CachedFile,
+) -> Result<bool, QuaggaError> {
+    if options.binary {
+        return Ok(true);
+    }
+
+    if let Some(is_text) = file.is_text {
+        return Ok(is_text);
+    }
+
+    let is_text = is_valid_text_content(start.bytes()?, options.encoding);
+    file.is_text = Some(is_text);
+
+    Ok(is_text)
+}
+
+/// Checks if the file is left out of the prompt as generated or minified,
+/// classifying it by its start unless its class is already known.
+///
+/// # Arguments
+///
+/// * `start` - The start of the file.
+/// * `options` - Options for generating the prompt.
+/// * `file` - The cached results for the file, updated with its class.
+fn is_skipped_class(
+    start: &mut FileStart,
+    options: &QuaggaOptions,
+    file: &mut CachedFile,
+) -> Result<bool, QuaggaError> {
+    if !options.skip_generated && !options.skip_minified {
         return Ok(false);
     }
 
-    Ok(true)
+    let file_class = match file.file_class {
+        Some(file_class) => file_class,
+        None => classify_file(start.path, start.text()?),
+    };
+
+    file.file_class = Some(file_class);
+    Ok(file_class.is_skipped(options))
+}
+
+/// Reads the start of the file, long enough for `CLASSIFY_SAMPLE_SIZE` bytes of its text
+/// converted to UTF-8. Text in other encodings, such as UTF-16, takes at most twice as many bytes.
+fn read_file_start(path: &Path) -> Result<Vec<u8>, QuaggaError> {
+    let size = 2 * CLASSIFY_SAMPLE_SIZE;
+    let mut sample = Vec::with_capacity(size);
+
+    File::open(path)
+        .and_then(|file| file.take(size as u64).read_to_end(&mut sample))
+        .map_err(|source| QuaggaError::FileRead {
+            path: path.to_path_buf(),
+            source,
+        })?;
+
+    Ok(sample)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::options::{OnError, SortOrder};
     use crate::test_utils::temp_dir::TempDir;
-    use clap::Parser;
+    use std::fs;
     use std::os::unix::fs as unix_fs;
+    use std::time::{Duration, SystemTime};
 
     #[test]
     fn test_get_all_files() {
@@ -99,10 +481,12 @@ mod tests {
         td.mkfile(".hidden"); // Should be ignored
         td.mkfile("subdir/file3.txt");
 
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -120,10 +504,12 @@ mod tests {
         td.mkfile_with_contents("file2.rs", "println!(\"Hello, world!\");");
         td.mkfile_with_bytes("binary.bin", &[0x00, 0xFF, 0x00, 0xFF]);
 
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -143,10 +529,12 @@ mod tests {
     #[test]
     fn test_get_all_files_with_no_files() {
         let td = TempDir::new().unwrap();
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -155,34 +543,142 @@ mod tests {
 
     #[test]
     fn test_get_all_files_with_nonexistent_directory() {
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.root = PathBuf::from("/path/to/nonexistent/directory");
+        let options = QuaggaOptions {
+            root: PathBuf::from("/path/to/nonexistent/directory"),
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_err());
     }
 
+    /// Creates a file without read permissions.
+    /// Returns `None` if the file is still readable (e.g. when running as root).
+    #[cfg(unix)]
+    fn mkfile_without_read_permissions(td: &TempDir, name: &str) -> Option<PathBuf> {
+        use std::os::unix::fs::PermissionsExt;
+        let file_path = td.mkfile_with_contents(name, "fn main() {}");
+        let mut perms = std::fs::metadata(&file_path).unwrap().permissions();
+        perms.set_mode(0o000); // No permissions
+        std::fs::set_permissions(&file_path, perms).unwrap();
+
+        if std::fs::File::open(&file_path).is_ok() {
+            return None;
+        }
+
+        Some(file_path)
+    }
+
     #[test]
+    #[cfg(unix)]
     fn test_get_all_files_with_file_read_error() {
         let td = TempDir::new().unwrap();
-        let file_path = td.mkfile_with_contents("file1.txt", "fn main() {}");
-
-        // Set the file permissions to simulate a read error (e.g., remove read permissions)
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            let mut perms = std::fs::metadata(&file_path).unwrap().permissions();
-            perms.set_mode(0o000); // No permissions
-            std::fs::set_permissions(&file_path, perms).unwrap();
+
+        if mkfile_without_read_permissions(&td, "file1.txt").is_none() {
+            return; // Permissions are not enforced for this user
         }
 
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            on_error: OnError::Fail,
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
-        assert!(result.is_err());
+        assert!(matches!(result, Err(QuaggaError::FileRead { .. })));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_get_all_files_with_file_read_error_skips_file() {
+        let td = TempDir::new().unwrap();
+        let readable_path = td.mkfile_with_contents("file2.txt", "Hello");
+
+        let Some(unreadable_path) = mkfile_without_read_permissions(&td, "file1.txt") else {
+            return; // Permissions are not enforced for this user
+        };
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            on_error: OnError::Warn,
+            ..Default::default()
+        };
+
+        let mut skipped = Vec::new();
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut skipped).unwrap();
+
+        assert_eq!(files, vec![readable_path]);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, unreadable_path);
+    }
+
+    #[test]
+    fn test_get_all_files_skips_generated_and_minified_files() {
+        let td = TempDir::new().unwrap();
+        let main = td.mkfile_with_contents("main.rs", "fn main() {}");
+        let lock = td.mkfile_with_contents("Cargo.lock", "version = 3");
+        let generated = td.mkfile_with_contents("api.rs", "// @generated\npub fn api() {}");
+        let minified = td.mkfile_with_contents("app.js", &"var a=1,b=2;".repeat(200));
+
+        let mut options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
+
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+        assert_eq!(files, vec![main.clone()]);
+
+        // Classified the same way when the content is read
+        options.contain = vec!["a".to_string()];
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+        assert_eq!(files, vec![main.clone()]);
+
+        options.contain = Vec::new();
+        options.skip_generated = false;
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+        assert_eq!(files, vec![lock.clone(), generated, main.clone()]);
+
+        options.skip_minified = false;
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+        assert_eq!(files.len(), 4);
+        assert!(files.contains(&minified));
+    }
+
+    #[test]
+    fn test_get_all_files_with_contain_read_error() {
+        let td = TempDir::new().unwrap();
+
+        // Valid UTF-8 at the start, so the file is not detected as binary
+        let mut bytes = "a".repeat(2000).into_bytes();
+        bytes.extend_from_slice(&[0x00, 0xC0, 0xC1]);
+        let invalid_path = td.mkfile_with_bytes("invalid.txt", &bytes);
+
+        let valid_path = td.mkfile_with_contents("valid.txt", "todo");
+
+        let mut options = QuaggaOptions {
+            root: td.path_buf(),
+            contain: vec!["todo".to_string()],
+            on_error: OnError::Skip,
+            ..Default::default()
+        };
+
+        let mut skipped = Vec::new();
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut skipped).unwrap();
+
+        assert_eq!(files, vec![valid_path]);
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, invalid_path);
+        assert_eq!(
+            skipped[0].reason,
+            "stream did not contain text in a known encoding"
+        );
+
+        options.on_error = OnError::Fail;
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
+
+        assert!(matches!(result, Err(QuaggaError::FileRead { .. })));
     }
 
     #[test]
@@ -192,10 +688,12 @@ mod tests {
         td.mkfile_with_contents("file2.md", "World!");
         td.mkfile_with_contents(".quagga_ignore", "*.md");
 
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -211,10 +709,13 @@ mod tests {
         td.mkfile_with_contents("file2.md", "World!");
         td.mkfile_with_contents(".quagga_ignore", "*.md");
 
-        let mut cli = Cli::parse_from(&["test", "--no-quagga-ignore"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            no_quagga_ignore: true,
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -229,15 +730,80 @@ mod tests {
         let file1 = td.mkfile_with_contents("file1.txt", "This is a test file.");
         td.mkfile_with_contents("file2.txt", "Another sample.");
 
-        let mut cli = Cli::parse_from(&["test", "--contain", "test"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            contain: vec!["test".to_string()],
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli).unwrap();
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
 
         assert_eq!(result.len(), 1);
         assert_eq!(result[0], file1);
     }
 
+    #[test]
+    fn test_get_all_files_with_contain_word_and_not_contain() {
+        let td = TempDir::new().unwrap();
+        let file1 = td.mkfile_with_contents("file1.rs", "unsafe { x }");
+        td.mkfile_with_contents("file2.rs", "// SAFETY: checked\nunsafe { x }");
+        td.mkfile_with_contents("file3.rs", "fn unsafely() {}");
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            contain_word: vec!["unsafe".to_string()],
+            not_contain: vec!["// SAFETY:".to_string()],
+            ..Default::default()
+        };
+
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+
+        assert_eq!(result, vec![file1]);
+    }
+
+    #[test]
+    fn test_get_all_files_with_invalid_contain_regex() {
+        let td = TempDir::new().unwrap();
+        td.mkfile("file1.txt");
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            contain_regex: vec!["(".to_string()],
+            ..Default::default()
+        };
+
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
+
+        assert!(matches!(result, Err(QuaggaError::InvalidRegex { .. })));
+    }
+
+    #[test]
+    fn test_get_all_files_modified_within_sorted_by_mtime() {
+        let td = TempDir::new().unwrap();
+        let now = SystemTime::now();
+        let hour = Duration::from_secs(60 * 60);
+
+        for (name, age) in [("a.txt", 2), ("b.txt", 1), ("c.txt", 72)] {
+            let path = td.mkfile(name);
+            let file = std::fs::File::options().write(true).open(path).unwrap();
+            file.set_modified(now - age * hour).unwrap();
+        }
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            modified_within: Some(24 * hour),
+            sort: Some(SortOrder::Mtime),
+            ..Default::default()
+        };
+
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+
+        assert_eq!(
+            result,
+            vec![td.path().join("b.txt"), td.path().join("a.txt")]
+        );
+    }
+
     #[test]
     fn test_get_all_files_respect_gitingore() {
         let td = TempDir::new().unwrap();
@@ -245,10 +811,12 @@ mod tests {
         td.mkfile_with_contents("file2.md", "World!");
         td.mkfile_with_contents(".gitignore", "*.md");
 
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -263,10 +831,13 @@ mod tests {
         td.mkfile_with_contents("file2.md", "World!");
         td.mkfile_with_contents(".gitignore", "*.md");
 
-        let mut cli = Cli::parse_from(&["test", "--no-gitignore"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            no_gitignore: true,
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -284,10 +855,13 @@ mod tests {
         td.mkdir("dir1/dir2");
         td.mkfile("dir1/dir2/file2.txt");
 
-        let mut cli = Cli::parse_from(&["test", "--max-depth", "2"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            max_depth: Some(2),
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -303,10 +877,13 @@ mod tests {
         td.mkfile_with_contents("file_five_bytes.txt", "12345");
 
         // Set the maximum file size to 4 bytes
-        let mut cli = Cli::parse_from(&["test", "--max-filesize", "4"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            max_filesize: 4,
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -315,35 +892,138 @@ mod tests {
     }
 
     #[test]
-    fn test_should_include_path_ignore_binary_files() {
+    fn test_inspect_path_ignore_binary_files() {
         let td = TempDir::new().unwrap();
         let text_file = td.mkfile_with_contents("file.txt", "Hello");
         let binary_file_path: PathBuf =
             td.mkfile_with_bytes("binary.bin", &[0x00, 0xFF, 0x00, 0xFF]);
 
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.root = td.path_buf();
-
-        let result_text = should_include_path(&text_file, &cli).unwrap();
-        let result_binary = should_include_path(&binary_file_path, &cli).unwrap();
-
-        assert!(result_text);
-        assert!(!result_binary);
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
+
+        let result_text = inspect_path(
+            &text_file,
+            &options,
+            &Filters::new(&options, &ArchiveCache::default()).unwrap(),
+            None,
+            false,
+        )
+        .unwrap();
+        let result_binary = inspect_path(
+            &binary_file_path,
+            &options,
+            &Filters::new(&options, &ArchiveCache::default()).unwrap(),
+            None,
+            false,
+        )
+        .unwrap();
+
+        assert_eq!(result_text, Some(String::new()));
+        assert_eq!(result_binary, None);
+
+        let result_text = inspect_path(
+            &text_file,
+            &options,
+            &Filters::new(&options, &ArchiveCache::default()).unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+        let result_binary = inspect_path(
+            &binary_file_path,
+            &options,
+            &Filters::new(&options, &ArchiveCache::default()).unwrap(),
+            None,
+            true,
+        )
+        .unwrap();
+
+        assert_eq!(result_text, Some("Hello".to_string()));
+        assert_eq!(result_binary, None);
     }
 
     #[test]
-    fn test_should_include_path_accept_binary_with_cli_override() {
+    fn test_inspect_path_accept_binary_with_cli_override() {
         let td: TempDir = TempDir::new().unwrap();
 
         let binary_file_path: PathBuf =
             td.mkfile_with_bytes("binary.bin", &[0x00, 0xFF, 0x00, 0xFF]);
 
-        let mut cli = Cli::parse_from(&["test", "--binary"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            binary: true,
+            ..Default::default()
+        };
+
+        let result_binary = inspect_path(
+            &binary_file_path,
+            &options,
+            &Filters::new(&options, &ArchiveCache::default()).unwrap(),
+            None,
+            false,
+        )
+        .unwrap();
+
+        assert!(result_binary.is_some());
+    }
+
+    #[test]
+    fn test_get_all_file_contents() {
+        let td = TempDir::new().unwrap();
+        td.mkdir("b");
+        let path1 = td.mkfile_with_contents("b/file.txt", "B");
+        let path2 = td.mkfile_with_contents("a.txt", "A");
+        let path3 = td.mkfile_with_contents("c.txt", "C todo");
+        td.mkfile_with_bytes("binary.bin", &[0x00, 0xFF, 0x00, 0xFF]);
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
 
-        let result_binary = should_include_path(&binary_file_path, &cli).unwrap();
+        let files =
+            get_all_file_contents(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
 
-        assert!(result_binary);
+        let paths: Vec<PathBuf> = files.iter().map(|file| file.path.clone()).collect();
+        let contents: Vec<&str> = files.iter().map(|file| file.content.as_str()).collect();
+
+        assert_eq!(paths, vec![path2, path1, path3.clone()]);
+        assert_eq!(contents, vec!["A", "B", "C todo"]);
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            contain: vec!["todo".to_string()],
+            ..Default::default()
+        };
+
+        let files =
+            get_all_file_contents(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].path, path3);
+        assert_eq!(files[0].content, "C todo");
+    }
+
+    #[test]
+    fn test_get_all_files_is_sorted() {
+        let td = TempDir::new().unwrap();
+
+        let mut expected: Vec<PathBuf> = (0..50)
+            .map(|i| td.mkfile(format!("file{}.txt", i)))
+            .collect();
+
+        expected.sort();
+
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
+
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+
+        assert_eq!(files, expected);
     }
 
     #[test]
@@ -352,10 +1032,13 @@ mod tests {
         td.mkfile("file.txt");
         td.mkfile(".hidden");
 
-        let mut cli = Cli::parse_from(&["test", "--hidden"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            hidden: true,
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli);
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new());
 
         assert!(result.is_ok());
         let files = result.unwrap();
@@ -379,11 +1062,13 @@ mod tests {
         let symlink_path = td.path().join("symlink_dir");
         unix_fs::symlink(&original_dir, &symlink_path).unwrap();
 
-        let mut cli = Cli::parse_from(&["quagga"]);
-        cli.root = td.path_buf();
-        cli.follow_links = true;
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            follow_links: true,
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli).unwrap();
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
 
         assert_eq!(result.len(), 1);
         td.assert_contains(&result, "symlink_dir/file3.txt"); // symlinked file should be included
@@ -404,11 +1089,55 @@ mod tests {
         let symlink_path = td.path().join("symlink_dir");
         unix_fs::symlink(&original_dir, &symlink_path).unwrap();
 
-        let mut cli = Cli::parse_from(&["quagga"]);
-        cli.root = td.path_buf();
+        let options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
 
-        let result = get_all_files(&cli).unwrap();
+        let result = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
 
         assert_eq!(result.len(), 0);
     }
+
+    #[test]
+    fn test_get_all_files_skips_utf16_generated_file() {
+        let td = TempDir::new().unwrap();
+        let main = td.mkfile_with_contents("main.cs", "class Program {}");
+        let text = "// <auto-generated>\n// DO NOT EDIT\nclass Generated {}\n";
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend(text.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        td.mkfile_with_bytes("gen.cs", &bytes);
+
+        let mut options = QuaggaOptions {
+            root: td.path_buf(),
+            ..Default::default()
+        };
+
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+        assert_eq!(files, vec![main.clone()]);
+
+        // Classified the same way when the content is read
+        options.contain = vec!["class".to_string()];
+        let files = get_all_files(&options, &ArchiveCache::default(), &mut Vec::new()).unwrap();
+        assert_eq!(files, vec![main]);
+    }
+
+    #[test]
+    fn test_file_start_is_read_once() {
+        let td = TempDir::new().unwrap();
+        let path = td.mkfile_with_contents("file.txt", &"fn main() {}\n".repeat(2000));
+        let options = QuaggaOptions {
+            skip_minified: true,
+            ..Default::default()
+        };
+
+        let mut start = FileStart::new(&path, None);
+        assert!(is_text_file(&mut start, &options, &mut CachedFile::default()).unwrap());
+        assert_eq!(start.bytes().unwrap().len(), 2 * CLASSIFY_SAMPLE_SIZE);
+
+        // The classification uses the sample read by the binary detection
+        fs::write(&path, [0x00, 0xFF]).unwrap();
+        assert!(!is_skipped_class(&mut start, &options, &mut CachedFile::default()).unwrap());
+        assert_eq!(start.bytes().unwrap().len(), 2 * CLASSIFY_SAMPLE_SIZE);
+    }
 }
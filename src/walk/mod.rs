@@ -1,5 +1,8 @@
+pub mod archive_walker;
 pub mod binary_detector;
+pub mod classifier;
 pub mod contain;
 pub mod file_walker;
+pub mod modified;
 pub mod quagga_ignore;
 pub mod walk_overrides;
@@ -0,0 +1,375 @@
+use crate::error::QuaggaError;
+use crate::file::archive::{split_entry_path, ArchiveCache};
+use crate::options::QuaggaOptions;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// A point in time that the modification times of files are compared with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeReference {
+    /// A fixed time, e.g. `2025-05-12` or `2025-05-12 14:30`, in the local time zone.
+    Time(SystemTime),
+
+    /// A time relative to now, e.g. `2d` for two days ago.
+    Age(Duration),
+
+    /// The modification time of a file.
+    File(PathBuf),
+}
+
+impl TimeReference {
+    /// Converts the reference to a point in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time, used for `TimeReference::Age`.
+    /// * `archives` - The cache of the archives read during the run.
+    ///
+    /// # Returns
+    ///
+    /// The point in time, or `QuaggaError::FileRead` if the modification time
+    /// of the reference file can not be read.
+    pub fn resolve(
+        &self,
+        now: SystemTime,
+        archives: &ArchiveCache,
+    ) -> Result<SystemTime, QuaggaError> {
+        match self {
+            TimeReference::Time(time) => Ok(*time),
+            TimeReference::Age(age) => Ok(now.checked_sub(*age).unwrap_or(SystemTime::UNIX_EPOCH)),
+            TimeReference::File(path) => modified_time(path, archives),
+        }
+    }
+}
+
+impl FromStr for TimeReference {
+    type Err = String;
+
+    /// Parses a date, an age or a path to a file, in this order.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if let Some(time) = parse_date(text) {
+            return Ok(TimeReference::Time(time));
+        }
+
+        if let Ok(age) = parse_age(text) {
+            return Ok(TimeReference::Age(age));
+        }
+
+        if text.is_empty() {
+            return Err("expected a date, an age or a path to a file".to_string());
+        }
+
+        Ok(TimeReference::File(PathBuf::from(text)))
+    }
+}
+
+/// Parses an age, which is a number followed by a unit: `s` (seconds), `m` (minutes),
+/// `h` (hours), `d` (days) or `w` (weeks), e.g. `2d`.
+///
+/// # Returns
+///
+/// The duration, or a message describing the expected format.
+pub fn parse_age(text: &str) -> Result<Duration, String> {
+    let error = || {
+        format!(
+            "invalid age '{}', expected a number and a unit: s, m, h, d or w, e.g. 2d",
+            text
+        )
+    };
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    let (number, unit) = text.split_at(digits);
+    let number: u64 = number.parse().map_err(|_| error())?;
+
+    let seconds_per_unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return Err(error()),
+    };
+
+    number
+        .checked_mul(seconds_per_unit)
+        .map(Duration::from_secs)
+        .ok_or_else(error)
+}
+
+/// Parses a date in the local time zone: `2025-05-12`, `2025-05-12 14:30`,
+/// `2025-05-12T14:30:00` or RFC 3339 with a time zone offset.
+fn parse_date(text: &str) -> Option<SystemTime> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Some(time.into());
+    }
+
+    let date_time = [
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%d %H:%M",
+        "%Y-%m-%dT%H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+    .or_else(|| {
+        NaiveDate::parse_from_str(text, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+    })?;
+
+    Local
+        .from_local_datetime(&date_time)
+        .earliest()
+        .map(SystemTime::from)
+}
+
+/// Returns the modification time of a file, or of a file inside an archive.
+pub fn modified_time(path: &Path, archives: &ArchiveCache) -> Result<SystemTime, QuaggaError> {
+    let modified = match split_entry_path(path) {
+        Some((archive, name)) => archives.find_entry(&archive, &name).and_then(|entry| {
+            entry.modified.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    "no modification time in the archive",
+                )
+            })
+        }),
+        None => path.metadata().and_then(|metadata| metadata.modified()),
+    };
+
+    modified.map_err(|source| QuaggaError::FileRead {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Checks the modification times of files against the `modified_within`,
+/// `newer_than` and `older_than` options.
+#[derive(Debug, Clone, Default)]
+pub struct ModifiedFilter {
+    /// Files modified at or after this time are included.
+    newer_than: Option<SystemTime>,
+
+    /// Files modified before this time are included.
+    older_than: Option<SystemTime>,
+
+    /// The cache of the archives read during the run.
+    archives: ArchiveCache,
+}
+
+impl ModifiedFilter {
+    /// Creates a filter from the options, resolving the reference times.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - Options for generating the prompt.
+    /// * `archives` - The cache of the archives read during the run.
+    ///
+    /// # Returns
+    ///
+    /// The filter, or `QuaggaError::FileRead` if the modification time of a reference file
+    /// can not be read.
+    pub fn new(options: &QuaggaOptions, archives: &ArchiveCache) -> Result<Self, QuaggaError> {
+        let now = SystemTime::now();
+        let within = options.modified_within.map(TimeReference::Age);
+
+        let newer_than = [within.as_ref(), options.newer_than.as_ref()]
+            .into_iter()
+            .flatten()
+            .map(|reference| reference.resolve(now, archives))
+            .collect::<Result<Vec<SystemTime>, QuaggaError>>()?
+            .into_iter()
+            .max();
+
+        let older_than = match &options.older_than {
+            Some(reference) => Some(reference.resolve(now, archives)?),
+            None => None,
+        };
+
+        Ok(ModifiedFilter {
+            newer_than,
+            older_than,
+            archives: archives.clone(),
+        })
+    }
+
+    /// Returns true if there are no time limits and all files match.
+    pub fn is_empty(&self) -> bool {
+        self.newer_than.is_none() && self.older_than.is_none()
+    }
+
+    /// Checks if the modification time of the file is within the limits.
+    ///
+    /// # Returns
+    ///
+    /// Whether the file matches, or `QuaggaError::FileRead` if its modification time
+    /// can not be read.
+    pub fn is_match(&self, path: &Path) -> Result<bool, QuaggaError> {
+        if self.is_empty() {
+            return Ok(true);
+        }
+
+        let modified = modified_time(path, &self.archives)?;
+        Ok(self.is_time_match(modified))
+    }
+
+    /// Checks if the modification time is within the limits.
+    pub fn is_time_match(&self, modified: SystemTime) -> bool {
+        self.newer_than.is_none_or(|limit| modified >= limit)
+            && self.older_than.is_none_or(|limit| modified < limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::temp_dir::TempDir;
+    use std::fs::File;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn set_modified(path: &Path, modified: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_age("30m"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_age("12h"), Ok(Duration::from_secs(12 * 60 * 60)));
+        assert_eq!(parse_age("2d"), Ok(2 * DAY));
+        assert_eq!(parse_age("1w"), Ok(7 * DAY));
+    }
+
+    #[test]
+    fn test_parse_age_invalid() {
+        assert!(parse_age("2").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("2y").is_err());
+        assert!(parse_age("-2d").is_err());
+        assert!(parse_age("").is_err());
+    }
+
+    #[test]
+    fn test_time_reference_from_str() {
+        let date = Local.with_ymd_and_hms(2025, 5, 12, 0, 0, 0).unwrap();
+        let date_time = Local.with_ymd_and_hms(2025, 5, 12, 14, 30, 0).unwrap();
+
+        assert_eq!("2025-05-12".parse(), Ok(TimeReference::Time(date.into())));
+
+        assert_eq!(
+            "2025-05-12 14:30".parse(),
+            Ok(TimeReference::Time(date_time.into()))
+        );
+
+        assert_eq!(
+            "2025-05-12T14:30:00".parse(),
+            Ok(TimeReference::Time(date_time.into()))
+        );
+
+        assert_eq!(
+            "2025-05-12T14:30:00Z".parse(),
+            Ok(TimeReference::Time(
+                DateTime::parse_from_rfc3339("2025-05-12T14:30:00Z")
+                    .unwrap()
+                    .into()
+            ))
+        );
+
+        assert_eq!("2d".parse(), Ok(TimeReference::Age(2 * DAY)));
+
+        assert_eq!(
+            "src/main.rs".parse(),
+            Ok(TimeReference::File(PathBuf::from("src/main.rs")))
+        );
+    }
+
+    #[test]
+    fn test_time_reference_resolve_file() {
+        let td = TempDir::new().unwrap();
+        let path = td.mkfile("reference.txt");
+        let modified = SystemTime::UNIX_EPOCH + 1000 * DAY;
+        set_modified(&path, modified);
+
+        let reference = TimeReference::File(path);
+
+        assert_eq!(
+            reference
+                .resolve(SystemTime::now(), &ArchiveCache::default())
+                .unwrap(),
+            modified
+        );
+    }
+
+    #[test]
+    fn test_time_reference_resolve_missing_file() {
+        let td = TempDir::new().unwrap();
+        let reference = TimeReference::File(td.path().join("missing.txt"));
+
+        let result = reference.resolve(SystemTime::now(), &ArchiveCache::default());
+
+        assert!(matches!(result, Err(QuaggaError::FileRead { .. })));
+    }
+
+    #[test]
+    fn test_modified_filter() {
+        let td = TempDir::new().unwrap();
+        let now = SystemTime::now();
+        let recent = td.mkfile("recent.txt");
+        let old = td.mkfile("old.txt");
+        let ancient = td.mkfile("ancient.txt");
+        set_modified(&recent, now - DAY / 2);
+        set_modified(&old, now - 3 * DAY);
+        set_modified(&ancient, now - 30 * DAY);
+
+        let options = QuaggaOptions {
+            modified_within: Some(2 * DAY),
+            ..Default::default()
+        };
+
+        let filter = ModifiedFilter::new(&options, &ArchiveCache::default()).unwrap();
+        assert!(filter.is_match(&recent).unwrap());
+        assert!(!filter.is_match(&old).unwrap());
+
+        let options = QuaggaOptions {
+            newer_than: Some(TimeReference::Age(10 * DAY)),
+            older_than: Some(TimeReference::File(recent.clone())),
+            ..Default::default()
+        };
+
+        let filter = ModifiedFilter::new(&options, &ArchiveCache::default()).unwrap();
+        assert!(!filter.is_match(&recent).unwrap());
+        assert!(filter.is_match(&old).unwrap());
+        assert!(!filter.is_match(&ancient).unwrap());
+    }
+
+    #[test]
+    fn test_modified_filter_uses_latest_lower_limit() {
+        let options = QuaggaOptions {
+            modified_within: Some(10 * DAY),
+            newer_than: Some(TimeReference::Age(DAY)),
+            ..Default::default()
+        };
+
+        let filter = ModifiedFilter::new(&options, &ArchiveCache::default()).unwrap();
+
+        assert!(filter.is_time_match(SystemTime::now()));
+        assert!(!filter.is_time_match(SystemTime::now() - 2 * DAY));
+    }
+
+    #[test]
+    fn test_modified_filter_empty() {
+        let filter =
+            ModifiedFilter::new(&QuaggaOptions::default(), &ArchiveCache::default()).unwrap();
+
+        assert!(filter.is_empty());
+        assert!(filter.is_match(Path::new("missing.txt")).unwrap());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::error::QuaggaError;
 use home::home_dir;
 use ignore::WalkBuilder;
 use std::path::PathBuf;
@@ -12,12 +13,12 @@ use std::path::PathBuf;
 ///
 /// # Returns
 ///
-/// * `Result<(), Box<dyn std::error::Error>>` - Ok if the files were successfully added, Err otherwise.
+/// * `Result<(), QuaggaError>` - Ok if the files were successfully added, Err otherwise.
 pub fn add_quagga_ignore_files(
     builder: &mut WalkBuilder,
     project_root: PathBuf,
     home_dir_override: Option<PathBuf>,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), QuaggaError> {
     add_home_ignore_file(builder, home_dir_override)?;
     add_project_ignore_file(builder, project_root)?;
     Ok(())
@@ -32,11 +33,11 @@ pub fn add_quagga_ignore_files(
 ///
 /// # Returns
 ///
-/// * `Result<(), Box<dyn std::error::Error>>` - Ok if the file was processed successfully, Err otherwise.
+/// * `Result<(), QuaggaError>` - Ok if the file was processed successfully, Err otherwise.
 fn add_home_ignore_file(
     builder: &mut WalkBuilder,
     home_dir_override: Option<PathBuf>,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), QuaggaError> {
     let home_directory = if let Some(dir) = home_dir_override {
         dir
     } else if let Some(dir) = home_dir() {
@@ -64,11 +65,11 @@ fn add_home_ignore_file(
 ///
 /// # Returns
 ///
-/// * `Result<(), Box<dyn std::error::Error>>` - Ok if the file was processed successfully, Err otherwise.
+/// * `Result<(), QuaggaError>` - Ok if the file was processed successfully, Err otherwise.
 fn add_project_ignore_file(
     builder: &mut WalkBuilder,
     project_root: PathBuf,
-) -> Result<(), Box<dyn std::error::Error>> {
+) -> Result<(), QuaggaError> {
     let project_ignore = project_root.join(".quagga_ignore");
 
     if project_ignore.exists() {
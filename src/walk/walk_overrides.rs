@@ -1,25 +1,25 @@
-use crate::cli::Cli;
+use crate::error::QuaggaError;
+use crate::options::QuaggaOptions;
 use ignore::{overrides::Override, overrides::OverrideBuilder};
-use std::error::Error;
 
-/// Builds an `Override` object based on the command-line include and exclude patterns.
+/// Builds an `Override` object based on the include and exclude patterns.
 ///
 /// # Arguments
 ///
-/// * `cli` - A reference to the parsed command-line arguments.
+/// * `options` - Options for generating the prompt.
 ///
 /// # Returns
 ///
 /// * `Ok(Override)` - The constructed `Override` object.
-/// * `Err(Box<dyn Error>)` - If there was an error building the overrides.
+/// * `Err(QuaggaError)` - If there was an error building the overrides.
 ///
 /// # Errors
 ///
 /// This function returns an error if any of the patterns provided are invalid.
-pub fn build_overrides(cli: &Cli) -> Result<Override, Box<dyn Error>> {
-    let mut builder = OverrideBuilder::new(&cli.root);
-    add_include_patterns(&mut builder, &cli.include)?;
-    add_exclude_patterns(&mut builder, &cli.exclude)?;
+pub fn build_overrides(options: &QuaggaOptions) -> Result<Override, QuaggaError> {
+    let mut builder = OverrideBuilder::new(&options.root);
+    add_include_patterns(&mut builder, &options.include)?;
+    add_exclude_patterns(&mut builder, &options.exclude)?;
     let overrides = builder.build()?;
     Ok(overrides)
 }
@@ -34,11 +34,11 @@ pub fn build_overrides(cli: &Cli) -> Result<Override, Box<dyn Error>> {
 /// # Returns
 ///
 /// * `Ok(())` if all patterns were added successfully.
-/// * `Err(Box<dyn Error>)` if any pattern is invalid.
+/// * `Err(QuaggaError)` if any pattern is invalid.
 fn add_include_patterns(
     builder: &mut OverrideBuilder,
     includes: &[String],
-) -> Result<(), Box<dyn Error>> {
+) -> Result<(), QuaggaError> {
     for pattern in includes {
         builder.add(pattern)?;
     }
@@ -55,11 +55,11 @@ fn add_include_patterns(
 /// # Returns
 ///
 /// * `Ok(())` if all patterns were added successfully.
-/// * `Err(Box<dyn Error>)` if any pattern is invalid.
+/// * `Err(QuaggaError)` if any pattern is invalid.
 fn add_exclude_patterns(
     builder: &mut OverrideBuilder,
     excludes: &[String],
-) -> Result<(), Box<dyn Error>> {
+) -> Result<(), QuaggaError> {
     for pattern in excludes {
         // Prefix with '!' to negate the pattern
         let negated_pattern = format!("!{}", pattern);
@@ -71,18 +71,18 @@ fn add_exclude_patterns(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cli::Cli;
-    use clap::Parser;
     use std::path::PathBuf;
 
     #[test]
     fn test_build_overrides_with_include_and_exclude() {
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.include = vec!["*.rs".to_string()];
-        cli.exclude = vec!["tests/*".to_string()];
-        cli.root = PathBuf::from(".");
+        let options = QuaggaOptions {
+            include: vec!["*.rs".to_string()],
+            exclude: vec!["tests/*".to_string()],
+            root: PathBuf::from("."),
+            ..Default::default()
+        };
 
-        let overrides = build_overrides(&cli).unwrap();
+        let overrides = build_overrides(&options).unwrap();
 
         assert!(overrides.matched("src/main.rs", false).is_whitelist());
 
@@ -122,11 +122,13 @@ mod tests {
 
     #[test]
     fn test_invalid_pattern() {
-        let mut cli = Cli::parse_from(&["test"]);
-        cli.include = vec!["**/*".to_string()];
-        cli.exclude = vec!["[".to_string()]; // Invalid pattern
+        let options = QuaggaOptions {
+            include: vec!["**/*".to_string()],
+            exclude: vec!["[".to_string()], // Invalid pattern
+            ..Default::default()
+        };
 
-        let result = build_overrides(&cli);
+        let result = build_overrides(&options);
         assert!(result.is_err());
     }
 }
@@ -1,6 +1,8 @@
 use expectrl::spawn;
 use quagga::test_utils::temp_dir::TempDir;
-use std::io::Read;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::thread::{self, JoinHandle};
 
 /// Constructs a command using the `quagga` binary and the provided parameters,
 /// spawns it in a terminal emulator, and returns the captured output.
@@ -35,6 +37,25 @@ pub fn run_in_terminal(params: String) -> String {
     output.replace("\r\n", "\n") // Normalize line endings
 }
 
+/// Checks if the tests can use the system clipboard: not in CI, and with a display server on Linux.
+/// The tests copying with OSC 52 do not need it, since they run in a terminal emulator.
+pub fn has_system_clipboard() -> bool {
+    if std::env::var("CI").is_ok() {
+        println!("Skipping clipboard test in CI environment");
+        return false;
+    }
+
+    let has_display = cfg!(not(target_os = "linux"))
+        || std::env::var_os("DISPLAY").is_some()
+        || std::env::var_os("WAYLAND_DISPLAY").is_some();
+
+    if !has_display {
+        println!("Skipping clipboard test without a display server");
+    }
+
+    has_display
+}
+
 /// Adds a custom template to the provided temporary directory.
 pub fn add_template(td: &TempDir) {
     let custom_template = r#"
@@ -55,3 +76,54 @@ pub fn add_template(td: &TempDir) {
 
     td.mkfile_with_contents(".quagga_template", custom_template);
 }
+
+/// Starts a server that accepts a single HTTP request and replies with the given response.
+///
+/// # Arguments
+///
+/// * `response` - The full HTTP response, including the status line and headers.
+///
+/// # Returns
+///
+/// The URL of the server, and a handle that returns the headers and the body of the request.
+pub fn mock_http_server(response: String) -> (String, JoinHandle<(String, String)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Failed to start server");
+    let url = format!("http://{}", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let (stream, _) = listener.accept().expect("Failed to accept request");
+        let mut reader = BufReader::new(stream);
+        let mut headers = String::new();
+
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+
+            if line == "\r\n" || line.is_empty() {
+                break;
+            }
+
+            headers.push_str(&line);
+        }
+
+        let length = headers
+            .lines()
+            .find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("content-length")
+                    .then(|| value.trim().parse::<usize>().unwrap())
+            })
+            .unwrap_or(0);
+
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).unwrap();
+
+        let mut stream = reader.into_inner();
+        stream.write_all(response.as_bytes()).unwrap();
+        stream.flush().unwrap();
+
+        (headers, String::from_utf8(body).unwrap())
+    });
+
+    (url, handle)
+}